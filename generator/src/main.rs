@@ -4,7 +4,7 @@
 use kserde::*;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write as FmtWrite;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
 struct Property {
@@ -67,11 +67,14 @@ struct Schema {
     enum_values: Vec<EnumValue>,
 }
 
-struct Parser {}
+struct Parser {
+    /// Directories searched, in order, to resolve `$ref`s.
+    search_paths: Vec<PathBuf>,
+}
 
 impl Parser {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(search_paths: Vec<PathBuf>) -> Self {
+        Self { search_paths }
     }
 
     fn extend_from_schema(&mut self, schema: &mut Schema, thing: &Thing) {
@@ -79,8 +82,15 @@ impl Parser {
 
         if let Some(ref_) = thing.get("$ref") {
             let ref_ = ref_.item.string().unwrap();
-            let source = std::fs::read_to_string(Path::new("schema").join(&**ref_))
+            // Extension schemas reference the core schemas by file name alone,
+            // so fall back to later search paths if the file isn't found.
+            let path = self
+                .search_paths
+                .iter()
+                .map(|p| p.join(&**ref_))
+                .find(|p| p.exists())
                 .expect("Could not find file");
+            let source = std::fs::read_to_string(path).expect("Could not find file");
             let json = Thing::from_json(&source).expect("Could not parse JSON");
             self.extend_from_schema(schema, &json);
             return;
@@ -472,10 +482,57 @@ enum RustType {
     //Enum { name: String, members: Vec<String> },
     Enum(RustEnum),
     KSerdeOwnedThing,
+    /// An index into a list of the named type.
+    Index(String),
+    /// The attributes of a mesh primitive or morph target, keyed by `Semantic`.
+    Attributes,
     Unimplemented,
 }
 
 impl RustType {
+    /// Whether the generated type implements `Copy`.
+    fn is_copy(&self) -> bool {
+        match self {
+            RustType::USIZE | RustType::Boolean | RustType::F32 | RustType::Index(_) => true,
+            RustType::Array(_, inner) | RustType::Option(inner) => inner.is_copy(),
+            _ => false,
+        }
+    }
+
+    /// Replaces the integers in this type with indices of `target`.
+    fn with_index(self, target: &str) -> RustType {
+        match self {
+            RustType::USIZE => RustType::Index(target.to_string()),
+            RustType::Vec(inner) => RustType::Vec(Box::new(inner.with_index(target))),
+            RustType::HashMap(key, value) => {
+                RustType::HashMap(key, Box::new(value.with_index(target)))
+            }
+            RustType::Option(inner) => RustType::Option(Box::new(inner.with_index(target))),
+            _ => panic!("Only integers can be indices"),
+        }
+    }
+
+    /// Replaces the maps of accessor indices in this type with `Attributes`.
+    fn with_attributes(self) -> RustType {
+        match self {
+            RustType::HashMap(..) => RustType::Attributes,
+            RustType::Vec(inner) => RustType::Vec(Box::new(inner.with_attributes())),
+            RustType::Option(inner) => RustType::Option(Box::new(inner.with_attributes())),
+            _ => panic!("Only maps can be attributes"),
+        }
+    }
+
+    /// Whether this type contains a `HashMap` or Json value, whose keys have no fixed order.
+    fn contains_map(&self) -> bool {
+        match self {
+            RustType::HashMap(..) | RustType::KSerdeOwnedThing => true,
+            RustType::Vec(inner) | RustType::Option(inner) | RustType::Array(_, inner) => {
+                inner.contains_map()
+            }
+            _ => false,
+        }
+    }
+
     fn type_name(&self) -> String {
         match self {
             RustType::String => "String".to_string(),
@@ -501,6 +558,8 @@ impl RustType {
             }
             RustType::Enum(s) => s.name.clone(),
             RustType::KSerdeOwnedThing => "ThingOwned".to_string(),
+            RustType::Index(target) => format!("Index<{}>", target),
+            RustType::Attributes => "Attributes".to_string(),
             RustType::Unimplemented => "UNIMPLEMENTED".to_string(),
         }
     }
@@ -509,6 +568,9 @@ impl RustType {
 struct RustGenerator {
     rust_types: Vec<(String, RustType)>,
     rust_types_set: HashSet<String>,
+    /// Types defined elsewhere (the core glTF types when generating an extension)
+    /// that should be referenced instead of generated again.
+    external_types: HashMap<String, RustType>,
 }
 
 impl<'a> RustGenerator {
@@ -516,6 +578,14 @@ impl<'a> RustGenerator {
         Self {
             rust_types: Vec::new(),
             rust_types_set: HashSet::new(),
+            external_types: HashMap::new(),
+        }
+    }
+
+    pub fn with_external_types(external_types: HashMap<String, RustType>) -> Self {
+        Self {
+            external_types,
+            ..Self::new()
         }
     }
 
@@ -532,6 +602,9 @@ impl<'a> RustGenerator {
             } => {
                 if let Some(title) = schema.title.as_ref() {
                     let name = title.to_camel_case();
+                    if let Some(external_type) = self.external_types.get(&name) {
+                        return external_type.clone();
+                    }
                     let json_name = schema.title.clone();
                     // let name = json_name.to_camel_case();
                     let description = schema.description.clone().unwrap();
@@ -556,8 +629,14 @@ impl<'a> RustGenerator {
 
                             let mut property_name = property.name.to_snake_case();
                             enum_name.to_camel_case();
-                            let property_type =
+                            let mut property_type =
                                 self.rust_type_from_schema(&&enum_name, &property.schema);
+                            if let Some(target) = index_target(&name, &property.name) {
+                                property_type = property_type.with_index(target);
+                            }
+                            if is_attributes(&name, &property.name) {
+                                property_type = property_type.with_attributes();
+                            }
 
                             // If a property is not required remap it to an `Option`, unless
                             let property_type = if !property.required
@@ -647,7 +726,7 @@ impl<'a> RustGenerator {
                                         value: Some(*n as u32),
                                     },
                                     EnumValue::String(s) => EnumMember {
-                                        name: s.clone().to_camel_case(),
+                                        name: s.replace('.', " ").to_camel_case(),
                                         description: value.description.clone(),
                                         json_value: JsonEnumValue::String(s.clone()),
                                         value: None,
@@ -688,8 +767,219 @@ impl<'a> RustGenerator {
 
         let mut output = String::new();
         write!(output, "use kserde::*;\n\n").unwrap();
+        write!(
+            output,
+            "use crate::{{Attributes, GltfProperty, Index, RootObject, Sorted, VisitIndices}};\n"
+        )
+        .unwrap();
         write!(output, "use std::collections::HashMap;\n\n").unwrap();
+        self.write_rust_types(&mut output);
+        self.write_object_kinds(&mut output);
+        self.write_root_objects(&mut output);
+        self.write_index_kinds(&mut output);
+        output
+    }
 
+    fn write_index_kinds(&self, output: &mut String) {
+        write!(output, "/// The kinds of objects an index can refer to.\n").unwrap();
+        write!(
+            output,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum IndexKind {{\n"
+        )
+        .unwrap();
+        for kind in index_kinds() {
+            write!(output, "    {},\n", kind).unwrap();
+        }
+        write!(output, "}}\n\n").unwrap();
+    }
+
+    /// Writes an index alias for each type, and `RootObject` for each type in a list of the glTF.
+    fn write_root_objects(&self, output: &mut String) {
+        let root = self.rust_types.iter().find_map(|(_, t)| match t {
+            RustType::Struct(s) if s.name == "GlTf" => Some(s),
+            _ => None,
+        });
+        let mut lists = Vec::new();
+        for property in root.map_or(&[][..], |s| &s.properties[..]) {
+            if let RustType::Option(inner) = &property.property_type {
+                if let RustType::Vec(item) = &**inner {
+                    if let RustType::Struct(s) = &**item {
+                        lists.push((s.name.clone(), property.name.clone()));
+                    }
+                }
+            }
+        }
+
+        for (name, _) in lists.iter() {
+            write!(output, "pub type {}Index = Index<{}>;\n", name, name).unwrap();
+        }
+        // Animation samplers are indexed within their animation rather than the glTF.
+        write!(
+            output,
+            "pub type AnimationSamplerIndex = Index<AnimationSampler>;\n\n"
+        )
+        .unwrap();
+
+        for (name, list) in lists.iter() {
+            write!(output, "impl RootObject for {} {{\n", name).unwrap();
+            write!(output, "    fn list(gltf: &GlTf) -> &Vec<Self> {{\n").unwrap();
+            write!(output, "        &gltf.{}\n", list).unwrap();
+            write!(output, "    }}\n").unwrap();
+            write!(
+                output,
+                "    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {{\n"
+            )
+            .unwrap();
+            write!(output, "        &mut gltf.{}\n", list).unwrap();
+            write!(output, "    }}\n").unwrap();
+            write!(output, "}}\n\n").unwrap();
+        }
+    }
+
+    /// Writes an enum of every type with extensions and a function for each type
+    /// to visit the extensions of itself and its children.
+    fn write_object_kinds(&self, output: &mut String) {
+        let extensible: Vec<&RustStruct> = self
+            .rust_types
+            .iter()
+            .rev()
+            .filter_map(|(_, t)| match t {
+                RustType::Struct(s) if s.properties.iter().any(|p| p.json_name == "extensions") => {
+                    Some(s)
+                }
+                _ => None,
+            })
+            .collect();
+
+        write!(
+            output,
+            "/// The kinds of glTF objects that can be extended.\n"
+        )
+        .unwrap();
+        write!(
+            output,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum ObjectKind {{\n"
+        )
+        .unwrap();
+        for s in extensible.iter() {
+            write!(output, "    {},\n", s.name).unwrap();
+        }
+        write!(output, "}}\n\n").unwrap();
+
+        for s in extensible.iter() {
+            write!(output, "impl {} {{\n", s.name).unwrap();
+            write!(
+                output,
+                "    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {{\n"
+            )
+            .unwrap();
+            write!(
+                output,
+                "        f(ObjectKind::{}, &self.extensions);\n",
+                s.name
+            )
+            .unwrap();
+            for property in s.properties.iter() {
+                match &property.property_type {
+                    RustType::Struct(_) => {
+                        write!(
+                            output,
+                            "        self.{}.visit_extensions(f);\n",
+                            property.name
+                        )
+                        .unwrap();
+                    }
+                    RustType::Option(inner) | RustType::Vec(inner) => match &**inner {
+                        RustType::Struct(_) => {
+                            write!(
+                                output,
+                                "        for v in self.{}.iter() {{\n",
+                                property.name
+                            )
+                            .unwrap();
+                            write!(output, "            v.visit_extensions(f);\n").unwrap();
+                            write!(output, "        }}\n").unwrap();
+                        }
+                        RustType::Vec(inner) => {
+                            if let RustType::Struct(_) = &**inner {
+                                write!(
+                                    output,
+                                    "        for v in self.{}.iter() {{\n",
+                                    property.name
+                                )
+                                .unwrap();
+                                write!(output, "            v.visit_extensions(f);\n").unwrap();
+                                write!(output, "        }}\n").unwrap();
+                            }
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+            write!(output, "    }}\n").unwrap();
+            write!(output, "}}\n\n").unwrap();
+        }
+    }
+
+    /// Generates a module for an extension.
+    /// `roots` are the names of the structs that attach to other glTF objects,
+    /// and the kinds of object they attach to.
+    pub fn generate_extension(
+        &mut self,
+        extension_name: &str,
+        roots: &[(String, Vec<String>, &'a Schema)],
+    ) -> String {
+        for (name, _, schema) in roots {
+            self.generate_struct(name.clone(), schema);
+        }
+
+        let mut output = String::new();
+        write!(
+            output,
+            "//! Types for the `{}` extension.\n\n",
+            extension_name
+        )
+        .unwrap();
+        write!(output, "use kserde::*;\n\n").unwrap();
+        write!(output, "use crate::*;\n").unwrap();
+        write!(output, "use std::collections::HashMap;\n\n").unwrap();
+        self.write_rust_types(&mut output);
+
+        for (name, objects, _) in roots {
+            write!(output, "impl GltfExtension for {} {{\n", name).unwrap();
+            write!(
+                output,
+                "    const NAME: &'static str = \"{}\";\n",
+                extension_name
+            )
+            .unwrap();
+            let objects: Vec<String> = objects
+                .iter()
+                .map(|o| format!("ObjectKind::{}", o))
+                .collect();
+            write!(
+                output,
+                "    const EXTENDS: &'static [ObjectKind] = &[{}];\n",
+                objects.join(", ")
+            )
+            .unwrap();
+            write!(
+                output,
+                "    fn from_thing(thing: &ThingOwned) -> Option<Self> {{\n"
+            )
+            .unwrap();
+            write!(output, "        from_thing_via_json(thing)\n").unwrap();
+            write!(output, "    }}\n").unwrap();
+            write!(output, "    fn to_thing(&self) -> ThingOwned {{\n").unwrap();
+            write!(output, "        to_thing_via_json(self)\n").unwrap();
+            write!(output, "    }}\n").unwrap();
+            write!(output, "}}\n\n").unwrap();
+        }
+        output
+    }
+
+    fn write_rust_types(&self, output: &mut String) {
         // Reverse because we want to the top level structure at the top of the file
         for (_, s) in self.rust_types.iter().rev() {
             match s {
@@ -800,17 +1090,22 @@ impl<'a> RustGenerator {
                                             .unwrap();
                                             write!(output, "        }}\n").unwrap();
                                         }
-                                        _ => {
+                                        inner => {
                                             write!(
                                                 output,
                                                 "        if let Some(v) = self.{}.as_ref() {{\n",
                                                 property.name
                                             )
                                             .unwrap();
+                                            let value = if inner.contains_map() {
+                                                "&Sorted(v)"
+                                            } else {
+                                                "v"
+                                            };
                                             write!(
                                                 output,
-                                                "           serializer.property(\"{}\", v);\n",
-                                                property.json_name
+                                                "           serializer.property(\"{}\", {});\n",
+                                                property.json_name, value
                                             )
                                             .unwrap();
                                             write!(output, "        }}\n").unwrap();
@@ -821,6 +1116,39 @@ impl<'a> RustGenerator {
                                     panic!("Unexpected optional type: {:?}", &property.name)
                                 }
                             }
+                        } else if matches!(
+                            &property.property_type,
+                            RustType::Option(inner)
+                                if !matches!(&**inner, RustType::Vec(..) | RustType::HashMap(..))
+                        ) {
+                            // Missing values are left out, rather than written as null
+                            // like the nulls in `extras`.
+                            write!(
+                                output,
+                                "        if let Some(v) = self.{}.as_ref() {{\n",
+                                property.name
+                            )
+                            .unwrap();
+                            let value = if property.property_type.contains_map() {
+                                "&Sorted(v)"
+                            } else {
+                                "v"
+                            };
+                            write!(
+                                output,
+                                "           serializer.property(\"{}\", {});\n",
+                                property.json_name, value
+                            )
+                            .unwrap();
+                            write!(output, "        }}\n").unwrap();
+                        } else if property.property_type.contains_map() {
+                            // Maps are written with sorted keys so the output is deterministic.
+                            write!(
+                                output,
+                                "        serializer.property(\"{}\", &Sorted(&self.{}));\n",
+                                property.json_name, property.name
+                            )
+                            .unwrap();
                         } else {
                             write!(
                                 output,
@@ -849,7 +1177,7 @@ impl<'a> RustGenerator {
                     .unwrap();
                     write!(
                         output,
-                        "        deserializer.begin_object().then(|| {{}})?;\n"
+                        "        deserializer.begin_object().then_some(())?;\n"
                     )
                     .unwrap();
                     for property in s.properties.iter() {
@@ -904,7 +1232,7 @@ impl<'a> RustGenerator {
                                     RustType::Vec(..) => {
                                         write!(
                                             output,
-                                            "            {}: {}.unwrap_or_else(|| Vec::new()),\n",
+                                            "            {}: {}.unwrap_or_default(),\n",
                                             property.name, property.name
                                         )
                                         .unwrap();
@@ -913,7 +1241,7 @@ impl<'a> RustGenerator {
                                     RustType::HashMap(..) => {
                                         write!(
                                             output,
-                                            "            {}: {}.unwrap_or_else(|| HashMap::new()),\n",
+                                            "            {}: {}.unwrap_or_default(),\n",
                                             property.name, property.name,
                                         )
                                         .unwrap();
@@ -925,83 +1253,11 @@ impl<'a> RustGenerator {
                             _ => property.name.clone(),
                         };
 
-                        let mut cloned = false;
                         if let Some(default_value) = &property.default_value {
                             value = format!(
-                                "{}{}.map_or_else(|| {}, |m| m)",
+                                "{}.unwrap_or({})",
                                 value,
-                                if property.incompatible_with.len() > 0 {
-                                    cloned = true;
-                                    ".clone()"
-                                } else {
-                                    ""
-                                },
-                                match default_value {
-                                    ThingOwned::String(s) => {
-                                        match &property.property_type {
-                                            // Find an enum member with the same name
-                                            RustType::Enum(e) => {
-                                                let mut result = "".to_string();
-                                                for member in &e.members {
-                                                    match &member.json_value {
-                                                        JsonEnumValue::String(v) => {
-                                                            if v == s {
-                                                                result = format!(
-                                                                    "{}::{}",
-                                                                    e.name, member.name
-                                                                );
-                                                                break;
-                                                            }
-                                                        }
-                                                        _ => unimplemented!(),
-                                                    }
-                                                }
-                                                result
-                                            }
-                                            _ => s.clone(),
-                                        }
-                                    }
-                                    ThingOwned::Bool(b) => b.to_string(),
-                                    ThingOwned::Number(n) => {
-                                        match &property.property_type {
-                                            RustType::USIZE => {
-                                                format!("{}usize", n.to_string())
-                                            }
-                                            RustType::F32 => {
-                                                format!("{}f32", n.to_string())
-                                            }
-                                            RustType::Enum(e) => {
-                                                // Find the matching enum value
-                                                let mut s = "".to_string();
-                                                for member in &e.members {
-                                                    if *n as u32 == member.value.unwrap() {
-                                                        s = format!("{}::{}", e.name, member.name);
-                                                        break;
-                                                    }
-                                                }
-                                                s
-                                            }
-                                            _ => unreachable!(),
-                                        }
-                                    }
-                                    ThingOwned::Object(_) => unimplemented!(),
-                                    ThingOwned::Array(a) => {
-                                        let mut s = "[".to_string();
-                                        for v in a {
-                                            match v {
-                                                ThingOwned::Number(n) => {
-                                                    s.push_str(&format!("{}f32, ", n.to_string()))
-                                                }
-                                                _ => s.push_str(&format!("{}, ", &v.to_json())),
-                                            }
-                                        }
-                                        s.push_str("]");
-                                        s
-                                    }
-                                    ThingOwned::Null => {
-                                        unimplemented!()
-                                    }
-                                }
+                                default_value_expression(default_value, &property.property_type)
                             );
 
                             if optional {
@@ -1029,19 +1285,19 @@ impl<'a> RustGenerator {
                                 condition += ".is_none()";
                             }
 
-                            // The same but leave off the comma
+                            // Incompatible properties check each other, so values that
+                            // can't be copied are cloned before the other checks use them.
+                            if !property.property_type.is_copy() {
+                                value += ".clone()";
+                            }
                             write!(
                                 output,
-                                "            {}: if {} {{{}{}}} else {{ None }},\n",
-                                property.name,
-                                condition,
-                                value,
-                                if cloned { "" } else { ".clone()" }
+                                "            {}: if {} {{ {} }} else {{ None }},\n",
+                                property.name, condition, value,
                             )
                             .unwrap();
                         } else {
-                            // The same but leave off the comma
-                            write!(output, "            {}: {},\n", property.name, value).unwrap();
+                            write_field(output, &property.name, &value);
                         }
                     }
                     write!(output, "        }})\n").unwrap();
@@ -1049,6 +1305,121 @@ impl<'a> RustGenerator {
                     write!(output, "    }}\n").unwrap();
 
                     write!(output, "}}\n\n").unwrap();
+
+                    // Construct with the required properties and the schema's defaults for the rest.
+                    let required: Vec<&RustStructProperty> = s
+                        .properties
+                        .iter()
+                        .filter(|p| {
+                            !matches!(p.property_type, RustType::Option(_))
+                                && p.default_value.is_none()
+                        })
+                        .collect();
+                    write!(output, "impl {} {{\n", s.name).unwrap();
+                    write!(
+                        output,
+                        "    /// Creates the object from its required properties. The rest are empty or the schema's default.\n"
+                    )
+                    .unwrap();
+                    let arguments: Vec<String> = required
+                        .iter()
+                        .map(|p| format!("{}: {}", p.name, p.property_type.type_name()))
+                        .collect();
+                    write!(
+                        output,
+                        "    pub fn new({}) -> Self {{\n",
+                        arguments.join(", ")
+                    )
+                    .unwrap();
+                    write!(output, "        Self {{\n").unwrap();
+                    for property in s.properties.iter() {
+                        let value = match (&property.property_type, &property.default_value) {
+                            (RustType::Option(inner), _) => match &**inner {
+                                RustType::Vec(..) => "Vec::new()".to_string(),
+                                RustType::HashMap(..) => "HashMap::new()".to_string(),
+                                _ => "None".to_string(),
+                            },
+                            (property_type, Some(default_value)) => {
+                                default_value_expression(default_value, property_type)
+                            }
+                            (_, None) => property.name.clone(),
+                        };
+                        write_field(output, &property.name, &value);
+                    }
+                    write!(output, "        }}\n").unwrap();
+                    write!(output, "    }}\n").unwrap();
+                    write!(output, "}}\n\n").unwrap();
+
+                    if required.is_empty() {
+                        write!(output, "impl Default for {} {{\n", s.name).unwrap();
+                        write!(output, "    fn default() -> Self {{\n").unwrap();
+                        write!(output, "        Self::new()\n").unwrap();
+                        write!(output, "    }}\n").unwrap();
+                        write!(output, "}}\n\n").unwrap();
+                    }
+
+                    // Give generic access to the extensions and extras of every type that has them.
+                    if s.properties.iter().any(|p| p.json_name == "extensions")
+                        && s.properties.iter().any(|p| p.json_name == "extras")
+                    {
+                        write!(output, "impl GltfProperty for {} {{\n", s.name).unwrap();
+                        write!(
+                            output,
+                            "    fn extensions(&self) -> &HashMap<String, ThingOwned> {{\n"
+                        )
+                        .unwrap();
+                        write!(output, "        &self.extensions\n").unwrap();
+                        write!(output, "    }}\n").unwrap();
+                        write!(
+                            output,
+                            "    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {{\n"
+                        )
+                        .unwrap();
+                        write!(output, "        &mut self.extensions\n").unwrap();
+                        write!(output, "    }}\n").unwrap();
+                        write!(output, "    fn extras(&self) -> Option<&ThingOwned> {{\n").unwrap();
+                        write!(output, "        self.extras.as_ref()\n").unwrap();
+                        write!(output, "    }}\n").unwrap();
+                        write!(
+                            output,
+                            "    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {{\n"
+                        )
+                        .unwrap();
+                        write!(output, "        &mut self.extras\n").unwrap();
+                        write!(output, "    }}\n").unwrap();
+                        write!(output, "}}\n\n").unwrap();
+                    }
+
+                    let mut visit = String::new();
+                    for property in s.properties.iter() {
+                        let expression = format!("self.{}", property.name);
+                        if let Some(code) =
+                            visit_indices_code(&expression, &property.property_type, 0)
+                        {
+                            visit.push_str(&code);
+                        }
+                    }
+                    // Extensions of extensions aren't visited, as they have no `ObjectKind`.
+                    if self.external_types.is_empty()
+                        && s.properties.iter().any(|p| p.json_name == "extensions")
+                    {
+                        write!(
+                            visit,
+                            "        crate::extensions::visit_indices_mut(ObjectKind::{}, &mut self.extensions, f);\n",
+                            s.name
+                        )
+                        .unwrap();
+                    }
+                    write!(output, "impl VisitIndices for {} {{\n", s.name).unwrap();
+                    let f = if visit.is_empty() { "_f" } else { "f" };
+                    write!(
+                        output,
+                        "    fn visit_indices_mut(&mut self, {}: &mut dyn FnMut(IndexKind, &mut usize)) {{\n{}",
+                        f, visit
+                    )
+                    .unwrap();
+                    write!(output, "    }}\n").unwrap();
+                    write!(output, "}}\n\n").unwrap();
                 }
                 RustType::Enum(rust_enum) => {
                     write!(output, "/// {}\n", rust_enum.description).unwrap();
@@ -1069,6 +1440,15 @@ impl<'a> RustGenerator {
                             write!(output, "    {},\n", member.name,).unwrap();
                         }
                     }
+                    let open = OPEN_ENUMS.contains(&&*rust_enum.name);
+                    if open {
+                        write!(
+                            output,
+                            "    /// Any other value, such as the types that extensions add.\n"
+                        )
+                        .unwrap();
+                        write!(output, "    Other(String),\n").unwrap();
+                    }
                     write!(output, "}}\n\n").unwrap();
 
                     // Implement serialization for this enum
@@ -1100,6 +1480,13 @@ impl<'a> RustGenerator {
                             }
                         }
                     }
+                    if open {
+                        write!(
+                            output,
+                            "            Self::Other(value) => value.serialize(serializer),\n"
+                        )
+                        .unwrap();
+                    }
                     write!(output, "        }}\n").unwrap();
 
                     write!(output, "    }}\n").unwrap();
@@ -1161,7 +1548,12 @@ impl<'a> RustGenerator {
                                 _ => unreachable!(),
                             }
                         }
-                        write!(output, "        _ => None?\n").unwrap();
+                        if open {
+                            write!(output, "        _ => Self::Other(value.to_string()),\n")
+                                .unwrap();
+                        } else {
+                            write!(output, "        _ => None?\n").unwrap();
+                        }
                         write!(output, "        }})\n").unwrap();
                         write!(output, "    }}\n").unwrap();
                     }
@@ -1170,17 +1562,403 @@ impl<'a> RustGenerator {
                 _ => unimplemented!(),
             }
         }
-        output
+    }
+}
+
+/// Writes a field of a struct expression, with the shorthand when the value is a variable
+/// with the same name.
+fn write_field(output: &mut String, name: &str, value: &str) {
+    if name == value {
+        write!(output, "            {},\n", name).unwrap();
+    } else {
+        write!(output, "            {}: {},\n", name, value).unwrap();
+    }
+}
+
+/// The Rust expression for a property's default value from the schema.
+fn default_value_expression(default_value: &ThingOwned, property_type: &RustType) -> String {
+    match default_value {
+        ThingOwned::String(s) => {
+            match property_type {
+                // Find an enum member with the same name
+                RustType::Enum(e) => {
+                    let mut result = "".to_string();
+                    for member in &e.members {
+                        match &member.json_value {
+                            JsonEnumValue::String(v) => {
+                                if v == s {
+                                    result = format!("{}::{}", e.name, member.name);
+                                    break;
+                                }
+                            }
+                            _ => unimplemented!(),
+                        }
+                    }
+                    result
+                }
+                _ => s.clone(),
+            }
+        }
+        ThingOwned::Bool(b) => b.to_string(),
+        ThingOwned::Number(n) => {
+            match property_type {
+                RustType::USIZE => {
+                    format!("{}usize", n.to_string())
+                }
+                RustType::F32 => {
+                    format!("{}f32", n.to_string())
+                }
+                RustType::Enum(e) => {
+                    // Find the matching enum value
+                    let mut s = "".to_string();
+                    for member in &e.members {
+                        if *n as u32 == member.value.unwrap() {
+                            s = format!("{}::{}", e.name, member.name);
+                            break;
+                        }
+                    }
+                    s
+                }
+                _ => unreachable!(),
+            }
+        }
+        ThingOwned::Object(_) => unimplemented!(),
+        ThingOwned::Array(a) => {
+            let mut s = "[".to_string();
+            for v in a {
+                match v {
+                    ThingOwned::Number(n) => s.push_str(&format!("{}f32, ", n.to_string())),
+                    _ => s.push_str(&format!("{}, ", &v.to_json())),
+                }
+            }
+            s.push_str("]");
+            s
+        }
+        ThingOwned::Null => {
+            unimplemented!()
+        }
+    }
+}
+
+/// The enums whose schemas also allow any other string. Extensions add values to these, like
+/// the image types of the texture extensions, so the other values are kept instead of rejected.
+const OPEN_ENUMS: &[&str] = &["ImageMimeType"];
+
+/// The properties that are indices, as the struct, the Json property and the type
+/// of the objects they refer to. Each type is also a kind of index.
+/// The schemas only describe these as `glTFid`, so they're listed here.
+const INDEX_TARGETS: &[(&str, &str, &str)] = &[
+    ("GlTf", "scene", "Scene"),
+    ("Scene", "nodes", "Node"),
+    ("Node", "camera", "Camera"),
+    ("Node", "children", "Node"),
+    ("Node", "skin", "Skin"),
+    ("Node", "mesh", "Mesh"),
+    ("Skin", "inverseBindMatrices", "Accessor"),
+    ("Skin", "skeleton", "Node"),
+    ("Skin", "joints", "Node"),
+    ("Texture", "sampler", "Sampler"),
+    ("Texture", "source", "Image"),
+    ("TextureInfo", "index", "Texture"),
+    ("MaterialNormalTextureInfo", "index", "Texture"),
+    ("MaterialOcclusionTextureInfo", "index", "Texture"),
+    ("Image", "bufferView", "BufferView"),
+    ("MeshPrimitive", "attributes", "Accessor"),
+    ("MeshPrimitive", "indices", "Accessor"),
+    ("MeshPrimitive", "material", "Material"),
+    ("MeshPrimitive", "targets", "Accessor"),
+    ("BufferView", "buffer", "Buffer"),
+    ("Accessor", "bufferView", "BufferView"),
+    ("AccessorSparseIndices", "bufferView", "BufferView"),
+    ("AccessorSparseValues", "bufferView", "BufferView"),
+    ("AnimationChannel", "sampler", "AnimationSampler"),
+    ("AnimationChannelTarget", "node", "Node"),
+    ("AnimationSampler", "input", "Accessor"),
+    ("AnimationSampler", "output", "Accessor"),
+    ("KhrDracoMeshCompression", "bufferView", "BufferView"),
+    ("ExtMeshoptCompressionBufferView", "buffer", "Buffer"),
+    ("ExtMeshGpuInstancing", "attributes", "Accessor"),
+    (
+        "KhrMaterialsVariantsMapping",
+        "variants",
+        "KhrMaterialsVariantsVariant",
+    ),
+    ("KhrMaterialsVariantsMapping", "material", "Material"),
+    ("KhrTextureBasisu", "source", "Image"),
+    ("ExtTextureWebp", "source", "Image"),
+    ("MsftTextureDds", "source", "Image"),
+];
+
+fn index_target(struct_name: &str, property: &str) -> Option<&'static str> {
+    INDEX_TARGETS
+        .iter()
+        .find(|(s, p, _)| *s == struct_name && *p == property)
+        .map(|(_, _, target)| *target)
+}
+
+/// The kinds of objects indices refer to, in the order they're first listed.
+fn index_kinds() -> Vec<&'static str> {
+    let mut kinds = Vec::new();
+    for (_, _, target) in INDEX_TARGETS {
+        if !kinds.contains(target) {
+            kinds.push(*target);
+        }
+    }
+    kinds
+}
+
+/// Returns code that calls `f` with each index in `expression`, which has the type `rust_type`,
+/// or `None` if it contains no indices.
+fn visit_indices_code(expression: &str, rust_type: &RustType, depth: usize) -> Option<String> {
+    let v = format!("v{}", depth);
+    let indent = "    ".repeat(depth + 2);
+    Some(match rust_type {
+        RustType::Index(target) => format!(
+            "{}f(IndexKind::{}, {}.value_mut());\n",
+            indent, target, expression
+        ),
+        RustType::Attributes => format!(
+            "{}for {} in {}.values_mut() {{\n{}    f(IndexKind::Accessor, {}.value_mut());\n{}}}\n",
+            indent, v, expression, indent, v, indent
+        ),
+        RustType::Struct(_) => format!("{}{}.visit_indices_mut(f);\n", indent, expression),
+        // Optional lists and maps are stored without the `Option`.
+        RustType::Option(inner) if matches!(**inner, RustType::Vec(_) | RustType::HashMap(..)) => {
+            visit_indices_code(expression, inner, depth)?
+        }
+        RustType::Option(inner) | RustType::Vec(inner) | RustType::HashMap(_, inner) => {
+            let iterate = match rust_type {
+                RustType::HashMap(..) => "values_mut",
+                _ => "iter_mut",
+            };
+            format!(
+                "{}for {} in {}.{}() {{\n{}{}}}\n",
+                indent,
+                v,
+                expression,
+                iterate,
+                visit_indices_code(&v, inner, depth + 1)?,
+                indent
+            )
+        }
+        _ => None?,
+    })
+}
+
+/// Adds the Json path and the kind of each index in a value of `rust_type` at `path` to `paths`.
+/// `*` in a path is each element of an array or each value of an object.
+fn index_paths(rust_type: &RustType, path: &mut Vec<String>, paths: &mut Vec<(String, String)>) {
+    let quoted = |path: &[String]| {
+        path.iter()
+            .map(|p| format!("\"{}\"", p))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match rust_type {
+        RustType::Index(target) => paths.push((quoted(path), target.clone())),
+        RustType::Attributes => {
+            path.push("*".to_string());
+            paths.push((quoted(path), "Accessor".to_string()));
+            path.pop();
+        }
+        RustType::Struct(s) => {
+            for property in s.properties.iter() {
+                path.push(property.json_name.clone());
+                index_paths(&property.property_type, path, paths);
+                path.pop();
+            }
+        }
+        RustType::Option(inner) => index_paths(inner, path, paths),
+        RustType::Array(_, inner) | RustType::Vec(inner) | RustType::HashMap(_, inner) => {
+            path.push("*".to_string());
+            index_paths(inner, path, paths);
+            path.pop();
+        }
+        _ => {}
+    }
+}
+
+/// Whether the property maps attribute semantics to accessors.
+fn is_attributes(struct_name: &str, property: &str) -> bool {
+    matches!(
+        (struct_name, property),
+        ("MeshPrimitive", "attributes") | ("MeshPrimitive", "targets")
+    )
+}
+
+/// Extension schemas are named `<object>.<extension name>.schema.json`.
+/// Returns the name of the object the schema attaches to.
+fn extended_object(file_name: &str, extension_name: &str) -> Option<String> {
+    let suffix = format!(".{}.schema.json", extension_name);
+    file_name
+        .strip_suffix(&suffix)
+        .map(|object| object.replace('.', " ").to_camel_case())
+}
+
+/// The kinds of objects an extension of `object` can attach to.
+fn object_kinds(object: &str) -> Vec<String> {
+    match object {
+        // The normal and occlusion texture infos extend `textureInfo` in the schema.
+        "TextureInfo" => vec![
+            "TextureInfo".to_string(),
+            "MaterialNormalTextureInfo".to_string(),
+            "MaterialOcclusionTextureInfo".to_string(),
+        ],
+        _ => vec![object.to_string()],
     }
 }
 
 fn main() {
     let source = std::fs::read_to_string("schema/glTF.schema.json").unwrap();
     let json = kserde::Thing::from_json(&source).expect("Could not parse JSON");
-    let mut parser = Parser::new();
+    let mut parser = Parser::new(vec![PathBuf::from("schema")]);
     let schema = parser.parse_schema(&json);
 
     let mut rust_generator = RustGenerator::new();
     let result = rust_generator.generate(&schema);
     std::fs::write("../src/gltf_json.rs", result).unwrap();
+
+    // Each directory in `extensions` is named after an extension and contains its schemas.
+    let core_types: HashMap<String, RustType> = rust_generator.rust_types.into_iter().collect();
+    let mut extension_names: Vec<String> = std::fs::read_dir("extensions")
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    extension_names.sort();
+
+    let mut extensions_module = String::new();
+    let mut register_enabled = String::new();
+    let mut index_table = String::new();
+    write!(
+        extensions_module,
+        "//! Typed extensions. Each is enabled with a cargo feature of the same name.\n\nuse crate::{{IndexKind, ObjectKind}};\n\n"
+    )
+    .unwrap();
+
+    for extension_name in extension_names.iter() {
+        let schema_path = Path::new("extensions").join(extension_name).join("schema");
+        let mut root_files: Vec<(String, PathBuf)> = std::fs::read_dir(&schema_path)
+            .unwrap()
+            .filter_map(|entry| {
+                let path = entry.unwrap().path();
+                let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+                extended_object(&file_name, extension_name).map(|object| (object, path))
+            })
+            .collect();
+        root_files.sort();
+
+        let mut parser = Parser::new(vec![schema_path.clone(), PathBuf::from("schema")]);
+        let mut roots = Vec::new();
+        for (object, path) in root_files.iter() {
+            let source = std::fs::read_to_string(path).unwrap();
+            let json = kserde::Thing::from_json(&source).expect("Could not parse JSON");
+            let mut schema = parser.parse_schema(&json);
+
+            // Name the struct after the extension, and the object it extends
+            // if the extension attaches to more than one kind of object.
+            let mut name = extension_name.to_camel_case();
+            if root_files.len() > 1 {
+                name.push_str(object);
+            }
+            schema.title = Some(name.clone());
+            roots.push((name, object_kinds(object), schema));
+        }
+
+        let roots: Vec<(String, Vec<String>, &Schema)> = roots
+            .iter()
+            .map(|(name, objects, schema)| (name.clone(), objects.clone(), schema))
+            .collect();
+        let mut rust_generator = RustGenerator::with_external_types(core_types.clone());
+        let result = rust_generator.generate_extension(extension_name, &roots);
+
+        let module_name = extension_name.to_snake_case();
+        std::fs::write(format!("../src/extensions/{}.rs", module_name), result).unwrap();
+
+        write!(
+            extensions_module,
+            "#[cfg(feature = \"{}\")]\npub mod {};\n",
+            extension_name, module_name
+        )
+        .unwrap();
+        for (name, _, _) in roots.iter() {
+            write!(
+                extensions_module,
+                "#[cfg(feature = \"{}\")]\npub use {}::{};\n",
+                extension_name, module_name, name
+            )
+            .unwrap();
+            write!(
+                register_enabled,
+                "    #[cfg(feature = \"{}\")]\n    registry.register::<{}>();\n",
+                extension_name, name
+            )
+            .unwrap();
+        }
+        // The indices are listed whether or not the extension's feature is enabled,
+        // so objects can be removed and moved without breaking the extension.
+        for (name, objects, _) in roots.iter() {
+            let root = rust_generator
+                .rust_types
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, t)| t)
+                .unwrap();
+            let mut paths = Vec::new();
+            index_paths(root, &mut Vec::new(), &mut paths);
+            for object in objects.iter() {
+                for (path, kind) in paths.iter() {
+                    write!(
+                        index_table,
+                        "    (\"{}\", ObjectKind::{}, &[{}], IndexKind::{}),\n",
+                        extension_name, object, path, kind
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    write!(
+        extensions_module,
+        "\n/// Registers every extension enabled with a cargo feature.\n"
+    )
+    .unwrap();
+    write!(
+        extensions_module,
+        "#[allow(unused_variables)]\npub(crate) fn register_enabled(registry: &mut crate::ExtensionRegistry) {{\n{}}}\n",
+        register_enabled
+    )
+    .unwrap();
+    let names: Vec<String> = extension_names
+        .iter()
+        .map(|name| format!("    \"{}\",\n", name))
+        .collect();
+    write!(
+        extensions_module,
+        "\n/// The extensions with a schema, whose indices are known even if their features are disabled.\npub(crate) const NAMES: &[&str] = &[\n{}];\n",
+        names.concat()
+    )
+    .unwrap();
+    write!(
+        extensions_module,
+        "\n/// The indices in each extension, as the extension's name, the kind of object it extends,\n/// the Json path to the index and the kind of object the index refers to.\n/// `*` in a path is each element of an array or each value of an object.\n"
+    )
+    .unwrap();
+    write!(
+        extensions_module,
+        "const INDICES: &[(&str, ObjectKind, &[&str], IndexKind)] = &[\n{}];\n",
+        index_table
+    )
+    .unwrap();
+    write!(
+        extensions_module,
+        "\n/// Calls `f` with every index in the known extensions of an object,\n/// whether or not their features are enabled.\n"
+    )
+    .unwrap();
+    write!(
+        extensions_module,
+        "pub(crate) fn visit_indices_mut(\n    object: ObjectKind,\n    extensions: &mut std::collections::HashMap<String, kserde::ThingOwned>,\n    f: &mut dyn FnMut(IndexKind, &mut usize),\n) {{\n    for (name, extends, path, kind) in INDICES {{\n        if *extends == object {{\n            if let Some(extension) = extensions.get_mut(*name) {{\n                crate::visit_json_indices(extension, path, *kind, f);\n            }}\n        }}\n    }}\n}}\n"
+    )
+    .unwrap();
+    std::fs::write("../src/extensions/mod.rs", extensions_module).unwrap();
 }
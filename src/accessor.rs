@@ -0,0 +1,536 @@
+use crate::*;
+
+#[derive(Debug)]
+pub enum AccessorError {
+    /// The index doesn't refer to an accessor.
+    MissingAccessor(AccessorIndex),
+    /// The accessor refers to a buffer view that doesn't exist.
+    MissingBufferView(BufferViewIndex),
+    /// The buffer doesn't exist or is too short for the data that refers to it.
+    MissingBufferData(BufferIndex),
+    /// The primitive has no accessor for an attribute the operation needs.
+    MissingAttribute(Semantic),
+    /// The operation needs triangles, but the primitive is points or lines or has no triangles.
+    NoTriangles,
+    /// The operation needs lines, but the primitive has none.
+    NoLines,
+    /// The primitive uses `KHR_draco_mesh_compression`, so its vertices can't be read
+    /// until it's decoded.
+    Compressed,
+    /// The accessor has no buffer view and more components than [MAX_UNBACKED_COMPONENTS].
+    TooLarge(AccessorIndex),
+}
+
+/// The most components read from an accessor without a buffer view, whose values are zeros
+/// apart from its sparse ones. No data bounds the count of such an accessor, so this keeps
+/// a corrupt count from allocating without limit.
+pub const MAX_UNBACKED_COMPONENTS: usize = 1 << 26;
+
+impl AccessorType {
+    /// The number of components in each element.
+    pub fn component_count(&self) -> usize {
+        match self {
+            AccessorType::Scalar => 1,
+            AccessorType::Vec2 => 2,
+            AccessorType::Vec3 => 3,
+            AccessorType::Vec4 | AccessorType::Mat2 => 4,
+            AccessorType::Mat3 => 9,
+            AccessorType::Mat4 => 16,
+        }
+    }
+
+    /// The number of rows in each column, which is the component count for vectors.
+    fn rows(&self) -> usize {
+        match self {
+            AccessorType::Mat2 => 2,
+            AccessorType::Mat3 => 3,
+            AccessorType::Mat4 => 4,
+            _ => self.component_count(),
+        }
+    }
+}
+
+impl AccessorComponentType {
+    /// The size of a component in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            AccessorComponentType::Byte | AccessorComponentType::UnsignedByte => 1,
+            AccessorComponentType::Short | AccessorComponentType::UnsignedShort => 2,
+            AccessorComponentType::UnsignedInt | AccessorComponentType::Float => 4,
+        }
+    }
+}
+
+/// Where each component of an element is stored.
+/// Matrix columns start at a multiple of 4 bytes, so matrices of bytes and shorts are padded.
+struct Layout {
+    component_size: usize,
+    element_size: usize,
+    offsets: Vec<usize>,
+}
+
+impl Layout {
+    fn new(type_: &AccessorType, component_type: &AccessorComponentType) -> Self {
+        let component_size = component_type.size();
+        let rows = type_.rows();
+        let column_size = if rows == type_.component_count() {
+            rows * component_size
+        } else {
+            (rows * component_size + 3) & !3
+        };
+        let offsets = (0..type_.component_count())
+            .map(|i| (i / rows) * column_size + (i % rows) * component_size)
+            .collect();
+        Self {
+            component_size,
+            element_size: column_size * type_.component_count() / rows,
+            offsets,
+        }
+    }
+}
+
+/// Converts a component to a float, dequantizing normalized integers to [0, 1] or [-1, 1].
+fn component_to_f32(component_type: &AccessorComponentType, normalized: bool, bytes: &[u8]) -> f32 {
+    match component_type {
+        AccessorComponentType::Byte => {
+            let value = bytes[0] as i8 as f32;
+            if normalized {
+                (value / 127.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        AccessorComponentType::UnsignedByte => {
+            let value = bytes[0] as f32;
+            if normalized {
+                value / 255.0
+            } else {
+                value
+            }
+        }
+        AccessorComponentType::Short => {
+            let value = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized {
+                (value / 32767.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        AccessorComponentType::UnsignedShort => {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized {
+                value / 65535.0
+            } else {
+                value
+            }
+        }
+        AccessorComponentType::UnsignedInt => {
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+        }
+        AccessorComponentType::Float => {
+            f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }
+    }
+}
+
+fn component_to_u32(component_type: &AccessorComponentType, bytes: &[u8]) -> u32 {
+    match component_type {
+        AccessorComponentType::Byte => bytes[0] as i8 as u32,
+        AccessorComponentType::UnsignedByte => bytes[0] as u32,
+        AccessorComponentType::Short => i16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+        AccessorComponentType::UnsignedShort => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+        AccessorComponentType::UnsignedInt => {
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }
+        AccessorComponentType::Float => {
+            f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u32
+        }
+    }
+}
+
+/// Appends a float in the component type, quantizing it if the component type is an integer.
+fn write_component(
+    data: &mut Vec<u8>,
+    component_type: &AccessorComponentType,
+    normalized: bool,
+    value: f32,
+) {
+    let integer = |min: f32, max: f32| {
+        if normalized {
+            (value * max).round().max(-max).min(max)
+        } else {
+            value.round().max(min).min(max)
+        }
+    };
+    match component_type {
+        AccessorComponentType::Byte => data.push(integer(-128.0, 127.0) as i8 as u8),
+        AccessorComponentType::UnsignedByte => data.push(integer(0.0, 255.0) as u8),
+        AccessorComponentType::Short => {
+            data.extend_from_slice(&(integer(-32768.0, 32767.0) as i16).to_le_bytes())
+        }
+        AccessorComponentType::UnsignedShort => {
+            data.extend_from_slice(&(integer(0.0, 65535.0) as u16).to_le_bytes())
+        }
+        AccessorComponentType::UnsignedInt => {
+            data.extend_from_slice(&(value.round().max(0.0) as u32).to_le_bytes())
+        }
+        AccessorComponentType::Float => data.extend_from_slice(&value.to_le_bytes()),
+    }
+}
+
+impl GlTf {
+    /// Reads the components of every element of an accessor as floats.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers.
+    /// Normalized integers are dequantized to [0, 1] or [-1, 1] as the specification describes,
+    /// which is how `KHR_mesh_quantization` stores attributes. Other integers are converted as is.
+    /// Sparse values are applied and accessors without a buffer view are read as zeros,
+    /// up to [MAX_UNBACKED_COMPONENTS] components.
+    pub fn read_accessor_f32(
+        &self,
+        accessor: AccessorIndex,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<f32>, AccessorError> {
+        self.read_accessor(accessor, buffers, component_to_f32)
+    }
+
+    /// Reads the components of every element of an accessor as integers,
+    /// for example indices or joints.
+    pub fn read_accessor_u32(
+        &self,
+        accessor: AccessorIndex,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<u32>, AccessorError> {
+        self.read_accessor(accessor, buffers, |component_type, _, bytes| {
+            component_to_u32(component_type, bytes)
+        })
+    }
+
+    pub(crate) fn read_accessor<T: Clone + Default>(
+        &self,
+        accessor_index: AccessorIndex,
+        buffers: &[Vec<u8>],
+        read: impl Fn(&AccessorComponentType, bool, &[u8]) -> T,
+    ) -> Result<Vec<T>, AccessorError> {
+        let accessor = self
+            .get(accessor_index)
+            .ok_or(AccessorError::MissingAccessor(accessor_index))?;
+        let layout = Layout::new(&accessor.type_, &accessor.component_type);
+        let components = layout.offsets.len();
+
+        // The count isn't trusted, so it's checked against the data, or against
+        // `MAX_UNBACKED_COMPONENTS` without a buffer view, before anything is allocated.
+        let view_data = match accessor.buffer_view {
+            Some(view) => {
+                let (data, buffer) = self.buffer_view_data(view, buffers)?;
+                let stride = self.buffer_views[view.value()]
+                    .byte_stride
+                    .unwrap_or(layout.element_size);
+                let end = match accessor.count {
+                    0 => Some(0),
+                    count => (count - 1)
+                        .checked_mul(stride)
+                        .and_then(|start| start.checked_add(accessor.byte_offset))
+                        .and_then(|start| start.checked_add(layout.element_size)),
+                };
+                if !matches!(end, Some(end) if end <= data.len()) {
+                    Err(AccessorError::MissingBufferData(buffer))?
+                }
+                Some((data, buffer, stride))
+            }
+            None => {
+                let size = accessor.count.checked_mul(components);
+                if !matches!(size, Some(size) if size <= MAX_UNBACKED_COMPONENTS) {
+                    Err(AccessorError::TooLarge(accessor_index))?
+                }
+                None
+            }
+        };
+        let mut values = vec![T::default(); accessor.count * components];
+
+        let read_element = |data: &[u8], values: &mut [T]| {
+            for (value, &offset) in values.iter_mut().zip(&layout.offsets) {
+                *value = read(
+                    &accessor.component_type,
+                    accessor.normalized,
+                    &data[offset..offset + layout.component_size],
+                );
+            }
+        };
+
+        if let Some((data, buffer, stride)) = view_data {
+            for (i, values) in values.chunks_exact_mut(components).enumerate() {
+                let start = accessor.byte_offset + i * stride;
+                let element = data
+                    .get(start..start + layout.element_size)
+                    .ok_or(AccessorError::MissingBufferData(buffer))?;
+                read_element(element, values);
+            }
+        }
+
+        if let Some(sparse) = &accessor.sparse {
+            let (index_data, buffer) =
+                self.buffer_view_data(sparse.indices.buffer_view, buffers)?;
+            let index_type = match sparse.indices.component_type {
+                AccessorSparseIndicesComponentType::UnsignedByte => {
+                    AccessorComponentType::UnsignedByte
+                }
+                AccessorSparseIndicesComponentType::UnsignedShort => {
+                    AccessorComponentType::UnsignedShort
+                }
+                AccessorSparseIndicesComponentType::UnsignedInt => {
+                    AccessorComponentType::UnsignedInt
+                }
+            };
+            let index_size = index_type.size();
+            let (value_data, value_buffer) =
+                self.buffer_view_data(sparse.values.buffer_view, buffers)?;
+            for i in 0..sparse.count {
+                let start = sparse.indices.byte_offset + i * index_size;
+                let index = index_data
+                    .get(start..start + index_size)
+                    .map(|bytes| component_to_u32(&index_type, bytes) as usize)
+                    .filter(|&index| index < accessor.count)
+                    .ok_or(AccessorError::MissingBufferData(buffer))?;
+                let start = sparse.values.byte_offset + i * layout.element_size;
+                let element = value_data
+                    .get(start..start + layout.element_size)
+                    .ok_or(AccessorError::MissingBufferData(value_buffer))?;
+                read_element(
+                    element,
+                    &mut values[index * components..(index + 1) * components],
+                );
+            }
+        }
+        Ok(values)
+    }
+
+    /// Returns the bytes of a buffer view and the index of its buffer.
+    pub(crate) fn buffer_view_data<'a>(
+        &self,
+        view: BufferViewIndex,
+        buffers: &'a [Vec<u8>],
+    ) -> Result<(&'a [u8], BufferIndex), AccessorError> {
+        let view = self
+            .get(view)
+            .ok_or(AccessorError::MissingBufferView(view))?;
+        let data = buffers
+            .get(view.buffer.value())
+            .and_then(|b| b.get(view.byte_offset..view.byte_offset.checked_add(view.byte_length)?))
+            .ok_or(AccessorError::MissingBufferData(view.buffer))?;
+        Ok((data, view.buffer))
+    }
+
+    /// Appends data to the first buffer as a new buffer view and returns the view's index.
+    ///
+    /// The buffer is created if the glTF has none, which is where a GLB stores its binary data.
+    pub fn push_buffer_view(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        data: &[u8],
+        byte_stride: Option<usize>,
+        target: Option<BufferViewTarget>,
+    ) -> BufferViewIndex {
+        if self.buffers.is_empty() {
+            self.buffers.push(Buffer::new(0));
+        }
+        buffers.resize(self.buffers.len(), Vec::new());
+        let buffer = &mut buffers[0];
+        // Views start at a multiple of 4 bytes, as every component type requires.
+        buffer.resize((buffer.len() + 3) & !3, 0);
+        let byte_offset = buffer.len();
+        buffer.extend_from_slice(data);
+        self.buffers[0].byte_length = buffer.len();
+        self.push(BufferView {
+            byte_offset,
+            byte_stride,
+            target,
+            ..BufferView::new(Index::new(0), data.len())
+        })
+    }
+
+    /// Adds an accessor with the values converted to the component type, and returns its index.
+    ///
+    /// Integer component types round the values, after scaling them to the type's range if
+    /// `normalized` is true. `min` and `max` are set to the stored values.
+    /// Elements of vertex attributes, which have the `ArrayBuffer` target,
+    /// are padded to a multiple of 4 bytes as the specification requires.
+    pub fn push_accessor(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        values: &[f32],
+        type_: AccessorType,
+        component_type: AccessorComponentType,
+        normalized: bool,
+        target: Option<BufferViewTarget>,
+    ) -> AccessorIndex {
+        let layout = Layout::new(&type_, &component_type);
+        let components = layout.offsets.len();
+        let vertex_attribute = matches!(target, Some(BufferViewTarget::ArrayBuffer));
+        let stride = if vertex_attribute {
+            (layout.element_size + 3) & !3
+        } else {
+            layout.element_size
+        };
+
+        let mut data = Vec::with_capacity(values.len() / components.max(1) * stride);
+        let mut min = vec![f32::MAX; components];
+        let mut max = vec![f32::MIN; components];
+        for element in values.chunks_exact(components) {
+            let start = data.len();
+            for (i, (&value, &offset)) in element.iter().zip(&layout.offsets).enumerate() {
+                data.resize(start + offset, 0);
+                write_component(&mut data, &component_type, normalized, value);
+                let stored = component_to_f32(&component_type, false, &data[start + offset..]);
+                min[i] = min[i].min(stored);
+                max[i] = max[i].max(stored);
+            }
+            data.resize(start + stride, 0);
+        }
+        let count = values.len() / components;
+        if count == 0 {
+            min.clear();
+            max.clear();
+        }
+
+        let byte_stride = (vertex_attribute && stride != layout.element_size).then_some(stride);
+        let buffer_view = self.push_buffer_view(buffers, &data, byte_stride, target);
+        self.push(Accessor {
+            buffer_view: Some(buffer_view),
+            normalized,
+            max,
+            min,
+            ..Accessor::new(component_type, count, type_)
+        })
+    }
+
+    /// Adds an accessor with copies of elements of another accessor, in the order they're
+    /// listed, and returns its index. The stored bytes are copied, so integers and floats
+    /// keep their exact values. `min` and `max` are set to the copied values.
+    pub(crate) fn copy_accessor_elements(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        accessor: AccessorIndex,
+        elements: &[u32],
+        target: Option<BufferViewTarget>,
+    ) -> Result<AccessorIndex, AccessorError> {
+        let values = self.read_accessor(accessor, buffers, |_, _, bytes| {
+            let mut component = [0; 4];
+            component[..bytes.len()].copy_from_slice(bytes);
+            component
+        })?;
+        let old = &self.accessors[accessor.value()];
+        let (type_, component_type) = (old.type_.clone(), old.component_type.clone());
+        let normalized = old.normalized;
+        let layout = Layout::new(&type_, &component_type);
+        let components = layout.offsets.len();
+        let vertex_attribute = matches!(target, Some(BufferViewTarget::ArrayBuffer));
+        let stride = if vertex_attribute {
+            (layout.element_size + 3) & !3
+        } else {
+            layout.element_size
+        };
+
+        let mut data = vec![0; elements.len() * stride];
+        let mut min = vec![f32::MAX; components];
+        let mut max = vec![f32::MIN; components];
+        for (i, &element) in elements.iter().enumerate() {
+            let start = element as usize * components;
+            let element = values
+                .get(start..start + components)
+                .ok_or(AccessorError::MissingAccessor(accessor))?;
+            for (c, (bytes, &offset)) in element.iter().zip(&layout.offsets).enumerate() {
+                let offset = i * stride + offset;
+                let bytes = &bytes[..layout.component_size];
+                data[offset..offset + layout.component_size].copy_from_slice(bytes);
+                let stored = component_to_f32(&component_type, false, bytes);
+                min[c] = min[c].min(stored);
+                max[c] = max[c].max(stored);
+            }
+        }
+        if elements.is_empty() {
+            min.clear();
+            max.clear();
+        }
+
+        let byte_stride = (vertex_attribute && stride != layout.element_size).then_some(stride);
+        let buffer_view = self.push_buffer_view(buffers, &data, byte_stride, target);
+        Ok(self.push(Accessor {
+            buffer_view: Some(buffer_view),
+            normalized,
+            max,
+            min,
+            ..Accessor::new(component_type, elements.len(), type_)
+        }))
+    }
+
+    /// Adds an accessor of triangle or line indices, stored as shorts if they fit,
+    /// and returns its index.
+    ///
+    /// `min` and `max` are found as integers, though `Accessor` stores them as floats,
+    /// which round indices above 2^24.
+    pub fn push_indices(&mut self, buffers: &mut Vec<Vec<u8>>, indices: &[u32]) -> AccessorIndex {
+        let (component_type, data): (_, Vec<u8>) = if indices.iter().all(|&i| i < u16::MAX as u32) {
+            let data = indices.iter().flat_map(|&i| (i as u16).to_le_bytes());
+            (AccessorComponentType::UnsignedShort, data.collect())
+        } else {
+            let data = indices.iter().flat_map(|i| i.to_le_bytes());
+            (AccessorComponentType::UnsignedInt, data.collect())
+        };
+        let buffer_view = self.push_buffer_view(
+            buffers,
+            &data,
+            None,
+            Some(BufferViewTarget::ElementArrayBuffer),
+        );
+        let (min, max) = match (indices.iter().min(), indices.iter().max()) {
+            (Some(&min), Some(&max)) => (vec![min as f32], vec![max as f32]),
+            _ => (Vec::new(), Vec::new()),
+        };
+        self.push(Accessor {
+            buffer_view: Some(buffer_view),
+            max,
+            min,
+            ..Accessor::new(component_type, indices.len(), AccessorType::Scalar)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+
+    #[test]
+    fn counts_larger_than_the_data_are_rejected() {
+        let (mut gltf, buffers) = cube();
+        let accessor = gltf.meshes[0].primitives[0].attributes[&Semantic::Position];
+        let view = gltf.accessors[accessor.value()].buffer_view.unwrap();
+        let buffer = gltf.buffer_views[view.value()].buffer;
+        gltf.accessors[accessor.value()].count = usize::MAX / 2;
+        let result = gltf.read_accessor_f32(accessor, &buffers);
+        assert!(matches!(result, Err(AccessorError::MissingBufferData(b)) if b == buffer));
+
+        // Ranges past the end of memory are missing data too, rather than overflowing.
+        gltf.accessors[accessor.value()].count = 1;
+        gltf.buffer_views[view.value()].byte_offset = usize::MAX;
+        let result = gltf.read_accessor_f32(accessor, &buffers);
+        assert!(matches!(result, Err(AccessorError::MissingBufferData(b)) if b == buffer));
+    }
+
+    #[test]
+    fn counts_without_buffer_views_are_limited() {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        let zeros = gltf.push(Accessor::new(
+            AccessorComponentType::Float,
+            2,
+            AccessorType::Vec3,
+        ));
+        assert_eq!(gltf.read_accessor_f32(zeros, &[]).unwrap(), [0.0; 6]);
+        for count in [MAX_UNBACKED_COMPONENTS / 3 + 1, usize::MAX / 2] {
+            gltf.accessors[zeros.value()].count = count;
+            let result = gltf.read_accessor_f32(zeros, &[]);
+            assert!(matches!(result, Err(AccessorError::TooLarge(a)) if a == zeros));
+        }
+    }
+}
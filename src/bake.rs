@@ -0,0 +1,512 @@
+//! Applying node transforms to mesh data, for tools that need geometry in scene space.
+
+use std::collections::HashMap;
+
+use crate::*;
+
+/// How the values of an accessor change with the transform.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Usage {
+    /// Positions, which are moved by the translation.
+    Point,
+    /// Directions and displacements, such as morph target positions.
+    Vector,
+    /// Normals, which are transformed by the inverse transpose and normalized.
+    Normal,
+    /// Morph target normal displacements, which are transformed like normals but not normalized.
+    NormalDisplacement,
+    /// Tangents, whose `w` is the handedness of the bitangent.
+    Tangent,
+}
+
+impl Usage {
+    fn of(semantic: &Semantic, morph_target: bool) -> Option<Self> {
+        Some(match (semantic, morph_target) {
+            (Semantic::Position, false) => Self::Point,
+            (Semantic::Normal, false) => Self::Normal,
+            (Semantic::Tangent, false) => Self::Tangent,
+            (Semantic::Position, true) | (Semantic::Tangent, true) => Self::Vector,
+            (Semantic::Normal, true) => Self::NormalDisplacement,
+            _ => None?,
+        })
+    }
+}
+
+/// The parts of a matrix that transform each kind of value.
+struct Transform {
+    matrix: [f32; 16],
+    /// The columns of the inverse transpose of the upper 3x3 matrix.
+    normal_columns: [[f32; 3]; 3],
+    /// Whether the transform mirrors, which reverses the winding of triangles.
+    mirrors: bool,
+}
+
+impl Transform {
+    fn new(matrix: [f32; 16]) -> Self {
+        let column = |c: usize| [matrix[c * 4], matrix[c * 4 + 1], matrix[c * 4 + 2]];
+        let (c0, c1, c2) = (column(0), column(1), column(2));
+        let mut normal_columns = [cross(c1, c2), cross(c2, c0), cross(c0, c1)];
+        let determinant: f32 = (0..3).map(|i| c0[i] * normal_columns[0][i]).sum();
+        if determinant != 0.0 {
+            for value in normal_columns.iter_mut().flatten() {
+                *value /= determinant;
+            }
+        }
+        Self {
+            matrix,
+            normal_columns,
+            mirrors: determinant < 0.0,
+        }
+    }
+
+    fn linear(&self, v: &[f32]) -> [f32; 3] {
+        let m = &self.matrix;
+        let mut out = [0.0; 3];
+        for (row, out) in out.iter_mut().enumerate() {
+            *out = m[row] * v[0] + m[4 + row] * v[1] + m[8 + row] * v[2];
+        }
+        out
+    }
+
+    fn normal(&self, v: &[f32]) -> [f32; 3] {
+        let n = &self.normal_columns;
+        let mut out = [0.0; 3];
+        for (row, out) in out.iter_mut().enumerate() {
+            *out = n[0][row] * v[0] + n[1][row] * v[1] + n[2][row] * v[2];
+        }
+        out
+    }
+
+    fn apply(&self, values: &mut [f32], usage: Usage) {
+        let components = if usage == Usage::Tangent { 4 } else { 3 };
+        for value in values.chunks_exact_mut(components) {
+            let transformed = match usage {
+                Usage::Point => {
+                    let v = self.linear(value);
+                    [
+                        v[0] + self.matrix[12],
+                        v[1] + self.matrix[13],
+                        v[2] + self.matrix[14],
+                    ]
+                }
+                Usage::Vector => self.linear(value),
+                Usage::Normal => normalize(self.normal(value)),
+                Usage::NormalDisplacement => self.normal(value),
+                Usage::Tangent => {
+                    if self.mirrors {
+                        value[3] = -value[3];
+                    }
+                    normalize(self.linear(value))
+                }
+            };
+            value[..3].copy_from_slice(&transformed);
+        }
+    }
+}
+
+/// Identifies a matrix exactly, so meshes with the same transform are only baked once.
+fn matrix_key(matrix: &[f32; 16]) -> [u32; 16] {
+    matrix.map(f32::to_bits)
+}
+
+impl GlTf {
+    /// Applies the world transform of nodes with meshes to the meshes' positions, normals
+    /// and tangents, including those of morph targets, and removes the nodes' transforms.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers, the new accessors are appended
+    /// to the first one. The original accessors are left in place.
+    ///
+    /// A mesh is only changed in place if a single node uses it, otherwise it's copied for each
+    /// transform. The triangles of meshes with a mirroring transform are reversed so they keep
+    /// facing the same way, and strips and fans are converted to lists to do so.
+    ///
+    /// Nodes that can't be baked are left as they are, along with their ancestors: nodes with
+    /// skins or extensions, skin joints, nodes whose transforms are animated and their
+    /// descendants, nodes with cameras and children and meshes with `KHR_draco_mesh_compression`.
+    /// Nodes under a node that's left as it is are only baked if that node's transform can be
+    /// inverted, as their transform becomes its inverse.
+    ///
+    /// Other nodes without meshes or children, such as cameras, are left in place instead: when
+    /// their parent is baked, their world transform becomes their transform.
+    pub fn bake_transforms(&mut self, buffers: &mut Vec<Vec<u8>>) -> Result<(), AccessorError> {
+        let world = self.world_matrices();
+        let baked = self.baked_nodes(&world);
+
+        let mut users = vec![0; self.meshes.len()];
+        for mesh in self.nodes.iter().filter_map(|node| node.mesh) {
+            if let Some(users) = users.get_mut(mesh.value()) {
+                *users += 1;
+            }
+        }
+        // The copy of each mesh that's baked with each transform.
+        let mut baked_meshes: HashMap<(MeshIndex, [u32; 16]), MeshIndex> = HashMap::new();
+        let mut converted = HashMap::new();
+        let original_meshes = self.meshes.clone();
+        for (n, &node_world) in world.iter().enumerate() {
+            let mesh = match self.nodes[n].mesh {
+                Some(mesh) if baked[n] && node_world != IDENTITY_MATRIX => mesh,
+                _ => continue,
+            };
+            let key = (mesh, matrix_key(&node_world));
+            let baked_mesh = match baked_meshes.get(&key) {
+                Some(&baked_mesh) => baked_mesh,
+                None => {
+                    let baked_mesh = if users[mesh.value()] == 1 {
+                        mesh
+                    } else {
+                        self.push(original_meshes[mesh.value()].clone())
+                    };
+                    let transform = Transform::new(node_world);
+                    self.bake_mesh(buffers, baked_mesh, &transform, &mut converted)?;
+                    baked_meshes.insert(key, baked_mesh);
+                    baked_mesh
+                }
+            };
+            self.nodes[n].mesh = Some(baked_mesh);
+        }
+
+        // A baked node is at the origin, so its transform undoes its parent's if that's kept,
+        // and a kept node under a baked one takes its world transform.
+        let parents = self.node_parents();
+        for (n, node) in self.nodes.iter_mut().enumerate() {
+            let parent_baked = parents[n].is_some_and(|parent| baked[parent.value()]);
+            if !baked[n] && !parent_baked {
+                continue;
+            }
+            let local = match parents[n] {
+                _ if !baked[n] => world[n],
+                Some(parent) if !baked[parent.value()] => {
+                    invert_matrix(&world[parent.value()]).unwrap_or(IDENTITY_MATRIX)
+                }
+                _ => IDENTITY_MATRIX,
+            };
+            node.matrix = (local != IDENTITY_MATRIX).then_some(local);
+            node.translation = None;
+            node.rotation = None;
+            node.scale = None;
+        }
+        Ok(())
+    }
+
+    /// Which nodes are baked. A baked node's children are all baked too, so the nodes that
+    /// aren't baked keep their place in the scene without changing their transforms.
+    fn baked_nodes(&self, world: &[[f32; 16]]) -> Vec<bool> {
+        let mut animated = vec![false; self.nodes.len()];
+        for channel in self.animations.iter().flat_map(|a| &a.channels) {
+            let moves = !matches!(channel.target.path, AnimationChannelTargetPath::Weights);
+            if let Some(animated) = channel
+                .target
+                .node
+                .and_then(|node| animated.get_mut(node.value()))
+            {
+                *animated |= moves;
+            }
+        }
+        let mut joint = vec![false; self.nodes.len()];
+        for node in self.skins.iter().flat_map(|s| &s.joints) {
+            if let Some(joint) = joint.get_mut(node.value()) {
+                *joint = true;
+            }
+        }
+
+        // Whether each node and its descendants could be baked, ignoring its ancestors.
+        let mut bakeable: Vec<Option<bool>> = vec![None; self.nodes.len()];
+        fn find_bakeable(
+            gltf: &GlTf,
+            n: usize,
+            animated: &[bool],
+            joint: &[bool],
+            bakeable: &mut Vec<Option<bool>>,
+        ) -> bool {
+            if let Some(bakeable) = bakeable[n] {
+                return bakeable;
+            }
+            // Guards against cycles in invalid documents.
+            bakeable[n] = Some(false);
+            let node = &gltf.nodes[n];
+            // Leaves without meshes aren't baked, but they can keep their place under a node
+            // that is.
+            let leaf = node.mesh.is_none() && node.children.is_empty();
+            let mut result = !animated[n]
+                && !joint[n]
+                && (node.camera.is_none() || leaf)
+                && node.skin.is_none()
+                && node.extensions.is_empty()
+                && node.mesh.map_or(true, |mesh| gltf.can_bake(mesh));
+            for child in &node.children {
+                if child.value() < gltf.nodes.len() {
+                    result &= find_bakeable(gltf, child.value(), animated, joint, bakeable);
+                }
+            }
+            bakeable[n] = Some(result);
+            result
+        }
+
+        // Nodes are baked from the roots down. A node's children can only be baked
+        // if its transform stays the same or can be undone, and isn't animated.
+        let mut baked = vec![false; self.nodes.len()];
+        let mut visited = vec![false; self.nodes.len()];
+        let parents = self.node_parents();
+        let mut stack: Vec<(usize, bool)> = (0..self.nodes.len())
+            .filter(|&n| parents[n].is_none())
+            .map(|n| (n, true))
+            .collect();
+        while let Some((n, allowed)) = stack.pop() {
+            if std::mem::replace(&mut visited[n], true) {
+                continue;
+            }
+            let node = &self.nodes[n];
+            let leaf = node.mesh.is_none() && node.children.is_empty();
+            baked[n] = allowed && !leaf && find_bakeable(self, n, &animated, &joint, &mut bakeable);
+            let children_allowed =
+                allowed && !animated[n] && (baked[n] || invert_matrix(&world[n]).is_some());
+            for child in &self.nodes[n].children {
+                if child.value() < self.nodes.len() {
+                    stack.push((child.value(), children_allowed));
+                }
+            }
+        }
+        baked
+    }
+
+    fn can_bake(&self, mesh: MeshIndex) -> bool {
+        self.get(mesh).is_some_and(|mesh| {
+            !mesh
+                .primitives
+                .iter()
+                .any(|p| p.extensions.contains_key("KHR_draco_mesh_compression"))
+        })
+    }
+
+    /// Transforms the attributes of every primitive of a mesh, and reverses the triangles
+    /// if the transform mirrors. `converted` holds the accessors already transformed.
+    fn bake_mesh(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mesh: MeshIndex,
+        transform: &Transform,
+        converted: &mut HashMap<(AccessorIndex, Usage, [u32; 16]), AccessorIndex>,
+    ) -> Result<(), AccessorError> {
+        let key = matrix_key(&transform.matrix);
+        for p in 0..self.meshes[mesh.value()].primitives.len() {
+            let primitive = &self.meshes[mesh.value()].primitives[p];
+            let mut attributes = primitive.attributes.clone();
+            let mut targets = primitive.targets.clone();
+            let all_attributes = std::iter::once((&mut attributes, false))
+                .chain(targets.iter_mut().map(|target| (target, true)));
+            for (attributes, morph_target) in all_attributes {
+                for (semantic, accessor) in attributes.iter_mut() {
+                    let usage = match Usage::of(semantic, morph_target) {
+                        Some(usage) => usage,
+                        None => continue,
+                    };
+                    *accessor = match converted.get(&(*accessor, usage, key)) {
+                        Some(&new) => new,
+                        None => {
+                            let mut values = self.read_accessor_f32(*accessor, buffers)?;
+                            transform.apply(&mut values, usage);
+                            let type_ = self.accessors[accessor.value()].type_.clone();
+                            let new = self.push_accessor(
+                                buffers,
+                                &values,
+                                type_,
+                                AccessorComponentType::Float,
+                                false,
+                                Some(BufferViewTarget::ArrayBuffer),
+                            );
+                            converted.insert((*accessor, usage, key), new);
+                            new
+                        }
+                    };
+                }
+            }
+
+            // Strips and fans are read as triangles, so they're reversed as a list.
+            let primitive = &self.meshes[mesh.value()].primitives[p];
+            let mut indices = primitive.indices;
+            let mut mode = primitive.mode.clone();
+            let triangles = match mode {
+                MeshPrimitiveMode::Triangles
+                | MeshPrimitiveMode::TriangleStrip
+                | MeshPrimitiveMode::TriangleFan
+                    if transform.mirrors =>
+                {
+                    self.read_triangles(primitive, buffers)?
+                }
+                _ => Vec::new(),
+            };
+            if !triangles.is_empty() {
+                let values: Vec<u32> = triangles.iter().flat_map(|&[a, b, c]| [a, c, b]).collect();
+                indices = Some(self.push_indices(buffers, &values));
+                mode = MeshPrimitiveMode::Triangles;
+            }
+
+            let primitive = &mut self.meshes[mesh.value()].primitives[p];
+            primitive.attributes = attributes;
+            primitive.targets = targets;
+            primitive.indices = indices;
+            primitive.mode = mode;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::test_models::*;
+    use crate::*;
+    use kserde::ThingOwned;
+
+    /// The normal of each of a node's triangles in the scene, facing the way it's seen from.
+    fn face_normals(gltf: &GlTf, buffers: &[Vec<u8>], node: usize) -> Vec<[f32; 3]> {
+        let matrix = gltf.world_matrices()[node];
+        let point = |p: [f32; 3]| {
+            [0, 1, 2].map(|r| {
+                matrix[r] * p[0] + matrix[4 + r] * p[1] + matrix[8 + r] * p[2] + matrix[12 + r]
+            })
+        };
+        let mirrored = Transform::new(matrix).mirrors;
+        let mesh = &gltf.meshes[gltf.nodes[node].mesh.unwrap().value()];
+        let mut normals = Vec::new();
+        for primitive in &mesh.primitives {
+            let positions = gltf
+                .read_vec3_attribute(primitive, Semantic::Position, buffers)
+                .unwrap();
+            for [a, b, c] in gltf.read_triangles(primitive, buffers).unwrap() {
+                let [a, b, c] = [a, b, c].map(|v| point(positions[v as usize]));
+                let normal = normalize(cross(subtract(b, a), subtract(c, a)));
+                normals.push(if mirrored { normal.map(|n| -n) } else { normal });
+            }
+        }
+        normals
+    }
+
+    #[test]
+    fn baking_leaves_kept_nodes_and_their_meshes() {
+        let (mut gltf, mut buffers) = cube();
+        let original_positions = gltf
+            .read_vec3_attribute(&gltf.meshes[0].primitives[0], Semantic::Position, &buffers)
+            .unwrap();
+        gltf.nodes[0].translation = Some([1.0, 0.0, 0.0]);
+        let mut light = Node {
+            mesh: Some(MeshIndex::new(0)),
+            translation: Some([0.0, 2.0, 0.0]),
+            ..Default::default()
+        };
+        light
+            .extensions
+            .insert("KHR_lights_punctual".to_string(), ThingOwned::Null);
+        let light = gltf.push(light);
+        let mirrored = gltf.push(Node {
+            mesh: Some(MeshIndex::new(0)),
+            scale: Some([-1.0, 1.0, 1.0]),
+            ..Default::default()
+        });
+        gltf.scenes[0].nodes.extend([light, mirrored]);
+        let positions = world_positions(&gltf, &buffers);
+        let normals: Vec<_> = (0..3).map(|n| face_normals(&gltf, &buffers, n)).collect();
+
+        gltf.bake_transforms(&mut buffers).unwrap();
+        assert_close(&world_positions(&gltf, &buffers), &positions, 1e-6);
+        for (n, normals) in normals.iter().enumerate() {
+            assert_close(&face_normals(&gltf, &buffers, n), normals, 1e-6);
+        }
+        // The node that keeps its place still uses the original mesh and transform.
+        let light = &gltf.nodes[light.value()];
+        assert_eq!(light.mesh, Some(MeshIndex::new(0)));
+        assert_eq!(light.translation, Some([0.0, 2.0, 0.0]));
+        let mesh_positions = gltf
+            .read_vec3_attribute(&gltf.meshes[0].primitives[0], Semantic::Position, &buffers)
+            .unwrap();
+        assert_close(&mesh_positions, &original_positions, 0.0);
+        assert!(gltf.nodes[0].translation.is_none() && gltf.nodes[0].matrix.is_none());
+    }
+
+    #[test]
+    fn baking_keeps_the_place_of_leaves_without_meshes() {
+        let (mut gltf, mut buffers) = cube();
+        gltf.nodes[0].translation = Some([1.0, 0.0, 0.0]);
+        let camera = gltf.push(Camera::new(CameraType::Perspective));
+        let camera = gltf.push(Node {
+            camera: Some(camera),
+            translation: Some([0.0, 2.0, 0.0]),
+            ..Default::default()
+        });
+        let empty = gltf.push(Node {
+            scale: Some([2.0, 2.0, 2.0]),
+            ..Default::default()
+        });
+        gltf.nodes[0].children.extend([camera, empty]);
+        let world = gltf.world_matrices();
+        let positions = world_positions(&gltf, &buffers);
+
+        gltf.bake_transforms(&mut buffers).unwrap();
+        assert_close(&world_positions(&gltf, &buffers), &positions, 1e-6);
+        assert!(gltf.nodes[0].translation.is_none() && gltf.nodes[0].matrix.is_none());
+        for n in [camera.value(), empty.value()] {
+            assert_eq!(gltf.nodes[n].matrix, Some(world[n]));
+            assert!(gltf.nodes[n].translation.is_none() && gltf.nodes[n].scale.is_none());
+            assert_eq!(gltf.world_matrices()[n], world[n]);
+        }
+    }
+
+    #[test]
+    fn baking_copies_meshes_used_with_different_transforms() {
+        let (mut gltf, mut buffers) = cube();
+        gltf.nodes[0].translation = Some([1.0, 0.0, 0.0]);
+        let other = gltf.push(Node {
+            mesh: Some(MeshIndex::new(0)),
+            translation: Some([0.0, 3.0, 0.0]),
+            ..Default::default()
+        });
+        gltf.scenes[0].nodes.push(other);
+        let positions = world_positions(&gltf, &buffers);
+
+        gltf.bake_transforms(&mut buffers).unwrap();
+        assert_eq!(gltf.meshes.len(), 2);
+        assert_ne!(gltf.nodes[0].mesh, gltf.nodes[other.value()].mesh);
+        assert_close(&world_positions(&gltf, &buffers), &positions, 1e-6);
+    }
+
+    #[test]
+    fn baking_leaves_skinned_and_animated_models() {
+        let (original, _) = fox();
+        let (mut gltf, mut buffers) = fox();
+        gltf.bake_transforms(&mut buffers).unwrap();
+        assert_eq!(
+            gltf.to_json_formatted(None),
+            original.to_json_formatted(None)
+        );
+    }
+
+    #[test]
+    fn baking_mirrored_fans_keeps_their_winding() {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        let mut buffers = Vec::new();
+        let square = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        let positions = gltf.push_accessor(
+            &mut buffers,
+            &square,
+            AccessorType::Vec3,
+            AccessorComponentType::Float,
+            false,
+            Some(BufferViewTarget::ArrayBuffer),
+        );
+        let mesh = gltf.push(Mesh::new(vec![MeshPrimitive {
+            mode: MeshPrimitiveMode::TriangleFan,
+            ..MeshPrimitive::new(std::iter::once((Semantic::Position, positions)).collect())
+        }]));
+        gltf.push(Node {
+            mesh: Some(mesh),
+            scale: Some([-1.0, 1.0, 1.0]),
+            ..Default::default()
+        });
+        let normals = face_normals(&gltf, &buffers, 0);
+
+        gltf.bake_transforms(&mut buffers).unwrap();
+        let primitive = &gltf.meshes[0].primitives[0];
+        assert!(matches!(primitive.mode, MeshPrimitiveMode::Triangles));
+        assert_close(&face_normals(&gltf, &buffers, 0), &normals, 1e-6);
+    }
+}
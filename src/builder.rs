@@ -0,0 +1,184 @@
+use crate::*;
+
+/// Constructs a glTF from scratch, with all of its data in a single buffer.
+///
+/// ```ignore
+/// let mut builder = GlTfBuilder::new();
+/// let positions = builder.add_vertex_attribute(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0], AccessorType::Vec3);
+/// let material = builder.add_material(Some("red"), [1.0, 0.0, 0.0, 1.0], 0.0, 1.0);
+/// let primitive = GlTfBuilder::primitive(&[(Semantic::Position, positions)], None, Some(material));
+/// let mesh = builder.add_mesh(None, vec![primitive]);
+/// let node = builder.add_node(Some("triangle"), Some(mesh), &[]);
+/// builder.add_scene(None, &[node]);
+/// let (gltf, buffer) = builder.finish();
+/// ```
+pub struct GlTfBuilder {
+    gltf: GlTf,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Default for GlTfBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GlTfBuilder {
+    /// Starts an empty glTF 2.0 document generated by "kgltf".
+    pub fn new() -> Self {
+        let mut asset = Asset::new("2.0".to_string());
+        asset.generator = Some("kgltf".to_string());
+        Self {
+            gltf: GlTf::new(asset),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Adds an accessor of float vertex attributes, `type_` components at a time.
+    /// `min` and `max` are set, which `POSITION` requires.
+    pub fn add_vertex_attribute(&mut self, values: &[f32], type_: AccessorType) -> AccessorIndex {
+        self.gltf.push_accessor(
+            &mut self.buffers,
+            values,
+            type_,
+            AccessorComponentType::Float,
+            false,
+            Some(BufferViewTarget::ArrayBuffer),
+        )
+    }
+
+    /// Adds an accessor of indices, stored as shorts if they fit.
+    pub fn add_indices(&mut self, indices: &[u32]) -> AccessorIndex {
+        self.gltf.push_indices(&mut self.buffers, indices)
+    }
+
+    /// Adds a metallic-roughness material.
+    pub fn add_material(
+        &mut self,
+        name: Option<&str>,
+        base_color_factor: [f32; 4],
+        metallic_factor: f32,
+        roughness_factor: f32,
+    ) -> MaterialIndex {
+        self.gltf.push(Material {
+            name: name.map(|n| n.to_string()),
+            pbr_metallic_roughness: Some(MaterialPbrMetallicRoughness {
+                base_color_factor,
+                metallic_factor,
+                roughness_factor,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a triangle list primitive to pass to [GlTfBuilder::add_mesh].
+    pub fn primitive(
+        attributes: &[(Semantic, AccessorIndex)],
+        indices: Option<AccessorIndex>,
+        material: Option<MaterialIndex>,
+    ) -> MeshPrimitive {
+        MeshPrimitive {
+            indices,
+            material,
+            ..MeshPrimitive::new(attributes.iter().cloned().collect())
+        }
+    }
+
+    pub fn add_mesh(&mut self, name: Option<&str>, primitives: Vec<MeshPrimitive>) -> MeshIndex {
+        self.gltf.push(Mesh {
+            name: name.map(|n| n.to_string()),
+            ..Mesh::new(primitives)
+        })
+    }
+
+    /// Adds a node without a transform. Use [GlTfBuilder::node_mut] to set one.
+    pub fn add_node(
+        &mut self,
+        name: Option<&str>,
+        mesh: Option<MeshIndex>,
+        children: &[NodeIndex],
+    ) -> NodeIndex {
+        self.gltf.push(Node {
+            children: children.to_vec(),
+            mesh,
+            name: name.map(|n| n.to_string()),
+            ..Default::default()
+        })
+    }
+
+    pub fn node_mut(&mut self, node: NodeIndex) -> &mut Node {
+        &mut self.gltf.nodes[node.value()]
+    }
+
+    /// Adds a scene. The first scene is the one shown when the glTF is loaded.
+    pub fn add_scene(&mut self, name: Option<&str>, nodes: &[NodeIndex]) -> SceneIndex {
+        let scene = self.gltf.push(Scene {
+            nodes: nodes.to_vec(),
+            name: name.map(|n| n.to_string()),
+            ..Default::default()
+        });
+        self.gltf.scene.get_or_insert(scene);
+        scene
+    }
+
+    /// The document so far, for anything the builder doesn't cover.
+    pub fn gltf_mut(&mut self) -> &mut GlTf {
+        &mut self.gltf
+    }
+
+    /// Returns the glTF and the data of its first buffer, which holds everything the builder
+    /// added. It has no `uri` so it can be written as the binary chunk of a GLB.
+    pub fn finish(self) -> (GlTf, Vec<u8>) {
+        (
+            self.gltf,
+            self.buffers.into_iter().next().unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn built_documents_round_trip() {
+        let mut builder = GlTfBuilder::new();
+        let vertices = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let positions = builder.add_vertex_attribute(&vertices, AccessorType::Vec3);
+        let indices = builder.add_indices(&[0, 2, 1]);
+        let material = builder.add_material(Some("red"), [1.0, 0.0, 0.0, 1.0], 0.0, 1.0);
+        let primitive = GlTfBuilder::primitive(
+            &[(Semantic::Position, positions)],
+            Some(indices),
+            Some(material),
+        );
+        let mesh = builder.add_mesh(None, vec![primitive]);
+        let node = builder.add_node(Some("triangle"), Some(mesh), &[]);
+        let scene = builder.add_scene(None, &[node]);
+        // A buffer added after the builder's doesn't replace its data.
+        builder.gltf_mut().push(Buffer::new(0));
+        let (gltf, buffer) = builder.finish();
+
+        let json = gltf.to_json_formatted(None);
+        let gltf = GlTf::from_json(&json).unwrap();
+        assert_eq!(gltf.to_json_formatted(None), json);
+        assert_eq!(gltf.scene, Some(scene));
+        assert_eq!(gltf.buffers[0].byte_length, buffer.len());
+        let buffers = [buffer, Vec::new()];
+        assert_eq!(
+            gltf.read_accessor_f32(positions, &buffers).unwrap(),
+            vertices
+        );
+        assert_eq!(
+            gltf.read_accessor_u32(indices, &buffers).unwrap(),
+            [0, 2, 1]
+        );
+        let primitive = &gltf.meshes[mesh.value()].primitives[0];
+        assert_eq!(primitive.material, Some(material));
+        let pbr = gltf.materials[material.value()]
+            .pbr_metallic_roughness
+            .as_ref();
+        assert_eq!(pbr.map(|p| p.base_color_factor), Some([1.0, 0.0, 0.0, 1.0]));
+    }
+}
@@ -0,0 +1,203 @@
+//! Collapsing identical objects into one.
+
+use kserde::*;
+use std::collections::HashMap;
+
+use crate::prune::{new_indices, retain_used};
+use crate::*;
+
+/// Identifies an object by its Json, without its name or where its data is stored,
+/// and by its data.
+type Key = (String, Vec<u8>);
+
+fn json_key<T: Serialize<JsonWriter>>(object: &T) -> String {
+    let mut writer = JsonWriter::new(None);
+    object.serialize(&mut writer);
+    writer.finish()
+}
+
+impl GlTf {
+    /// Replaces objects that are identical to an earlier object of the same type with
+    /// that object, and removes them.
+    ///
+    /// Samplers, images, textures, materials and accessors are compared by their properties
+    /// other than their names. Images in buffer views and accessors are compared by their data,
+    /// which is read from `buffers`. Buffer views that are no longer used are left in place,
+    /// they can be removed with [GlTf::prune].
+    ///
+    /// Returns an error without changing anything if an object uses an extension whose
+    /// indices aren't known, as they can't be updated.
+    pub fn dedup(&mut self, buffers: &[Vec<u8>]) -> Result<(), ExtensionError> {
+        self.check_extension_indices()?;
+        // Objects are compared after the objects they refer to have been deduplicated.
+        let keys = self
+            .samplers
+            .iter()
+            .map(|sampler| {
+                let sampler = Sampler {
+                    name: None,
+                    ..sampler.clone()
+                };
+                Some((json_key(&sampler), Vec::new()))
+            })
+            .collect();
+        self.dedup_list::<Sampler>(IndexKind::Sampler, keys);
+
+        let keys = self
+            .images
+            .iter()
+            .map(|image| {
+                let data = match image.buffer_view {
+                    Some(view) => self.buffer_view_data(view, buffers).ok()?.0.to_vec(),
+                    None => Vec::new(),
+                };
+                let image = Image {
+                    name: None,
+                    buffer_view: None,
+                    ..image.clone()
+                };
+                Some((json_key(&image), data))
+            })
+            .collect();
+        self.dedup_list::<Image>(IndexKind::Image, keys);
+
+        let keys = self
+            .textures
+            .iter()
+            .map(|texture| {
+                let texture = Texture {
+                    name: None,
+                    ..texture.clone()
+                };
+                Some((json_key(&texture), Vec::new()))
+            })
+            .collect();
+        self.dedup_list::<Texture>(IndexKind::Texture, keys);
+
+        let keys = self
+            .materials
+            .iter()
+            .map(|material| {
+                let material = Material {
+                    name: None,
+                    ..material.clone()
+                };
+                Some((json_key(&material), Vec::new()))
+            })
+            .collect();
+        self.dedup_list::<Material>(IndexKind::Material, keys);
+
+        let keys = (0..self.accessors.len())
+            .map(|a| {
+                // Each component is read as its bytes, which includes any sparse values.
+                let components = self
+                    .read_accessor(AccessorIndex::new(a), buffers, |_, _, bytes| {
+                        let mut component = [0; 4];
+                        component[..bytes.len()].copy_from_slice(bytes);
+                        component
+                    })
+                    .ok()?;
+                let accessor = &self.accessors[a];
+                // Index and vertex data can't share a buffer view, so the target has to match too.
+                let target = accessor
+                    .buffer_view
+                    .and_then(|view| self.get(view))
+                    .map(|view| &view.target);
+                let accessor = Accessor {
+                    name: None,
+                    buffer_view: None,
+                    byte_offset: 0,
+                    sparse: None,
+                    ..accessor.clone()
+                };
+                let key = format!("{}{:?}", json_key(&accessor), target);
+                Some((key, components.concat()))
+            })
+            .collect();
+        self.dedup_list::<Accessor>(IndexKind::Accessor, keys);
+        Ok(())
+    }
+
+    /// Removes the objects whose key is the same as an earlier object's, and updates the
+    /// indices that refer to them. Objects without a key are kept.
+    fn dedup_list<T: RootObject>(&mut self, kind: IndexKind, keys: Vec<Option<Key>>) {
+        let mut first = HashMap::new();
+        let original: Vec<usize> = keys
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| key.map_or(i, |key| *first.entry(key).or_insert(i)))
+            .collect();
+        let kept: Vec<bool> = original.iter().enumerate().map(|(i, &o)| i == o).collect();
+        if kept.iter().all(|&kept| kept) {
+            return;
+        }
+        let new = new_indices(&kept);
+        retain_used(T::list_mut(self), &kept);
+        self.visit_indices_mut(&mut |k, index| {
+            if k == kind {
+                if let Some(&original) = original.get(*index) {
+                    *index = new[original];
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+    use kserde::ThingOwned;
+
+    #[test]
+    fn dedup_round_trips_models() {
+        let (original, _) = fox();
+        let (mut gltf, buffers) = fox();
+        gltf.dedup(&buffers).unwrap();
+        assert_eq!(
+            gltf.to_json_formatted(None),
+            original.to_json_formatted(None)
+        );
+
+        // A merged copy of the cube uses the original objects once it's deduplicated,
+        // and its buffer is left unused.
+        let (cube, cube_buffers) = cube();
+        let (mut gltf, mut buffers) = (cube.clone(), cube_buffers.clone());
+        let options = MergeOptions {
+            concatenate_buffers: false,
+            scenes: MergeScenes::Merge,
+        };
+        gltf.merge(&mut buffers, cube.clone(), cube_buffers.clone(), &options)
+            .unwrap();
+        gltf.dedup(&buffers).unwrap();
+        gltf.prune(&mut buffers, &PruneOptions::default()).unwrap();
+        assert_eq!(gltf.accessors.len(), cube.accessors.len());
+        assert_eq!(gltf.buffer_views.len(), cube.buffer_views.len());
+        assert_eq!(gltf.buffers.len(), cube.buffers.len());
+        assert_eq!(gltf.images.len(), cube.images.len());
+        assert_eq!(gltf.materials.len(), cube.materials.len());
+        assert_eq!(gltf.meshes.len(), 2);
+        assert_eq!(
+            super::json_key(&gltf.meshes[0]),
+            super::json_key(&gltf.meshes[1])
+        );
+        let positions = world_positions(&cube, &cube_buffers);
+        assert_close(
+            &world_positions(&gltf, &buffers),
+            &[positions.clone(), positions].concat(),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn dedup_refuses_unknown_extensions() {
+        let (mut gltf, buffers) = cube();
+        gltf.push(gltf.materials[0].clone());
+        gltf.textures[0]
+            .extensions
+            .insert("EXT_unknown".to_string(), ThingOwned::Null);
+        let result = gltf.dedup(&buffers);
+        assert!(matches!(result, Err(ExtensionError::UnknownIndices(_))));
+        assert_eq!(gltf.materials.len(), 2);
+    }
+}
@@ -0,0 +1,537 @@
+//! Decoding of the attribute values of each point.
+
+use super::buffer::DecoderBuffer;
+use super::connectivity::Connectivity;
+use super::corner_table::*;
+use super::prediction::*;
+use super::rans::decode_symbols;
+use super::transform::{Octahedron, TRANSFORM_OCTAHEDRON, TRANSFORM_WRAP};
+use super::traversal::{traverse, TraversalMethod};
+use super::{DracoAttribute, DracoError};
+
+const ATTRIBUTE_POSITION: u8 = 0;
+const NUM_ATTRIBUTE_TYPES: u8 = 5;
+
+const DATA_TYPE_INT8: u8 = 1;
+const DATA_TYPE_UINT8: u8 = 2;
+const DATA_TYPE_INT16: u8 = 3;
+const DATA_TYPE_UINT16: u8 = 4;
+const DATA_TYPE_INT32: u8 = 5;
+const DATA_TYPE_UINT32: u8 = 6;
+const DATA_TYPE_INT64: u8 = 7;
+const DATA_TYPE_UINT64: u8 = 8;
+const DATA_TYPE_FLOAT32: u8 = 9;
+const DATA_TYPE_FLOAT64: u8 = 10;
+const DATA_TYPE_BOOL: u8 = 11;
+
+const DECODER_GENERIC: u8 = 0;
+const DECODER_INTEGER: u8 = 1;
+const DECODER_QUANTIZATION: u8 = 2;
+const DECODER_NORMALS: u8 = 3;
+
+const MESH_VERTEX_ATTRIBUTE: u8 = 0;
+
+fn data_type_size(data_type: u8) -> usize {
+    match data_type {
+        DATA_TYPE_INT8 | DATA_TYPE_UINT8 | DATA_TYPE_BOOL => 1,
+        DATA_TYPE_INT16 | DATA_TYPE_UINT16 => 2,
+        DATA_TYPE_INT32 | DATA_TYPE_UINT32 | DATA_TYPE_FLOAT32 => 4,
+        _ => 8,
+    }
+}
+
+/// How the points of an attributes decoder are ordered.
+enum Sequencer {
+    /// Points are stored in order.
+    Linear,
+    /// Points are stored in the order a traversal of the mesh visits them.
+    Mesh {
+        attribute_data: Option<usize>,
+        per_corner: bool,
+        traversal: TraversalMethod,
+    },
+}
+
+struct Descriptor {
+    attribute_type: u8,
+    data_type: u8,
+    num_components: usize,
+    unique_id: u32,
+    decoder_type: u8,
+}
+
+/// The values of an attribute while it's decoded.
+struct Attribute {
+    descriptor: Descriptor,
+    /// Maps each point to a value.
+    point_map: Vec<u32>,
+    /// The integer values before they are converted to the attribute's type.
+    portable: Vec<i32>,
+    values: Vec<f64>,
+}
+
+pub(crate) fn decode_attributes(
+    buffer: &mut DecoderBuffer,
+    connectivity: &mut Connectivity,
+) -> Result<Vec<DracoAttribute>, DracoError> {
+    let num_decoders = buffer.u8()? as usize;
+    let mut sequencers = Vec::new();
+    for decoder_id in 0..num_decoders {
+        sequencers.push(create_sequencer(buffer, connectivity, decoder_id)?);
+    }
+
+    let mut descriptors = Vec::new();
+    for _ in 0..num_decoders {
+        descriptors.push(decode_descriptors(buffer)?);
+    }
+
+    let mut attributes: Vec<Attribute> = Vec::new();
+    for (sequencer, descriptors) in sequencers.iter().zip(descriptors) {
+        decode_values(
+            buffer,
+            connectivity,
+            sequencer,
+            descriptors,
+            &mut attributes,
+        )?;
+    }
+
+    let num_points = connectivity.num_points;
+    let mut decoded = Vec::new();
+    for attribute in attributes {
+        // Store the value of every point.
+        let num_components = attribute.descriptor.num_components;
+        // Every point has to be in the point map, which was already allocated.
+        let mut values =
+            Vec::with_capacity(num_points.min(attribute.point_map.len()) * num_components);
+        for point in 0..num_points {
+            let value = *attribute
+                .point_map
+                .get(point)
+                .ok_or(DracoError::MalformedData)? as usize;
+            values.extend_from_slice(
+                attribute
+                    .values
+                    .get(value * num_components..(value + 1) * num_components)
+                    .ok_or(DracoError::MalformedData)?,
+            );
+        }
+        decoded.push(DracoAttribute {
+            unique_id: attribute.descriptor.unique_id,
+            num_components,
+            values,
+        });
+    }
+    Ok(decoded)
+}
+
+fn create_sequencer(
+    buffer: &mut DecoderBuffer,
+    connectivity: &mut Connectivity,
+    decoder_id: usize,
+) -> Result<Sequencer, DracoError> {
+    let edgebreaker = match &mut connectivity.edgebreaker {
+        Some(edgebreaker) => edgebreaker,
+        None => return Ok(Sequencer::Linear),
+    };
+
+    let attribute_data_id = buffer.i8()?;
+    let decoder_type = buffer.u8()?;
+    let traversal = match buffer.u8()? {
+        0 => TraversalMethod::DepthFirst,
+        1 => TraversalMethod::PredictionDegree,
+        _ => Err(DracoError::MalformedData)?,
+    };
+
+    let attribute_data = if attribute_data_id >= 0 {
+        let data = edgebreaker
+            .attribute_data
+            .get_mut(attribute_data_id as usize)
+            .ok_or(DracoError::MalformedData)?;
+        data.decoder_id = Some(decoder_id);
+        Some(attribute_data_id as usize)
+    } else {
+        if edgebreaker.position_decoder_id.is_some() {
+            Err(DracoError::MalformedData)?
+        }
+        edgebreaker.position_decoder_id = Some(decoder_id);
+        None
+    };
+
+    let per_corner = decoder_type != MESH_VERTEX_ATTRIBUTE;
+    match attribute_data {
+        // Per vertex attributes with their own data ignore its seams.
+        Some(id) if !per_corner => edgebreaker.attribute_data[id].is_connectivity_used = false,
+        None if per_corner => Err(DracoError::MalformedData)?,
+        _ => (),
+    }
+    Ok(Sequencer::Mesh {
+        attribute_data,
+        per_corner,
+        traversal: if per_corner {
+            TraversalMethod::DepthFirst
+        } else {
+            traversal
+        },
+    })
+}
+
+fn decode_descriptors(buffer: &mut DecoderBuffer) -> Result<Vec<Descriptor>, DracoError> {
+    let num_attributes = buffer.varint_u32()? as usize;
+    // Every attribute takes at least 5 bytes.
+    if num_attributes == 0 || num_attributes > 5 * buffer.remaining() {
+        Err(DracoError::MalformedData)?
+    }
+
+    let mut descriptors = Vec::with_capacity(num_attributes);
+    for _ in 0..num_attributes {
+        let attribute_type = buffer.u8()?;
+        let data_type = buffer.u8()?;
+        let num_components = buffer.u8()? as usize;
+        let _normalized = buffer.u8()?;
+        let unique_id = buffer.varint_u32()?;
+        if attribute_type >= NUM_ATTRIBUTE_TYPES
+            || !(DATA_TYPE_INT8..=DATA_TYPE_BOOL).contains(&data_type)
+            || num_components == 0
+        {
+            Err(DracoError::MalformedData)?
+        }
+        descriptors.push(Descriptor {
+            attribute_type,
+            data_type,
+            num_components,
+            unique_id,
+            decoder_type: DECODER_GENERIC,
+        });
+    }
+
+    for descriptor in descriptors.iter_mut() {
+        descriptor.decoder_type = buffer.u8()?;
+        let valid = match descriptor.decoder_type {
+            DECODER_GENERIC | DECODER_INTEGER => true,
+            DECODER_QUANTIZATION => descriptor.data_type == DATA_TYPE_FLOAT32,
+            DECODER_NORMALS => {
+                descriptor.data_type == DATA_TYPE_FLOAT32 && descriptor.num_components == 3
+            }
+            _ => false,
+        };
+        if !valid {
+            Err(DracoError::MalformedData)?
+        }
+    }
+    Ok(descriptors)
+}
+
+/// Decodes the attributes of one attributes decoder and appends them to `attributes`.
+fn decode_values(
+    buffer: &mut DecoderBuffer,
+    connectivity: &mut Connectivity,
+    sequencer: &Sequencer,
+    descriptors: Vec<Descriptor>,
+    attributes: &mut Vec<Attribute>,
+) -> Result<(), DracoError> {
+    let num_points = connectivity.num_points;
+    let corners = &connectivity.corners;
+
+    // Order the points and map them to the values.
+    let edgebreaker = match (sequencer, &mut connectivity.edgebreaker) {
+        (Sequencer::Mesh { .. }, Some(edgebreaker)) => edgebreaker,
+        _ => {
+            let point_ids: Vec<u32> = (0..num_points as u32).collect();
+            let point_map = point_ids.clone();
+            return decode_attribute_values(
+                buffer,
+                point_ids,
+                point_map,
+                None,
+                descriptors,
+                attributes,
+            );
+        }
+    };
+    let (attribute_data, per_corner, traversal) = match sequencer {
+        Sequencer::Mesh {
+            attribute_data,
+            per_corner,
+            traversal,
+        } => (*attribute_data, *per_corner, *traversal),
+        Sequencer::Linear => unreachable!(),
+    };
+
+    let corner_table = &edgebreaker.corner_table;
+    let (attribute_connectivity, encoding_data) = match attribute_data {
+        Some(id) => {
+            let data = &mut edgebreaker.attribute_data[id];
+            (Some(&data.connectivity), &mut data.encoding_data)
+        }
+        None => (None, &mut edgebreaker.position_encoding_data),
+    };
+    let attribute_table = attribute_connectivity
+        .filter(|_| per_corner)
+        .map(|c| c.view(corner_table));
+    let table: &dyn Table = match &attribute_table {
+        Some(table) => table,
+        None => corner_table,
+    };
+
+    let point_ids = traverse(table, corners, encoding_data, traversal)?;
+    let mut point_map = vec![0; num_points];
+    for (corner, &point) in corners.iter().enumerate() {
+        let vertex = table.vertex(corner as u32);
+        if vertex == INVALID {
+            Err(DracoError::MalformedData)?
+        }
+        let value = encoding_data.vertex_to_value[vertex as usize] as usize;
+        if point as usize >= num_points || value >= point_ids.len() {
+            Err(DracoError::MalformedData)?
+        }
+        point_map[point as usize] = value as u32;
+    }
+    let mesh = MeshData {
+        table,
+        encoding_data,
+    };
+    decode_attribute_values(
+        buffer,
+        point_ids,
+        point_map,
+        Some(mesh),
+        descriptors,
+        attributes,
+    )
+}
+
+fn decode_attribute_values(
+    buffer: &mut DecoderBuffer,
+    point_ids: Vec<u32>,
+    point_map: Vec<u32>,
+    mesh: Option<MeshData>,
+    descriptors: Vec<Descriptor>,
+    attributes: &mut Vec<Attribute>,
+) -> Result<(), DracoError> {
+    let num_values = point_ids.len();
+    let first = attributes.len();
+
+    // All attributes are decoded to their portable form first.
+    for descriptor in descriptors {
+        let mut attribute = Attribute {
+            descriptor,
+            point_map: point_map.clone(),
+            portable: Vec::new(),
+            values: Vec::new(),
+        };
+        let descriptor = &attribute.descriptor;
+        if descriptor.decoder_type == DECODER_GENERIC {
+            attribute.values = decode_raw_values(buffer, descriptor, num_values)?;
+        } else {
+            let positions = attributes
+                .iter()
+                .find(|a| a.descriptor.attribute_type == ATTRIBUTE_POSITION)
+                .filter(|a| a.descriptor.num_components == 3 && !a.portable.is_empty())
+                .map(|a| Positions {
+                    values: &a.portable,
+                    point_map: &a.point_map,
+                });
+            attribute.portable =
+                decode_integer_values(buffer, descriptor, &point_ids, mesh, positions)?;
+        }
+        attributes.push(attribute);
+    }
+
+    // Then the data to convert them to their final values.
+    let mut transforms = Vec::new();
+    for attribute in &attributes[first..] {
+        let descriptor = &attribute.descriptor;
+        transforms.push(match descriptor.decoder_type {
+            DECODER_QUANTIZATION => {
+                let mut min_values = Vec::with_capacity(descriptor.num_components);
+                for _ in 0..descriptor.num_components {
+                    min_values.push(buffer.f32()?);
+                }
+                let range = buffer.f32()?;
+                let quantization_bits = buffer.u8()? as u32;
+                if !(1..=30).contains(&quantization_bits) {
+                    Err(DracoError::MalformedData)?
+                }
+                FinalTransform::Quantization {
+                    min_values,
+                    range,
+                    quantization_bits,
+                }
+            }
+            DECODER_NORMALS => FinalTransform::Octahedron(Octahedron::new(buffer.u8()? as u32)?),
+            _ => FinalTransform::None,
+        });
+    }
+
+    for (attribute, transform) in attributes[first..].iter_mut().zip(transforms) {
+        let descriptor = &attribute.descriptor;
+        match transform {
+            FinalTransform::Quantization {
+                min_values,
+                range,
+                quantization_bits,
+            } => {
+                let max_quantized_value = ((1u32 << quantization_bits) - 1) as f32;
+                let delta = range / max_quantized_value;
+                attribute.values = attribute
+                    .portable
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &q)| (q as f32 * delta + min_values[i % min_values.len()]) as f64)
+                    .collect();
+            }
+            FinalTransform::Octahedron(octahedron) => {
+                attribute.values = attribute
+                    .portable
+                    .chunks_exact(2)
+                    .flat_map(|st| octahedron.coords_to_unit_vector(st[0], st[1]).to_vec())
+                    .map(|v| v as f64)
+                    .collect();
+            }
+            FinalTransform::None if descriptor.decoder_type == DECODER_INTEGER => {
+                let data_type = descriptor.data_type;
+                attribute.values = attribute
+                    .portable
+                    .iter()
+                    .map(|&v| integer_value(data_type, v))
+                    .collect::<Result<_, _>>()?;
+            }
+            FinalTransform::None => (),
+        }
+    }
+    Ok(())
+}
+
+/// Converts a portable integer value to the attribute's type.
+fn integer_value(data_type: u8, value: i32) -> Result<f64, DracoError> {
+    Ok(match data_type {
+        DATA_TYPE_INT8 => value as i8 as f64,
+        DATA_TYPE_UINT8 => value as u8 as f64,
+        DATA_TYPE_INT16 => value as i16 as f64,
+        DATA_TYPE_UINT16 => value as u16 as f64,
+        DATA_TYPE_INT32 | DATA_TYPE_INT64 => value as f64,
+        DATA_TYPE_UINT32 | DATA_TYPE_UINT64 => value as u32 as f64,
+        DATA_TYPE_BOOL => (value != 0) as u8 as f64,
+        _ => Err(DracoError::MalformedData)?,
+    })
+}
+
+enum FinalTransform {
+    None,
+    Quantization {
+        min_values: Vec<f32>,
+        range: f32,
+        quantization_bits: u32,
+    },
+    Octahedron(Octahedron),
+}
+
+/// Reads values that are stored without compression.
+fn decode_raw_values(
+    buffer: &mut DecoderBuffer,
+    descriptor: &Descriptor,
+    num_values: usize,
+) -> Result<Vec<f64>, DracoError> {
+    let count = num_values * descriptor.num_components;
+    let size = data_type_size(descriptor.data_type);
+    let data = buffer.bytes(count.checked_mul(size).ok_or(DracoError::MalformedData)?)?;
+    Ok(data
+        .chunks_exact(size)
+        .map(|b| match descriptor.data_type {
+            DATA_TYPE_INT8 => b[0] as i8 as f64,
+            DATA_TYPE_UINT8 => b[0] as f64,
+            DATA_TYPE_BOOL => (b[0] != 0) as u8 as f64,
+            DATA_TYPE_INT16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+            DATA_TYPE_UINT16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+            DATA_TYPE_INT32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            DATA_TYPE_UINT32 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            DATA_TYPE_FLOAT32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            DATA_TYPE_INT64 => {
+                i64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f64
+            }
+            DATA_TYPE_UINT64 => {
+                u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f64
+            }
+            DATA_TYPE_FLOAT64 => {
+                f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+            }
+            _ => unreachable!(),
+        })
+        .collect())
+}
+
+/// Reads the integer values of an attribute, reversing their prediction.
+fn decode_integer_values(
+    buffer: &mut DecoderBuffer,
+    descriptor: &Descriptor,
+    point_ids: &[u32],
+    mesh: Option<MeshData>,
+    positions: Option<Positions>,
+) -> Result<Vec<i32>, DracoError> {
+    let is_normal = descriptor.decoder_type == DECODER_NORMALS;
+    let method = buffer.i8()?;
+    if !(PREDICTION_NONE..=6).contains(&method) {
+        Err(DracoError::MalformedData)?
+    }
+    let mut scheme = None;
+    if method != PREDICTION_NONE {
+        let transform_type = buffer.i8()?;
+        if !(-1..=3).contains(&transform_type) {
+            Err(DracoError::MalformedData)?
+        }
+        // Like the reference decoder, unsupported transforms disable the prediction.
+        let supported = if is_normal {
+            transform_type >= TRANSFORM_OCTAHEDRON
+        } else {
+            transform_type == TRANSFORM_WRAP
+        };
+        if supported {
+            scheme = Some(PredictionScheme::new(
+                method,
+                transform_type,
+                mesh,
+                positions,
+            )?);
+        }
+    }
+
+    let num_components = if is_normal {
+        2
+    } else {
+        descriptor.num_components
+    };
+    let count = point_ids.len() * num_components;
+    let mut values: Vec<i32> = if buffer.u8()? > 0 {
+        decode_symbols(count, num_components, buffer)?
+            .into_iter()
+            .map(|v| v as i32)
+            .collect()
+    } else {
+        let num_bytes = buffer.u8()? as usize;
+        if count > 0 && (num_bytes == 0 || num_bytes > 4) {
+            Err(DracoError::MalformedData)?
+        }
+        let data = buffer.bytes(count * num_bytes)?;
+        data.chunks_exact(num_bytes.max(1))
+            .map(|b| {
+                let mut bytes = [0; 4];
+                bytes[..b.len()].copy_from_slice(b);
+                i32::from_le_bytes(bytes)
+            })
+            .collect()
+    };
+
+    if count > 0 && !scheme.as_ref().is_some_and(|s| s.corrections_positive()) {
+        // Signed values are stored with the sign in the lowest bit.
+        for value in values.iter_mut() {
+            let v = *value as u32;
+            *value = ((v >> 1) as i32) ^ -((v & 1) as i32);
+        }
+    }
+    if let Some(scheme) = &mut scheme {
+        scheme.decode_prediction_data(buffer, point_ids.len())?;
+        scheme.compute_original_values(&mut values, num_components, point_ids)?;
+    }
+    Ok(values)
+}
@@ -0,0 +1,122 @@
+use super::DracoError;
+
+/// A cursor over Draco encoded data. Values are little endian.
+#[derive(Clone)]
+pub(crate) struct DecoderBuffer<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> DecoderBuffer<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn bytes(&mut self, length: usize) -> Result<&'a [u8], DracoError> {
+        if length > self.remaining() {
+            Err(DracoError::MalformedData)?
+        }
+        let bytes = &self.data[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, DracoError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn i8(&mut self) -> Result<i8, DracoError> {
+        Ok(self.u8()? as i8)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, DracoError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, DracoError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, DracoError> {
+        Ok(self.u32()? as i32)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, DracoError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    /// Decodes an unsigned LEB128 value.
+    pub fn varint(&mut self) -> Result<u64, DracoError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DracoError::MalformedData)
+    }
+
+    pub fn varint_u32(&mut self) -> Result<u32, DracoError> {
+        let value = self.varint()?;
+        if value > u32::MAX as u64 {
+            Err(DracoError::MalformedData)?
+        }
+        Ok(value as u32)
+    }
+
+    /// Starts reading individual bits from the current position.
+    /// The buffer is advanced past the bits with [DecoderBuffer::end_bits].
+    pub fn start_bits(&self) -> BitReader<'a> {
+        BitReader::new(&self.data[self.position..])
+    }
+
+    pub fn end_bits(&mut self, bits: BitReader<'a>) {
+        self.position += bits.bit_offset.div_ceil(8);
+    }
+}
+
+/// Reads bits starting from the least significant bit of each byte.
+/// Reading past the end of the data returns zeroes, like the reference decoder.
+#[derive(Clone)]
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    bit_offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            bit_offset: 0,
+        }
+    }
+
+    pub fn bit(&mut self) -> u32 {
+        let byte = self.bit_offset / 8;
+        if byte >= self.data.len() {
+            return 0;
+        }
+        let bit = (self.data[byte] >> (self.bit_offset % 8)) & 1;
+        self.bit_offset += 1;
+        bit as u32
+    }
+
+    pub fn bits(&mut self, count: u32) -> Result<u32, DracoError> {
+        if count > 32 {
+            Err(DracoError::MalformedData)?
+        }
+        let mut value = 0;
+        for i in 0..count {
+            value |= self.bit() << i;
+        }
+        Ok(value)
+    }
+}
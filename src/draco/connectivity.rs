@@ -0,0 +1,714 @@
+//! Decoding of the triangles of a mesh, either stored sequentially or
+//! compressed with Edgebreaker.
+
+use super::buffer::{BitReader, DecoderBuffer};
+use super::corner_table::*;
+use super::rans::{decode_symbols, RAnsBitDecoder};
+use super::DracoError;
+use std::collections::HashMap;
+
+const SEQUENTIAL_COMPRESSED_INDICES: u8 = 0;
+
+const EDGEBREAKER_STANDARD: u8 = 0;
+const EDGEBREAKER_VALENCE: u8 = 2;
+
+const TOPOLOGY_C: u32 = 0;
+const TOPOLOGY_S: u32 = 1;
+const TOPOLOGY_L: u32 = 3;
+const TOPOLOGY_R: u32 = 5;
+const TOPOLOGY_E: u32 = 7;
+
+// Valence contexts store symbols in this order.
+const VALENCE_SYMBOLS: [u32; 5] = [TOPOLOGY_C, TOPOLOGY_S, TOPOLOGY_L, TOPOLOGY_R, TOPOLOGY_E];
+
+pub(crate) struct Connectivity {
+    /// The point index of each corner.
+    pub corners: Vec<u32>,
+    pub num_points: usize,
+    pub edgebreaker: Option<EdgebreakerData>,
+}
+
+/// The order in which vertices were visited, which attribute predictions depend on.
+#[derive(Default)]
+pub(crate) struct EncodingData {
+    pub vertex_to_value: Vec<i32>,
+    pub value_to_corner: Vec<u32>,
+}
+
+impl EncodingData {
+    fn new(num_vertices: usize) -> Self {
+        Self {
+            vertex_to_value: vec![0; num_vertices],
+            value_to_corner: Vec::new(),
+        }
+    }
+}
+
+/// Connectivity for attributes that are not stored per vertex of the mesh's corner table.
+pub(crate) struct AttributeData {
+    pub decoder_id: Option<usize>,
+    pub connectivity: AttributeConnectivity,
+    pub is_connectivity_used: bool,
+    pub encoding_data: EncodingData,
+}
+
+pub(crate) struct EdgebreakerData {
+    pub corner_table: CornerTable,
+    pub attribute_data: Vec<AttributeData>,
+    pub position_encoding_data: EncodingData,
+    pub position_decoder_id: Option<usize>,
+}
+
+pub(crate) fn decode_sequential(buffer: &mut DecoderBuffer) -> Result<Connectivity, DracoError> {
+    let num_faces = buffer.varint_u32()? as usize;
+    let num_points = buffer.varint_u32()? as usize;
+    if num_faces > u32::MAX as usize / 3 {
+        Err(DracoError::MalformedData)?
+    }
+
+    let method = buffer.u8()?;
+    // The counts aren't trusted, so no more is reserved than the rest of the data could hold.
+    let mut corners = Vec::with_capacity((num_faces * 3).min(buffer.remaining()));
+    if method == SEQUENTIAL_COMPRESSED_INDICES {
+        // Indices are stored as the difference to the previous index.
+        let symbols = decode_symbols(num_faces * 3, 1, buffer)?;
+        let mut last = 0i64;
+        for symbol in symbols {
+            let mut difference = (symbol >> 1) as i64;
+            if symbol & 1 != 0 {
+                if difference > last {
+                    Err(DracoError::MalformedData)?
+                }
+                difference = -difference;
+            }
+            last += difference;
+            corners.push(last as u32);
+        }
+    } else {
+        // Each index takes at least a byte.
+        if num_faces * 3 > buffer.remaining() {
+            Err(DracoError::MalformedData)?
+        }
+        for _ in 0..num_faces * 3 {
+            corners.push(if num_points < 1 << 8 {
+                buffer.u8()? as u32
+            } else if num_points < 1 << 16 {
+                buffer.u16()? as u32
+            } else if num_points < 1 << 21 {
+                buffer.varint_u32()?
+            } else {
+                buffer.u32()?
+            });
+        }
+    }
+
+    if corners.iter().any(|&c| c as usize >= num_points) {
+        Err(DracoError::MalformedData)?
+    }
+    Ok(Connectivity {
+        corners,
+        num_points,
+        edgebreaker: None,
+    })
+}
+
+struct TopologySplit {
+    source_symbol: u32,
+    split_symbol: u32,
+    source_edge: u32,
+}
+
+struct Valence {
+    min_valence: i32,
+    max_valence: i32,
+    context_symbols: Vec<Vec<u32>>,
+    context_counters: Vec<i32>,
+    vertex_valences: Vec<i32>,
+    active_context: Option<usize>,
+    last_symbol: u32,
+}
+
+struct TraversalDecoder<'a> {
+    symbols: BitReader<'a>,
+    start_faces: RAnsBitDecoder<'a>,
+    attribute_seams: Vec<RAnsBitDecoder<'a>>,
+    valence: Option<Valence>,
+}
+
+impl<'a> TraversalDecoder<'a> {
+    fn start(
+        buffer: &mut DecoderBuffer<'a>,
+        traversal: u8,
+        num_vertices: usize,
+        num_faces: usize,
+        num_attribute_data: usize,
+    ) -> Result<Self, DracoError> {
+        let symbols = if traversal == EDGEBREAKER_STANDARD {
+            let length = buffer.varint()?;
+            if length > buffer.remaining() as u64 {
+                Err(DracoError::MalformedData)?
+            }
+            BitReader::new(buffer.bytes(length as usize)?)
+        } else {
+            BitReader::new(&[])
+        };
+
+        let start_faces = RAnsBitDecoder::start(buffer)?;
+        let mut attribute_seams = Vec::new();
+        for _ in 0..num_attribute_data {
+            attribute_seams.push(RAnsBitDecoder::start(buffer)?);
+        }
+
+        let valence = if traversal == EDGEBREAKER_VALENCE {
+            // Symbols are split into contexts by the valence of the active vertex.
+            let mode = buffer.u8()?;
+            if mode != 0 {
+                Err(DracoError::MalformedData)?
+            }
+            let (min_valence, max_valence) = (2, 7);
+            let mut context_symbols = Vec::new();
+            let mut context_counters = Vec::new();
+            for _ in min_valence..=max_valence {
+                let count = buffer.varint_u32()? as usize;
+                if count > num_faces {
+                    Err(DracoError::MalformedData)?
+                }
+                context_symbols.push(decode_symbols(count, 1, buffer)?);
+                context_counters.push(count as i32);
+            }
+            Some(Valence {
+                min_valence,
+                max_valence,
+                context_symbols,
+                context_counters,
+                vertex_valences: vec![0; num_vertices],
+                active_context: None,
+                last_symbol: TOPOLOGY_E,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            symbols,
+            start_faces,
+            attribute_seams,
+            valence,
+        })
+    }
+
+    fn decode_symbol(&mut self) -> Result<u32, DracoError> {
+        if let Some(valence) = &mut self.valence {
+            valence.last_symbol = match valence.active_context {
+                Some(context) => {
+                    valence.context_counters[context] -= 1;
+                    let counter = valence.context_counters[context];
+                    if counter < 0 {
+                        Err(DracoError::MalformedData)?
+                    }
+                    let symbol = valence.context_symbols[context][counter as usize];
+                    *VALENCE_SYMBOLS
+                        .get(symbol as usize)
+                        .ok_or(DracoError::MalformedData)?
+                }
+                // The first symbol is always an E.
+                None => TOPOLOGY_E,
+            };
+            Ok(valence.last_symbol)
+        } else {
+            let symbol = self.symbols.bit();
+            if symbol == TOPOLOGY_C {
+                Ok(symbol)
+            } else {
+                Ok(symbol | self.symbols.bits(2)? << 1)
+            }
+        }
+    }
+
+    fn new_active_corner_reached(&mut self, table: &CornerTable, corner: u32) {
+        if let Some(valence) = &mut self.valence {
+            let next = next(corner);
+            let previous = previous(corner);
+            let increments = match valence.last_symbol {
+                TOPOLOGY_C | TOPOLOGY_S => [0, 1, 1],
+                TOPOLOGY_R => [1, 1, 2],
+                TOPOLOGY_L => [1, 2, 1],
+                TOPOLOGY_E => [2, 2, 2],
+                _ => [0, 0, 0],
+            };
+            for (&c, &increment) in [corner, next, previous].iter().zip(increments.iter()) {
+                if increment > 0 {
+                    valence.vertex_valences[table.vertex(c) as usize] += increment;
+                }
+            }
+            let active = valence.vertex_valences[table.vertex(next) as usize];
+            let clamped = active.max(valence.min_valence).min(valence.max_valence);
+            valence.active_context = Some((clamped - valence.min_valence) as usize);
+        }
+    }
+
+    fn merge_vertices(&mut self, destination: u32, source: u32) {
+        if let Some(valence) = &mut self.valence {
+            valence.vertex_valences[destination as usize] +=
+                valence.vertex_valences[source as usize];
+        }
+    }
+}
+
+/// Returns the split symbol and edge if the symbol is the source of a topology split.
+fn topology_split(
+    splits: &mut Vec<TopologySplit>,
+    encoder_symbol: u32,
+) -> Result<Option<(u32, u32)>, DracoError> {
+    match splits.last() {
+        Some(split) if split.source_symbol > encoder_symbol => Err(DracoError::MalformedData),
+        Some(split) if split.source_symbol == encoder_symbol => {
+            let split = splits.pop().unwrap();
+            Ok(Some((split.split_symbol, split.source_edge)))
+        }
+        _ => Ok(None),
+    }
+}
+
+struct EdgebreakerDecoder {
+    corner_table: CornerTable,
+    is_vertex_hole: Vec<bool>,
+    topology_splits: Vec<TopologySplit>,
+    num_attribute_data: usize,
+}
+
+impl EdgebreakerDecoder {
+    /// Rebuilds the corner table from the symbols, in the reverse order they were encoded.
+    /// Returns the number of vertices.
+    fn decode_symbols(
+        &mut self,
+        traversal: &mut TraversalDecoder,
+        num_symbols: usize,
+    ) -> Result<usize, DracoError> {
+        let mut active_corners: Vec<u32> = Vec::new();
+        let mut split_active_corners: HashMap<usize, u32> = HashMap::new();
+        let mut invalid_vertices = Vec::new();
+        let remove_invalid_vertices = self.num_attribute_data == 0;
+        let max_num_vertices = self.is_vertex_hole.len();
+        let mut num_faces = 0;
+
+        for symbol_id in 0..num_symbols {
+            let table = &mut self.corner_table;
+            let corner = 3 * num_faces as u32;
+            num_faces += 1;
+            let mut check_topology_split = false;
+            let symbol = traversal.decode_symbol()?;
+            match symbol {
+                TOPOLOGY_C => {
+                    // A new face between the active edge and the next edge around vertex x.
+                    let corner_a = *active_corners.last().ok_or(DracoError::MalformedData)?;
+                    let vertex_x = table.vertex(next(corner_a));
+                    let corner_b = next(table.left_most_corner(vertex_x));
+                    if corner_b == INVALID
+                        || corner_a == corner_b
+                        || table.opposite(corner_a) != INVALID
+                        || table.opposite(corner_b) != INVALID
+                    {
+                        Err(DracoError::MalformedData)?
+                    }
+                    table.set_opposite_corners(corner_a, corner + 1);
+                    table.set_opposite_corners(corner_b, corner + 2);
+
+                    let vertex_a_previous = table.vertex(previous(corner_a));
+                    let vertex_b_next = table.vertex(next(corner_b));
+                    if vertex_x == vertex_a_previous || vertex_x == vertex_b_next {
+                        Err(DracoError::MalformedData)?
+                    }
+                    table.map_corner_to_vertex(corner, vertex_x);
+                    table.map_corner_to_vertex(corner + 1, vertex_b_next);
+                    table.map_corner_to_vertex(corner + 2, vertex_a_previous);
+                    table.set_left_most_corner(vertex_a_previous, corner + 2);
+                    self.is_vertex_hole[vertex_x as usize] = false;
+                    *active_corners.last_mut().unwrap() = corner;
+                }
+                TOPOLOGY_R | TOPOLOGY_L => {
+                    // A new face with a new vertex, attached to the active edge.
+                    let corner_a = *active_corners.last().ok_or(DracoError::MalformedData)?;
+                    if table.opposite(corner_a) != INVALID {
+                        Err(DracoError::MalformedData)?
+                    }
+                    let (opposite_corner, corner_l, corner_r) = if symbol == TOPOLOGY_R {
+                        (corner + 2, corner + 1, corner)
+                    } else {
+                        (corner + 1, corner, corner + 2)
+                    };
+                    table.set_opposite_corners(opposite_corner, corner_a);
+                    let new_vertex = table.add_new_vertex();
+                    if table.num_vertices() > max_num_vertices {
+                        Err(DracoError::MalformedData)?
+                    }
+                    table.map_corner_to_vertex(opposite_corner, new_vertex);
+                    table.set_left_most_corner(new_vertex, opposite_corner);
+
+                    let vertex_r = table.vertex(previous(corner_a));
+                    table.map_corner_to_vertex(corner_r, vertex_r);
+                    table.set_left_most_corner(vertex_r, corner_r);
+                    let vertex_l = table.vertex(next(corner_a));
+                    table.map_corner_to_vertex(corner_l, vertex_l);
+                    *active_corners.last_mut().unwrap() = corner;
+                    check_topology_split = true;
+                }
+                TOPOLOGY_S => {
+                    // A new face that merges the two most recent active edges.
+                    let corner_b = active_corners.pop().ok_or(DracoError::MalformedData)?;
+                    if let Some(&split_corner) = split_active_corners.get(&symbol_id) {
+                        active_corners.push(split_corner);
+                    }
+                    let corner_a = *active_corners.last().ok_or(DracoError::MalformedData)?;
+                    if corner_a == corner_b
+                        || table.opposite(corner_a) != INVALID
+                        || table.opposite(corner_b) != INVALID
+                    {
+                        Err(DracoError::MalformedData)?
+                    }
+                    table.set_opposite_corners(corner_a, corner + 2);
+                    table.set_opposite_corners(corner_b, corner + 1);
+
+                    let vertex_p = table.vertex(previous(corner_a));
+                    table.map_corner_to_vertex(corner, vertex_p);
+                    table.map_corner_to_vertex(corner + 1, table.vertex(next(corner_a)));
+                    let vertex_b_previous = table.vertex(previous(corner_b));
+                    table.map_corner_to_vertex(corner + 2, vertex_b_previous);
+                    table.set_left_most_corner(vertex_b_previous, corner + 2);
+
+                    // Vertex n is merged into vertex p.
+                    let mut corner_n = next(corner_b);
+                    let vertex_n = table.vertex(corner_n);
+                    traversal.merge_vertices(vertex_p, vertex_n);
+                    table.set_left_most_corner(vertex_p, table.left_most_corner(vertex_n));
+                    let first_corner = corner_n;
+                    while corner_n != INVALID {
+                        table.map_corner_to_vertex(corner_n, vertex_p);
+                        corner_n = table.swing_left(corner_n);
+                        if corner_n == first_corner {
+                            Err(DracoError::MalformedData)?
+                        }
+                    }
+                    table.make_vertex_isolated(vertex_n);
+                    if remove_invalid_vertices {
+                        invalid_vertices.push(vertex_n);
+                    }
+                    *active_corners.last_mut().unwrap() = corner;
+                }
+                TOPOLOGY_E => {
+                    // A new face with three new vertices.
+                    let first_vertex = table.add_new_vertex();
+                    table.map_corner_to_vertex(corner, first_vertex);
+                    let vertex = table.add_new_vertex();
+                    table.map_corner_to_vertex(corner + 1, vertex);
+                    let vertex = table.add_new_vertex();
+                    table.map_corner_to_vertex(corner + 2, vertex);
+                    if table.num_vertices() > max_num_vertices {
+                        Err(DracoError::MalformedData)?
+                    }
+                    table.set_left_most_corner(first_vertex, corner);
+                    table.set_left_most_corner(first_vertex + 1, corner + 1);
+                    table.set_left_most_corner(first_vertex + 2, corner + 2);
+                    active_corners.push(corner);
+                    check_topology_split = true;
+                }
+                _ => Err(DracoError::MalformedData)?,
+            }
+            traversal
+                .new_active_corner_reached(&self.corner_table, *active_corners.last().unwrap());
+
+            if check_topology_split {
+                // Faces created by L, R, and E can be connected to a later S face by a split.
+                let encoder_symbol_id = (num_symbols - symbol_id - 1) as u32;
+                while let Some((split_symbol, split_edge)) =
+                    topology_split(&mut self.topology_splits, encoder_symbol_id)?
+                {
+                    let active = *active_corners.last().unwrap();
+                    let new_active_corner = if split_edge == 1 {
+                        next(active)
+                    } else {
+                        previous(active)
+                    };
+                    let decoder_split_symbol = num_symbols - split_symbol as usize - 1;
+                    split_active_corners.insert(decoder_split_symbol, new_active_corner);
+                }
+            }
+        }
+        let table = &mut self.corner_table;
+        if table.num_vertices() > max_num_vertices {
+            Err(DracoError::MalformedData)?
+        }
+
+        // Each remaining active edge belongs to the start face of a component.
+        while let Some(corner) = active_corners.pop() {
+            let interior_face = traversal.start_faces.decode_bit();
+            if interior_face {
+                if num_faces >= table.num_faces() {
+                    Err(DracoError::MalformedData)?
+                }
+                let corner_a = corner;
+                let vertex_n = table.vertex(next(corner_a));
+                let corner_b = next(table.left_most_corner(vertex_n));
+                let vertex_x = table.vertex(next(corner_b));
+                let corner_c = next(table.left_most_corner(vertex_x));
+                if corner_b == INVALID
+                    || corner_c == INVALID
+                    || corner == corner_b
+                    || corner == corner_c
+                    || corner_b == corner_c
+                    || table.opposite(corner) != INVALID
+                    || table.opposite(corner_b) != INVALID
+                    || table.opposite(corner_c) != INVALID
+                {
+                    Err(DracoError::MalformedData)?
+                }
+                let vertex_p = table.vertex(next(corner_c));
+
+                let new_corner = 3 * num_faces as u32;
+                num_faces += 1;
+                table.set_opposite_corners(new_corner, corner);
+                table.set_opposite_corners(new_corner + 1, corner_b);
+                table.set_opposite_corners(new_corner + 2, corner_c);
+                table.map_corner_to_vertex(new_corner, vertex_x);
+                table.map_corner_to_vertex(new_corner + 1, vertex_p);
+                table.map_corner_to_vertex(new_corner + 2, vertex_n);
+                for c in new_corner..new_corner + 3 {
+                    self.is_vertex_hole[table.vertex(c) as usize] = false;
+                }
+            }
+        }
+        if num_faces != table.num_faces() {
+            Err(DracoError::MalformedData)?
+        }
+
+        // Move the last valid vertices into the place of the isolated ones.
+        let mut num_vertices = table.num_vertices();
+        for invalid_vertex in invalid_vertices {
+            let mut source = num_vertices as u32 - 1;
+            while table.left_most_corner(source) == INVALID {
+                num_vertices -= 1;
+                source = num_vertices as u32 - 1;
+            }
+            if source < invalid_vertex {
+                continue;
+            }
+            for c in table.corners_around(table.left_most_corner(source)) {
+                if table.vertex(c) != source {
+                    Err(DracoError::MalformedData)?
+                }
+                table.map_corner_to_vertex(c, invalid_vertex);
+            }
+            table.set_left_most_corner(invalid_vertex, table.left_most_corner(source));
+            table.make_vertex_isolated(source);
+            self.is_vertex_hole[invalid_vertex as usize] = self.is_vertex_hole[source as usize];
+            self.is_vertex_hole[source as usize] = false;
+            num_vertices -= 1;
+        }
+        Ok(num_vertices)
+    }
+
+    fn decode_split_events(&mut self, buffer: &mut DecoderBuffer) -> Result<(), DracoError> {
+        let num_splits = buffer.varint_u32()? as usize;
+        if num_splits > self.corner_table.num_faces() {
+            Err(DracoError::MalformedData)?
+        }
+        let mut last_source_symbol = 0u32;
+        for _ in 0..num_splits {
+            let source_symbol = buffer
+                .varint_u32()?
+                .checked_add(last_source_symbol)
+                .ok_or(DracoError::MalformedData)?;
+            let split_delta = buffer.varint_u32()?;
+            if split_delta > source_symbol {
+                Err(DracoError::MalformedData)?
+            }
+            self.topology_splits.push(TopologySplit {
+                source_symbol,
+                split_symbol: source_symbol - split_delta,
+                source_edge: 0,
+            });
+            last_source_symbol = source_symbol;
+        }
+        let mut bits = buffer.start_bits();
+        for split in self.topology_splits.iter_mut() {
+            split.source_edge = bits.bits(1)?;
+        }
+        buffer.end_bits(bits);
+        Ok(())
+    }
+}
+
+pub(crate) fn decode_edgebreaker(buffer: &mut DecoderBuffer) -> Result<Connectivity, DracoError> {
+    let traversal_method = buffer.u8()?;
+    if traversal_method != EDGEBREAKER_STANDARD && traversal_method != EDGEBREAKER_VALENCE {
+        Err(DracoError::Unsupported("predictive edgebreaker"))?
+    }
+
+    let num_encoded_vertices = buffer.varint_u32()? as usize;
+    let num_faces = buffer.varint_u32()? as usize;
+    if num_faces > u32::MAX as usize / 3 {
+        Err(DracoError::MalformedData)?
+    }
+    let num_attribute_data = buffer.u8()? as usize;
+    let num_symbols = buffer.varint_u32()? as usize;
+    if num_faces < num_symbols || num_faces > num_symbols + num_symbols / 3 {
+        Err(DracoError::MalformedData)?
+    }
+    // Each symbol takes at least a bit, which bounds the faces and vertices allocated below.
+    if num_symbols > buffer.remaining().saturating_mul(8) {
+        Err(DracoError::MalformedData)?
+    }
+    let num_split_symbols = buffer.varint_u32()? as usize;
+    if num_split_symbols > num_symbols {
+        Err(DracoError::MalformedData)?
+    }
+    // Every vertex is created by a face.
+    if num_encoded_vertices > num_faces * 3 {
+        Err(DracoError::MalformedData)?
+    }
+    // Split symbols can create extra vertices that are removed later.
+    let max_num_vertices = num_encoded_vertices + num_split_symbols;
+
+    let mut decoder = EdgebreakerDecoder {
+        corner_table: CornerTable::new(num_faces),
+        is_vertex_hole: vec![true; max_num_vertices],
+        topology_splits: Vec::new(),
+        num_attribute_data,
+    };
+    decoder.decode_split_events(buffer)?;
+
+    let mut traversal = TraversalDecoder::start(
+        buffer,
+        traversal_method,
+        max_num_vertices,
+        num_faces,
+        num_attribute_data,
+    )?;
+    let num_vertices = decoder.decode_symbols(&mut traversal, num_symbols)?;
+    let table = decoder.corner_table;
+
+    // Attribute seams are stored for each edge that isn't on a boundary.
+    let mut seams = vec![Vec::new(); num_attribute_data];
+    if num_attribute_data > 0 {
+        for face in 0..table.num_faces() as u32 {
+            let corner = 3 * face;
+            for &c in [corner, next(corner), previous(corner)].iter() {
+                let opposite = table.opposite(c);
+                if opposite == INVALID {
+                    // Boundary edges are always seams.
+                    for seam in seams.iter_mut() {
+                        seam.push(c);
+                    }
+                } else if opposite / 3 >= face {
+                    for (i, seam) in seams.iter_mut().enumerate() {
+                        if traversal.attribute_seams[i].decode_bit() {
+                            seam.push(c);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut attribute_data = Vec::new();
+    for seam in seams {
+        let mut connectivity = AttributeConnectivity::new(&table);
+        for corner in seam {
+            connectivity.add_seam_edge(&table, corner);
+        }
+        connectivity.recompute_vertices(&table)?;
+        let num_vertices = connectivity.view(&table).num_vertices();
+        attribute_data.push(AttributeData {
+            decoder_id: None,
+            connectivity,
+            is_connectivity_used: true,
+            encoding_data: EncodingData::new(num_vertices.max(table.num_vertices())),
+        });
+    }
+
+    let (corners, num_points) = assign_points_to_corners(
+        &table,
+        &attribute_data,
+        &decoder.is_vertex_hole,
+        num_vertices,
+    )?;
+    Ok(Connectivity {
+        corners,
+        num_points,
+        edgebreaker: Some(EdgebreakerData {
+            position_encoding_data: EncodingData::new(table.num_vertices()),
+            corner_table: table,
+            attribute_data,
+            position_decoder_id: None,
+        }),
+    })
+}
+
+/// Creates a point for each unique combination of attribute vertices.
+fn assign_points_to_corners(
+    table: &CornerTable,
+    attribute_data: &[AttributeData],
+    is_vertex_hole: &[bool],
+    num_vertices: usize,
+) -> Result<(Vec<u32>, usize), DracoError> {
+    if attribute_data.is_empty() {
+        // Without seams the points are the vertices.
+        let corners = (0..table.num_faces() as u32 * 3)
+            .map(|c| table.vertex(c))
+            .collect();
+        return Ok((corners, num_vertices));
+    }
+
+    let mut num_points = 0;
+    let mut corners = vec![0; table.num_faces() * 3];
+    for v in 0..table.num_vertices() as u32 {
+        let corner = table.left_most_corner(v);
+        if corner == INVALID {
+            continue;
+        }
+
+        // Interior vertices start from the first seam of any attribute.
+        let mut first = corner;
+        if !is_vertex_hole[v as usize] {
+            'attributes: for data in attribute_data {
+                if !data.connectivity.is_corner_on_seam(table, corner) {
+                    continue;
+                }
+                let attribute_table = data.connectivity.view(table);
+                let vertex = attribute_table.vertex(corner);
+                let mut current = table.swing_right(corner);
+                while current != corner {
+                    if current == INVALID {
+                        Err(DracoError::MalformedData)?
+                    }
+                    if attribute_table.vertex(current) != vertex {
+                        first = current;
+                        break 'attributes;
+                    }
+                    current = table.swing_right(current);
+                }
+            }
+        }
+
+        // Add a new point whenever any attribute changes while swinging right.
+        corners[first as usize] = num_points;
+        num_points += 1;
+        let mut previous_corner = first;
+        let mut current = table.swing_right(first);
+        while current != INVALID && current != first {
+            let seam = attribute_data.iter().any(|data| {
+                let attribute_table = data.connectivity.view(table);
+                attribute_table.vertex(current) != attribute_table.vertex(previous_corner)
+            });
+            if seam {
+                corners[current as usize] = num_points;
+                num_points += 1;
+            } else {
+                corners[current as usize] = corners[previous_corner as usize];
+            }
+            previous_corner = current;
+            current = table.swing_right(current);
+        }
+    }
+    Ok((corners, num_points as usize))
+}
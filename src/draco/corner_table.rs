@@ -0,0 +1,275 @@
+//! Corner tables describe the connectivity of a triangle mesh.
+//!
+//! Each face has three consecutive corners. Every corner maps to a vertex and
+//! to the opposite corner on the neighboring face, if there is one.
+
+use super::DracoError;
+
+pub(crate) const INVALID: u32 = u32::MAX;
+
+pub(crate) fn next(corner: u32) -> u32 {
+    match corner % 3 {
+        _ if corner == INVALID => INVALID,
+        2 => corner - 2,
+        _ => corner + 1,
+    }
+}
+
+pub(crate) fn previous(corner: u32) -> u32 {
+    match corner % 3 {
+        _ if corner == INVALID => INVALID,
+        0 => corner + 2,
+        _ => corner - 1,
+    }
+}
+
+/// The queries shared by the mesh's corner table and the per attribute corner tables.
+pub(crate) trait Table {
+    fn num_faces(&self) -> usize;
+    fn num_vertices(&self) -> usize;
+    fn vertex(&self, corner: u32) -> u32;
+    fn opposite(&self, corner: u32) -> u32;
+    fn left_most_corner(&self, vertex: u32) -> u32;
+
+    fn swing_left(&self, corner: u32) -> u32 {
+        next(self.opposite(next(corner)))
+    }
+
+    fn swing_right(&self, corner: u32) -> u32 {
+        previous(self.opposite(previous(corner)))
+    }
+
+    fn left_corner(&self, corner: u32) -> u32 {
+        self.opposite(previous(corner))
+    }
+
+    fn right_corner(&self, corner: u32) -> u32 {
+        self.opposite(next(corner))
+    }
+
+    fn is_on_boundary(&self, vertex: u32) -> bool {
+        let corner = self.left_most_corner(vertex);
+        corner == INVALID || self.swing_left(corner) == INVALID
+    }
+
+    /// Returns the corners around the vertex of `corner`, first swinging left and
+    /// then, if a boundary is reached, swinging right from `corner`.
+    fn corners_around(&self, corner: u32) -> Vec<u32> {
+        let mut corners = Vec::new();
+        if corner == INVALID {
+            return corners;
+        }
+        corners.push(corner);
+        let mut current = self.swing_left(corner);
+        while current != INVALID && current != corner {
+            corners.push(current);
+            current = self.swing_left(current);
+        }
+        if current == INVALID {
+            current = self.swing_right(corner);
+            while current != INVALID {
+                corners.push(current);
+                current = self.swing_right(current);
+            }
+        }
+        corners
+    }
+}
+
+pub(crate) struct CornerTable {
+    corner_to_vertex: Vec<u32>,
+    opposite_corners: Vec<u32>,
+    vertex_corners: Vec<u32>,
+}
+
+impl CornerTable {
+    pub fn new(num_faces: usize) -> Self {
+        Self {
+            corner_to_vertex: vec![INVALID; num_faces * 3],
+            opposite_corners: vec![INVALID; num_faces * 3],
+            vertex_corners: Vec::new(),
+        }
+    }
+
+    pub fn map_corner_to_vertex(&mut self, corner: u32, vertex: u32) {
+        self.corner_to_vertex[corner as usize] = vertex;
+    }
+
+    pub fn set_opposite_corners(&mut self, a: u32, b: u32) {
+        self.opposite_corners[a as usize] = b;
+        self.opposite_corners[b as usize] = a;
+    }
+
+    pub fn add_new_vertex(&mut self) -> u32 {
+        self.vertex_corners.push(INVALID);
+        (self.vertex_corners.len() - 1) as u32
+    }
+
+    pub fn set_left_most_corner(&mut self, vertex: u32, corner: u32) {
+        if vertex != INVALID {
+            self.vertex_corners[vertex as usize] = corner;
+        }
+    }
+
+    pub fn make_vertex_isolated(&mut self, vertex: u32) {
+        self.vertex_corners[vertex as usize] = INVALID;
+    }
+}
+
+impl Table for CornerTable {
+    fn num_faces(&self) -> usize {
+        self.corner_to_vertex.len() / 3
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.vertex_corners.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else {
+            self.corner_to_vertex[corner as usize]
+        }
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else {
+            self.opposite_corners[corner as usize]
+        }
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        if vertex == INVALID {
+            INVALID
+        } else {
+            self.vertex_corners[vertex as usize]
+        }
+    }
+}
+
+/// The connectivity of an attribute that has seams, for example texture coordinates.
+/// Vertices are split along seam edges.
+pub(crate) struct AttributeConnectivity {
+    is_edge_on_seam: Vec<bool>,
+    is_vertex_on_seam: Vec<bool>,
+    corner_to_vertex: Vec<u32>,
+    vertex_to_left_most_corner: Vec<u32>,
+}
+
+impl AttributeConnectivity {
+    pub fn new(table: &CornerTable) -> Self {
+        Self {
+            is_edge_on_seam: vec![false; table.num_faces() * 3],
+            is_vertex_on_seam: vec![false; table.num_vertices()],
+            corner_to_vertex: vec![INVALID; table.num_faces() * 3],
+            vertex_to_left_most_corner: Vec::new(),
+        }
+    }
+
+    pub fn add_seam_edge(&mut self, table: &CornerTable, corner: u32) {
+        self.is_edge_on_seam[corner as usize] = true;
+        self.is_vertex_on_seam[table.vertex(next(corner)) as usize] = true;
+        self.is_vertex_on_seam[table.vertex(previous(corner)) as usize] = true;
+        let opposite = table.opposite(corner);
+        if opposite != INVALID {
+            self.is_edge_on_seam[opposite as usize] = true;
+            self.is_vertex_on_seam[table.vertex(next(opposite)) as usize] = true;
+            self.is_vertex_on_seam[table.vertex(previous(opposite)) as usize] = true;
+        }
+    }
+
+    pub fn is_corner_on_seam(&self, table: &CornerTable, corner: u32) -> bool {
+        self.is_vertex_on_seam[table.vertex(corner) as usize]
+    }
+
+    /// Splits the vertices of the mesh along the seam edges.
+    pub fn recompute_vertices(&mut self, table: &CornerTable) -> Result<(), DracoError> {
+        self.vertex_to_left_most_corner.clear();
+        let mut num_vertices = 0;
+        for v in 0..table.num_vertices() as u32 {
+            let corner = table.left_most_corner(v);
+            if corner == INVALID {
+                continue;
+            }
+            let mut vertex = num_vertices;
+            num_vertices += 1;
+
+            // Find the first corner after a seam when swinging left.
+            let mut first = corner;
+            if self.is_vertex_on_seam[v as usize] {
+                let mut current = self.view(table).swing_left(first);
+                while current != INVALID {
+                    first = current;
+                    current = self.view(table).swing_left(current);
+                    if current == corner {
+                        Err(DracoError::MalformedData)?
+                    }
+                }
+            }
+
+            self.corner_to_vertex[first as usize] = vertex;
+            self.vertex_to_left_most_corner.push(first);
+            let mut current = table.swing_right(first);
+            while current != INVALID && current != first {
+                if self.is_edge_on_seam[next(current) as usize] {
+                    vertex = num_vertices;
+                    num_vertices += 1;
+                    self.vertex_to_left_most_corner.push(current);
+                }
+                self.corner_to_vertex[current as usize] = vertex;
+                current = table.swing_right(current);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn view<'a>(&'a self, table: &'a CornerTable) -> AttributeTable<'a> {
+        AttributeTable {
+            table,
+            attribute: self,
+        }
+    }
+}
+
+/// An attribute's connectivity together with the mesh's corner table.
+pub(crate) struct AttributeTable<'a> {
+    table: &'a CornerTable,
+    attribute: &'a AttributeConnectivity,
+}
+
+impl<'a> Table for AttributeTable<'a> {
+    fn num_faces(&self) -> usize {
+        self.table.num_faces()
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.attribute.vertex_to_left_most_corner.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else {
+            self.attribute.corner_to_vertex[corner as usize]
+        }
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        if corner == INVALID || self.attribute.is_edge_on_seam[corner as usize] {
+            INVALID
+        } else {
+            self.table.opposite(corner)
+        }
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        if vertex == INVALID {
+            INVALID
+        } else {
+            self.attribute.vertex_to_left_most_corner[vertex as usize]
+        }
+    }
+}
@@ -0,0 +1,415 @@
+//! Decoding for primitives compressed with `KHR_draco_mesh_compression`.
+//!
+//! This is a port of the decoder from [Draco](https://github.com/google/draco)
+//! for version 2.2 of the bitstream, which current encoders produce.
+//! Meshes can be stored sequentially or with Edgebreaker, point clouds only sequentially.
+
+mod attributes;
+mod buffer;
+mod connectivity;
+mod corner_table;
+mod prediction;
+mod rans;
+mod transform;
+mod traversal;
+
+use crate::extensions::khr_draco_mesh_compression::*;
+use crate::*;
+use buffer::DecoderBuffer;
+
+#[derive(Debug)]
+pub enum DracoError {
+    /// The data doesn't start with "DRACO".
+    IncorrectMagicNumber,
+    /// The bitstream version isn't supported by this decoder.
+    UnsupportedVersion(u8, u8),
+    /// The data uses an encoding this decoder doesn't support.
+    Unsupported(&'static str),
+    /// The compressed data is truncated or malformed.
+    MalformedData,
+    /// The extension refers to a buffer that doesn't exist or is too short.
+    MissingBufferData(usize),
+    /// The extension refers to an attribute id that isn't in the compressed data.
+    MissingAttribute(u32),
+    /// The accessor doesn't match the decoded data.
+    InvalidAccessor(usize),
+}
+
+const ENCODER_POINT_CLOUD: u8 = 0;
+const ENCODER_TRIANGULAR_MESH: u8 = 1;
+
+const METHOD_SEQUENTIAL: u8 = 0;
+const METHOD_EDGEBREAKER: u8 = 1;
+
+const FLAG_METADATA: u16 = 0x8000;
+
+/// The points and triangles of a decoded mesh or point cloud.
+pub struct DracoMesh {
+    /// Three point indices for each triangle. Empty for point clouds.
+    pub indices: Vec<u32>,
+    pub num_points: usize,
+    pub attributes: Vec<DracoAttribute>,
+}
+
+pub struct DracoAttribute {
+    /// The id that `KHR_draco_mesh_compression` uses to refer to the attribute.
+    pub unique_id: u32,
+    pub num_components: usize,
+    /// The values of each point, `num_components` at a time.
+    pub values: Vec<f64>,
+}
+
+/// Decodes a Draco mesh or point cloud.
+pub fn decode_mesh(data: &[u8]) -> Result<DracoMesh, DracoError> {
+    let mut buffer = DecoderBuffer::new(data);
+    if buffer.bytes(5).ok() != Some(&b"DRACO"[..]) {
+        Err(DracoError::IncorrectMagicNumber)?
+    }
+    let major = buffer.u8()?;
+    let minor = buffer.u8()?;
+    if (major, minor) != (2, 2) {
+        Err(DracoError::UnsupportedVersion(major, minor))?
+    }
+    let encoder_type = buffer.u8()?;
+    let method = buffer.u8()?;
+    let flags = buffer.u16()?;
+    if flags & FLAG_METADATA != 0 {
+        skip_metadata(&mut buffer)?;
+    }
+
+    let mut connectivity = match (encoder_type, method) {
+        (ENCODER_TRIANGULAR_MESH, METHOD_SEQUENTIAL) => {
+            connectivity::decode_sequential(&mut buffer)?
+        }
+        (ENCODER_TRIANGULAR_MESH, METHOD_EDGEBREAKER) => {
+            connectivity::decode_edgebreaker(&mut buffer)?
+        }
+        (ENCODER_POINT_CLOUD, METHOD_SEQUENTIAL) => {
+            let num_points = buffer.i32()?;
+            if num_points < 0 {
+                Err(DracoError::MalformedData)?
+            }
+            connectivity::Connectivity {
+                corners: Vec::new(),
+                num_points: num_points as usize,
+                edgebreaker: None,
+            }
+        }
+        (ENCODER_POINT_CLOUD, _) => Err(DracoError::Unsupported("point cloud encoding"))?,
+        _ => Err(DracoError::Unsupported("encoder type"))?,
+    };
+
+    let attributes = attributes::decode_attributes(&mut buffer, &mut connectivity)?;
+    Ok(DracoMesh {
+        indices: connectivity.corners,
+        num_points: connectivity.num_points,
+        attributes,
+    })
+}
+
+/// Skips the metadata of the geometry and its attributes, which glTF doesn't use.
+fn skip_metadata(buffer: &mut DecoderBuffer) -> Result<(), DracoError> {
+    let num_attribute_metadata = buffer.varint_u32()?;
+    let mut pending = 1u64;
+    for _ in 0..num_attribute_metadata {
+        buffer.varint_u32()?;
+        pending += 1;
+    }
+    // Each metadata is a list of entries followed by named sub metadata.
+    while pending > 0 {
+        pending -= 1;
+        let num_entries = buffer.varint_u32()?;
+        for _ in 0..num_entries {
+            skip_name(buffer)?;
+            let size = buffer.varint_u32()? as usize;
+            if size == 0 {
+                Err(DracoError::MalformedData)?
+            }
+            buffer.bytes(size)?;
+        }
+        let num_sub_metadata = buffer.varint_u32()?;
+        if num_sub_metadata as usize > buffer.remaining() {
+            Err(DracoError::MalformedData)?
+        }
+        for _ in 0..num_sub_metadata {
+            skip_name(buffer)?;
+            pending += 1;
+        }
+    }
+    Ok(())
+}
+
+fn skip_name(buffer: &mut DecoderBuffer) -> Result<(), DracoError> {
+    let length = buffer.u8()? as usize;
+    buffer.bytes(length)?;
+    Ok(())
+}
+
+/// Appends the values in the accessor's component type.
+fn write_values(data: &mut Vec<u8>, component_type: &AccessorComponentType, values: &[f64]) {
+    for &value in values {
+        match component_type {
+            AccessorComponentType::Byte => data.push(value as i8 as u8),
+            AccessorComponentType::UnsignedByte => data.push(value as u8),
+            AccessorComponentType::Short => data.extend_from_slice(&(value as i16).to_le_bytes()),
+            AccessorComponentType::UnsignedShort => {
+                data.extend_from_slice(&(value as u16).to_le_bytes())
+            }
+            AccessorComponentType::UnsignedInt => {
+                data.extend_from_slice(&(value as u32).to_le_bytes())
+            }
+            AccessorComponentType::Float => data.extend_from_slice(&(value as f32).to_le_bytes()),
+        }
+    }
+}
+
+impl GlTf {
+    /// Decodes every primitive compressed with `KHR_draco_mesh_compression`.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers.
+    /// The decoded indices and attributes are written to a new buffer that is appended
+    /// to `buffers`, and the primitive's accessors are pointed at it, so they can be
+    /// read as if the glTF was never compressed. The extension is removed afterwards.
+    pub fn decode_draco(&mut self, buffers: &mut Vec<Vec<u8>>) -> Result<(), DracoError> {
+        // Buffers without data are added, but any extra data is kept.
+        if buffers.len() < self.buffers.len() {
+            buffers.resize(self.buffers.len(), Vec::new());
+        }
+        let buffer_index = Index::new(self.buffers.len());
+        let mut data = Vec::new();
+
+        for m in 0..self.meshes.len() {
+            for p in 0..self.meshes[m].primitives.len() {
+                let primitive = &self.meshes[m].primitives[p];
+                let extension = match primitive.extension::<KhrDracoMeshCompression>() {
+                    Some(extension) => extension,
+                    None => continue,
+                };
+
+                let view = self
+                    .get(extension.buffer_view)
+                    .ok_or(DracoError::MissingBufferData(extension.buffer_view.value()))?;
+                let source = buffers
+                    .get(view.buffer.value())
+                    .and_then(|b| b.get(view.byte_offset..view.byte_offset + view.byte_length))
+                    .ok_or(DracoError::MissingBufferData(view.buffer.value()))?;
+                let mesh = decode_mesh(source)?;
+
+                // Collect the decoded values of each accessor before changing them.
+                let mut writes = Vec::new();
+                if let Some(indices) = primitive.indices {
+                    let values = mesh.indices.iter().map(|&i| i as f64).collect();
+                    writes.push((indices, mesh.indices.len(), values));
+                }
+                for (semantic, &unique_id) in &extension.attributes {
+                    let accessor = match primitive.attributes.get(&Semantic::parse(semantic)) {
+                        Some(&accessor) => accessor,
+                        None => continue,
+                    };
+                    let attribute = mesh
+                        .attributes
+                        .iter()
+                        .find(|a| a.unique_id == unique_id as u32)
+                        .ok_or(DracoError::MissingAttribute(unique_id as u32))?;
+                    let type_ = &self
+                        .get(accessor)
+                        .ok_or(DracoError::InvalidAccessor(accessor.value()))?
+                        .type_;
+                    if type_.component_count() != attribute.num_components {
+                        Err(DracoError::InvalidAccessor(accessor.value()))?
+                    }
+                    writes.push((accessor, mesh.num_points, attribute.values.clone()));
+                }
+
+                for (accessor_index, count, values) in writes {
+                    let accessor = self
+                        .accessors
+                        .get_mut(accessor_index.value())
+                        .ok_or(DracoError::InvalidAccessor(accessor_index.value()))?;
+                    // Views start at a multiple of 4 bytes, as every component type requires.
+                    data.resize((data.len() + 3) & !3, 0);
+                    let byte_offset = data.len();
+                    write_values(&mut data, &accessor.component_type, &values);
+                    self.buffer_views.push(BufferView {
+                        byte_offset,
+                        ..BufferView::new(buffer_index, data.len() - byte_offset)
+                    });
+                    accessor.buffer_view = Some(Index::new(self.buffer_views.len() - 1));
+                    accessor.byte_offset = 0;
+                    accessor.count = count;
+                }
+                self.meshes[m].primitives[p].remove_extension::<KhrDracoMeshCompression>();
+            }
+        }
+
+        if !data.is_empty() {
+            self.buffers.push(Buffer::new(data.len()));
+            buffers.insert(buffer_index.value(), data);
+        }
+        self.remove_extension_used(KhrDracoMeshCompression::NAME);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::transform::Octahedron;
+    use super::*;
+    use crate::test_models::*;
+
+    const POSITION_BITS: u32 = 14;
+    const NORMAL_BITS: u32 = 10;
+    const PREDICTION_NONE_BYTE: u8 = prediction::PREDICTION_NONE as u8;
+
+    fn varint(data: &mut Vec<u8>, mut value: usize) {
+        while value >= 0x80 {
+            data.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        data.push(value as u8);
+    }
+
+    /// Stores signed values with the sign in the lowest bit, as 4 bytes each.
+    fn raw_integers(data: &mut Vec<u8>, values: &[i32]) {
+        data.extend_from_slice(&[PREDICTION_NONE_BYTE, 0, 4]);
+        for &value in values {
+            data.extend_from_slice(&((value << 1) ^ (value >> 31)).to_le_bytes());
+        }
+    }
+
+    /// Encodes a mesh sequentially following the Draco 2.2 bitstream, with quantized positions,
+    /// octahedral normals and raw texture coordinates, whose unique ids are 0, 1 and 2.
+    fn encode(indices: &[u32], positions: &[f32], normals: &[f32], tex_coords: &[f32]) -> Vec<u8> {
+        let num_points = positions.len() / 3;
+        let mut data = b"DRACO".to_vec();
+        data.extend_from_slice(&[2, 2, ENCODER_TRIANGULAR_MESH, METHOD_SEQUENTIAL, 0, 0]);
+        varint(&mut data, indices.len() / 3);
+        varint(&mut data, num_points);
+        // Uncompressed indices, stored as bytes as there are fewer than 256 points.
+        data.push(1);
+        data.extend(indices.iter().map(|&i| i as u8));
+
+        // One attributes decoder with the position, normal and texture coordinate attributes.
+        data.extend_from_slice(&[1, 3]);
+        for (attribute_type, components, unique_id) in [(0, 3, 0), (1, 3, 1), (3, 2, 2)] {
+            data.extend_from_slice(&[attribute_type, 9, components, 0, unique_id]);
+        }
+        data.extend_from_slice(&[2, 3, 0]);
+
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for position in positions.chunks_exact(3) {
+            for i in 0..3 {
+                min[i] = min[i].min(position[i]);
+                max[i] = max[i].max(position[i]);
+            }
+        }
+        let range = (0..3).map(|i| max[i] - min[i]).fold(0.0, f32::max);
+        let max_quantized = ((1 << POSITION_BITS) - 1) as f32;
+        let quantized: Vec<i32> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| ((p - min[i % 3]) / range * max_quantized).round() as i32)
+            .collect();
+        raw_integers(&mut data, &quantized);
+
+        let octahedron = Octahedron::new(NORMAL_BITS).unwrap();
+        let mut coords = Vec::new();
+        for normal in normals.chunks_exact(3) {
+            let mut vector = [0, 1, 2].map(|i| (normal[i] * 1_000_000.0).round() as i64);
+            octahedron.canonicalize_integer_vector(&mut vector);
+            coords.extend_from_slice(&octahedron.integer_vector_to_coords(&vector));
+        }
+        raw_integers(&mut data, &coords);
+
+        for value in tex_coords {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in min.iter().chain([range].iter()) {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(POSITION_BITS as u8);
+        data.push(NORMAL_BITS as u8);
+        data
+    }
+
+    fn close(a: &[f32], b: &[f32], epsilon: f32) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() <= epsilon)
+    }
+
+    #[test]
+    fn decodes_cube() {
+        let (mut gltf, mut buffers) = cube();
+        let primitive = gltf.meshes[0].primitives[0].clone();
+        let read = |semantic| gltf.read_accessor_f32(primitive.attributes[&semantic], &buffers);
+        let positions = read(Semantic::Position).unwrap();
+        let normals = read(Semantic::Normal).unwrap();
+        let tex_coords = read(Semantic::TexCoord(0)).unwrap();
+        let indices = gltf
+            .read_accessor_u32(primitive.indices.unwrap(), &buffers)
+            .unwrap();
+
+        let compressed = encode(&indices, &positions, &normals, &tex_coords);
+        let buffer_view = gltf.push_buffer_view(&mut buffers, &compressed, None, None);
+        let attributes = [("POSITION", 0), ("NORMAL", 1), ("TEXCOORD_0", 2)]
+            .iter()
+            .map(|&(name, id)| (name.to_string(), id))
+            .collect();
+        gltf.meshes[0].primitives[0]
+            .set_extension(&KhrDracoMeshCompression::new(buffer_view, attributes));
+        gltf.add_extension_used(KhrDracoMeshCompression::NAME, true);
+
+        // Data after the glTF's buffers is kept.
+        buffers.push(vec![1, 2, 3]);
+        gltf.decode_draco(&mut buffers).unwrap();
+        assert_eq!(buffers.len(), gltf.buffers.len() + 1);
+        assert_eq!(buffers.last(), Some(&vec![1, 2, 3]));
+        assert!(gltf.extensions_required.is_empty());
+
+        let primitive = gltf.meshes[0].primitives[0].clone();
+        assert!(primitive.extensions.is_empty());
+        let read = |semantic| gltf.read_accessor_f32(primitive.attributes[&semantic], &buffers);
+        let decoded_indices = gltf.read_accessor_u32(primitive.indices.unwrap(), &buffers);
+        assert_eq!(decoded_indices.unwrap(), indices);
+        // The cube is 2 units wide, so positions are within one step of 2 / (2^14 - 1).
+        assert!(close(&read(Semantic::Position).unwrap(), &positions, 2e-4));
+        assert!(close(&read(Semantic::Normal).unwrap(), &normals, 1e-2));
+        assert_eq!(read(Semantic::TexCoord(0)).unwrap(), tex_coords);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = encode(&[0, 1, 2], &[0.0; 9], &[1.0, 0.0, 0.0].repeat(3), &[0.0; 6]);
+        assert!(decode_mesh(&data).is_ok());
+        assert!(decode_mesh(&data[..data.len() - 1]).is_err());
+        assert!(matches!(
+            decode_mesh(b"DRACB"),
+            Err(DracoError::IncorrectMagicNumber)
+        ));
+    }
+
+    #[test]
+    fn rejects_counts_larger_than_the_data() {
+        // Neither compressed nor uncompressed sequential indices reserve memory for the count
+        // up front.
+        for method in [0, 1] {
+            let mut data = b"DRACO".to_vec();
+            data.extend_from_slice(&[2, 2, ENCODER_TRIANGULAR_MESH, METHOD_SEQUENTIAL, 0, 0]);
+            varint(&mut data, 1_400_000_000);
+            varint(&mut data, 3);
+            data.extend_from_slice(&[method, 0, 1, 2]);
+            assert!(matches!(decode_mesh(&data), Err(DracoError::MalformedData)));
+        }
+
+        // Edgebreaker's symbols have to fit in the data before its faces are allocated.
+        let mut data = b"DRACO".to_vec();
+        data.extend_from_slice(&[2, 2, ENCODER_TRIANGULAR_MESH, METHOD_EDGEBREAKER, 0, 0, 0]);
+        varint(&mut data, 1_400_000_000);
+        varint(&mut data, 1_400_000_000);
+        data.push(0);
+        varint(&mut data, 1_400_000_000);
+        varint(&mut data, 0);
+        assert!(matches!(decode_mesh(&data), Err(DracoError::MalformedData)));
+    }
+}
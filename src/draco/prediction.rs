@@ -0,0 +1,569 @@
+//! Prediction schemes, which store attribute values as corrections to a value
+//! predicted from the values that were already decoded.
+
+use super::buffer::DecoderBuffer;
+use super::connectivity::EncodingData;
+use super::corner_table::*;
+use super::rans::RAnsBitDecoder;
+use super::transform::Transform;
+use super::DracoError;
+
+pub(crate) const PREDICTION_NONE: i8 = -2;
+const PREDICTION_DIFFERENCE: i8 = 0;
+const MESH_PREDICTION_PARALLELOGRAM: i8 = 1;
+const MESH_PREDICTION_MULTI_PARALLELOGRAM: i8 = 2;
+const MESH_PREDICTION_CONSTRAINED_MULTI_PARALLELOGRAM: i8 = 4;
+const MESH_PREDICTION_TEX_COORDS_PORTABLE: i8 = 5;
+const MESH_PREDICTION_GEOMETRIC_NORMAL: i8 = 6;
+
+const MAX_NUM_PARALLELOGRAMS: usize = 4;
+
+/// The connectivity that mesh predictions are computed on.
+#[derive(Clone, Copy)]
+pub(crate) struct MeshData<'a> {
+    pub table: &'a dyn Table,
+    pub encoding_data: &'a EncodingData,
+}
+
+impl<'a> MeshData<'a> {
+    /// Returns the index of the value of the vertex at `corner`.
+    fn value(&self, corner: u32) -> Option<usize> {
+        let vertex = self.table.vertex(corner);
+        if vertex == INVALID {
+            return None;
+        }
+        let value = *self.encoding_data.vertex_to_value.get(vertex as usize)?;
+        Some(value as usize)
+    }
+}
+
+/// The decoded integer positions that texture coordinates and normals are predicted from.
+#[derive(Clone, Copy)]
+pub(crate) struct Positions<'a> {
+    pub values: &'a [i32],
+    pub point_map: &'a [u32],
+}
+
+impl<'a> Positions<'a> {
+    fn get(&self, point: u32) -> Result<[i64; 3], DracoError> {
+        let value = *self
+            .point_map
+            .get(point as usize)
+            .ok_or(DracoError::MalformedData)? as usize;
+        let position = self
+            .values
+            .get(value * 3..value * 3 + 3)
+            .ok_or(DracoError::MalformedData)?;
+        Ok([position[0] as i64, position[1] as i64, position[2] as i64])
+    }
+}
+
+enum Method {
+    Difference,
+    Parallelogram,
+    MultiParallelogram,
+    ConstrainedMultiParallelogram { is_crease_edge: Vec<Vec<bool>> },
+    TexCoordsPortable { orientations: Vec<bool> },
+    GeometricNormal { flips: Vec<bool> },
+}
+
+pub(crate) struct PredictionScheme<'a> {
+    method: Method,
+    transform_type: i8,
+    transform: Option<Transform>,
+    mesh: Option<MeshData<'a>>,
+    positions: Option<Positions<'a>>,
+}
+
+impl<'a> PredictionScheme<'a> {
+    /// Creates the scheme for the stored method. Mesh predictions fall back to the
+    /// difference to the previous value without connectivity, like the reference decoder.
+    pub fn new(
+        method: i8,
+        transform_type: i8,
+        mesh: Option<MeshData<'a>>,
+        positions: Option<Positions<'a>>,
+    ) -> Result<Self, DracoError> {
+        let is_normal = Transform::corrections_positive(transform_type);
+        let method = match (method, mesh) {
+            (PREDICTION_DIFFERENCE, _) | (_, None) => Method::Difference,
+            (MESH_PREDICTION_GEOMETRIC_NORMAL, Some(_)) if is_normal => {
+                Method::GeometricNormal { flips: Vec::new() }
+            }
+            // Normals only support the geometric normal prediction.
+            (_, Some(_)) if is_normal => Method::Difference,
+            (MESH_PREDICTION_PARALLELOGRAM, Some(_)) => Method::Parallelogram,
+            (MESH_PREDICTION_MULTI_PARALLELOGRAM, Some(_)) => Method::MultiParallelogram,
+            (MESH_PREDICTION_CONSTRAINED_MULTI_PARALLELOGRAM, Some(_)) => {
+                Method::ConstrainedMultiParallelogram {
+                    is_crease_edge: Vec::new(),
+                }
+            }
+            (MESH_PREDICTION_TEX_COORDS_PORTABLE, Some(_)) => Method::TexCoordsPortable {
+                orientations: Vec::new(),
+            },
+            _ => Err(DracoError::Unsupported("prediction scheme"))?,
+        };
+        let needs_positions = matches!(
+            method,
+            Method::TexCoordsPortable { .. } | Method::GeometricNormal { .. }
+        );
+        if needs_positions && positions.is_none() {
+            Err(DracoError::MalformedData)?
+        }
+        Ok(Self {
+            method,
+            transform_type,
+            transform: None,
+            mesh,
+            positions,
+        })
+    }
+
+    /// Reads the data the scheme needs besides the corrections.
+    pub fn decode_prediction_data(
+        &mut self,
+        buffer: &mut DecoderBuffer,
+        num_values: usize,
+    ) -> Result<(), DracoError> {
+        match &mut self.method {
+            Method::ConstrainedMultiParallelogram { is_crease_edge } => {
+                let num_corners = self.mesh.map_or(0, |mesh| mesh.table.num_faces() * 3);
+                for _ in 0..MAX_NUM_PARALLELOGRAMS {
+                    let num_flags = buffer.varint_u32()? as usize;
+                    if num_flags > num_corners {
+                        Err(DracoError::MalformedData)?
+                    }
+                    let mut flags = Vec::with_capacity(num_flags);
+                    if num_flags > 0 {
+                        let mut decoder = RAnsBitDecoder::start(buffer)?;
+                        for _ in 0..num_flags {
+                            flags.push(decoder.decode_bit());
+                        }
+                    }
+                    is_crease_edge.push(flags);
+                }
+            }
+            Method::TexCoordsPortable { orientations } => {
+                let num_orientations = buffer.i32()?;
+                if num_orientations < 0 || num_orientations as usize > num_values {
+                    Err(DracoError::MalformedData)?
+                }
+                // Orientations are stored as changes to the previous orientation.
+                let mut last_orientation = true;
+                let mut decoder = RAnsBitDecoder::start(buffer)?;
+                for _ in 0..num_orientations {
+                    if !decoder.decode_bit() {
+                        last_orientation = !last_orientation;
+                    }
+                    orientations.push(last_orientation);
+                }
+            }
+            _ => (),
+        }
+        self.transform = Some(Transform::decode(self.transform_type, buffer)?);
+        if let Method::GeometricNormal { flips } = &mut self.method {
+            // The transform data comes before the flipped normals.
+            let mut decoder = RAnsBitDecoder::start(buffer)?;
+            *flips = (0..num_values).map(|_| decoder.decode_bit()).collect();
+        }
+        Ok(())
+    }
+
+    /// Replaces the corrections in `values` with the original values.
+    pub fn compute_original_values(
+        &mut self,
+        values: &mut [i32],
+        num_components: usize,
+        point_ids: &[u32],
+    ) -> Result<(), DracoError> {
+        if num_components == 0 || values.is_empty() {
+            return Ok(());
+        }
+        let transform = self.transform.as_ref().ok_or(DracoError::MalformedData)?;
+        let num_values = values.len() / num_components;
+        let mut predicted = vec![0; num_components];
+        match &mut self.method {
+            Method::Difference => {
+                apply(transform, values, num_components, 0, &predicted);
+                for p in 1..num_values {
+                    predicted
+                        .copy_from_slice(&values[(p - 1) * num_components..p * num_components]);
+                    apply(transform, values, num_components, p, &predicted);
+                }
+            }
+            Method::Parallelogram => {
+                let mesh = self.mesh.unwrap();
+                apply(transform, values, num_components, 0, &predicted);
+                for p in 1..num_values.min(mesh.encoding_data.value_to_corner.len()) {
+                    let corner = mesh.encoding_data.value_to_corner[p];
+                    if !parallelogram(&mesh, p, corner, values, num_components, &mut predicted) {
+                        predicted
+                            .copy_from_slice(&values[(p - 1) * num_components..p * num_components]);
+                    }
+                    apply(transform, values, num_components, p, &predicted);
+                }
+            }
+            Method::MultiParallelogram => {
+                let mesh = self.mesh.unwrap();
+                let mut parallelogram_predicted = vec![0; num_components];
+                apply(transform, values, num_components, 0, &predicted);
+                for p in 1..num_values.min(mesh.encoding_data.value_to_corner.len()) {
+                    let start = mesh.encoding_data.value_to_corner[p];
+                    let mut corner = start;
+                    let mut num_parallelograms = 0;
+                    predicted.iter_mut().for_each(|v| *v = 0);
+                    while corner != INVALID {
+                        let predicted_ = &mut parallelogram_predicted;
+                        if parallelogram(&mesh, p, corner, values, num_components, predicted_) {
+                            for (sum, &v) in predicted.iter_mut().zip(predicted_.iter()) {
+                                *sum = sum.wrapping_add(v);
+                            }
+                            num_parallelograms += 1;
+                        }
+                        corner = mesh.table.swing_right(corner);
+                        if corner == start {
+                            break;
+                        }
+                    }
+                    if num_parallelograms == 0 {
+                        predicted
+                            .copy_from_slice(&values[(p - 1) * num_components..p * num_components]);
+                    } else {
+                        predicted.iter_mut().for_each(|v| *v /= num_parallelograms);
+                    }
+                    apply(transform, values, num_components, p, &predicted);
+                }
+            }
+            Method::ConstrainedMultiParallelogram { is_crease_edge } => {
+                let mesh = self.mesh.unwrap();
+                let mut parallelograms = vec![vec![0; num_components]; MAX_NUM_PARALLELOGRAMS];
+                let mut is_crease_edge_position = [0; MAX_NUM_PARALLELOGRAMS];
+                apply(transform, values, num_components, 0, &predicted);
+                for p in 1..num_values.min(mesh.encoding_data.value_to_corner.len()) {
+                    // Find the parallelograms around the vertex, swinging left and then right.
+                    let start = mesh.encoding_data.value_to_corner[p];
+                    let mut corner = start;
+                    let mut num_parallelograms = 0;
+                    let mut first_pass = true;
+                    while corner != INVALID {
+                        let predicted_ = &mut parallelograms[num_parallelograms];
+                        if parallelogram(&mesh, p, corner, values, num_components, predicted_) {
+                            num_parallelograms += 1;
+                            if num_parallelograms == MAX_NUM_PARALLELOGRAMS {
+                                break;
+                            }
+                        }
+                        corner = if first_pass {
+                            mesh.table.swing_left(corner)
+                        } else {
+                            mesh.table.swing_right(corner)
+                        };
+                        if corner == start {
+                            break;
+                        }
+                        if corner == INVALID && first_pass {
+                            first_pass = false;
+                            corner = mesh.table.swing_right(start);
+                        }
+                    }
+
+                    // The encoder marks the parallelograms that are not used as creases.
+                    let mut num_used = 0;
+                    predicted.iter_mut().for_each(|v| *v = 0);
+                    for parallelogram in &parallelograms[..num_parallelograms] {
+                        let context = num_parallelograms - 1;
+                        let position = is_crease_edge_position[context];
+                        is_crease_edge_position[context] += 1;
+                        let is_crease = *is_crease_edge[context]
+                            .get(position)
+                            .ok_or(DracoError::MalformedData)?;
+                        if !is_crease {
+                            num_used += 1;
+                            for (sum, &v) in predicted.iter_mut().zip(parallelogram.iter()) {
+                                *sum = sum.wrapping_add(v);
+                            }
+                        }
+                    }
+                    if num_used == 0 {
+                        predicted
+                            .copy_from_slice(&values[(p - 1) * num_components..p * num_components]);
+                    } else {
+                        predicted.iter_mut().for_each(|v| *v /= num_used);
+                    }
+                    apply(transform, values, num_components, p, &predicted);
+                }
+            }
+            Method::TexCoordsPortable { orientations } => {
+                if num_components != 2 {
+                    Err(DracoError::MalformedData)?
+                }
+                let mesh = self.mesh.unwrap();
+                let positions = self.positions.unwrap();
+                for p in 0..num_values.min(mesh.encoding_data.value_to_corner.len()) {
+                    let corner = mesh.encoding_data.value_to_corner[p];
+                    let predicted = tex_coord_prediction(
+                        &mesh,
+                        &positions,
+                        point_ids,
+                        orientations,
+                        corner,
+                        values,
+                        p,
+                    )?;
+                    apply(transform, values, num_components, p, &predicted);
+                }
+            }
+            Method::GeometricNormal { flips } => {
+                if num_components != 2 {
+                    Err(DracoError::MalformedData)?
+                }
+                let mesh = self.mesh.unwrap();
+                let positions = self.positions.unwrap();
+                let octahedron = transform.octahedron().unwrap();
+                let corners = mesh.encoding_data.value_to_corner.iter();
+                for (p, (&corner, &flip)) in corners.zip(flips.iter()).enumerate() {
+                    let mut normal = normal_prediction(&mesh, &positions, point_ids, corner)?;
+                    octahedron.canonicalize_integer_vector(&mut normal);
+                    if flip {
+                        normal.iter_mut().for_each(|v| *v = -*v);
+                    }
+                    let predicted = octahedron.integer_vector_to_coords(&normal);
+                    apply(transform, values, num_components, p, &predicted);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the corrections are stored without a sign.
+    pub fn corrections_positive(&self) -> bool {
+        Transform::corrections_positive(self.transform_type)
+    }
+}
+
+/// Replaces the correction of value `p` with the original value.
+fn apply(
+    transform: &Transform,
+    values: &mut [i32],
+    num_components: usize,
+    p: usize,
+    predicted: &[i32],
+) {
+    let value = &mut values[p * num_components..(p + 1) * num_components];
+    let corrections = value.to_vec();
+    transform.compute_original_value(predicted, &corrections, value);
+}
+
+/// Predicts the value of `p` from the triangle opposite to `corner`, if all of its values were decoded.
+fn parallelogram(
+    mesh: &MeshData,
+    p: usize,
+    corner: u32,
+    values: &[i32],
+    num_components: usize,
+    predicted: &mut [i32],
+) -> bool {
+    let opposite = mesh.table.opposite(corner);
+    if opposite == INVALID {
+        return false;
+    }
+    let entries = (
+        mesh.value(opposite),
+        mesh.value(next(opposite)),
+        mesh.value(previous(opposite)),
+    );
+    let (opposite, next, previous) = match entries {
+        (Some(o), Some(n), Some(p_)) if o < p && n < p && p_ < p => (o, n, p_),
+        _ => return false,
+    };
+    for c in 0..num_components {
+        let result = values[next * num_components + c] as i64
+            + values[previous * num_components + c] as i64
+            - values[opposite * num_components + c] as i64;
+        if result > i32::MAX as i64 || result < i32::MIN as i64 {
+            return false;
+        }
+        predicted[c] = result as i32;
+    }
+    true
+}
+
+fn int_sqrt(number: u64) -> u64 {
+    if number == 0 {
+        return 0;
+    }
+    let mut remaining = number;
+    let mut root = 1u64;
+    while remaining >= 2 {
+        root *= 2;
+        remaining /= 4;
+    }
+    loop {
+        root = (root + number / root) / 2;
+        if root.wrapping_mul(root) <= number {
+            return root;
+        }
+    }
+}
+
+/// Predicts a texture coordinate from the positions and coordinates of the other corners
+/// of the triangle.
+fn tex_coord_prediction(
+    mesh: &MeshData,
+    positions: &Positions,
+    point_ids: &[u32],
+    orientations: &mut Vec<bool>,
+    corner: u32,
+    values: &[i32],
+    p: usize,
+) -> Result<[i32; 2], DracoError> {
+    let next_value = mesh.value(next(corner)).ok_or(DracoError::MalformedData)?;
+    let previous_value = mesh
+        .value(previous(corner))
+        .ok_or(DracoError::MalformedData)?;
+    let uv = |v: usize| [values[v * 2] as i64, values[v * 2 + 1] as i64];
+    let position = |v: usize| -> Result<[i64; 3], DracoError> {
+        positions.get(*point_ids.get(v).ok_or(DracoError::MalformedData)?)
+    };
+
+    if previous_value < p && next_value < p {
+        let n_uv = uv(next_value);
+        let p_uv = uv(previous_value);
+        if p_uv == n_uv {
+            // Degenerate texture triangles can't be used for the prediction.
+            return Ok([p_uv[0] as i32, p_uv[1] as i32]);
+        }
+        let tip_pos = position(p)?;
+        let next_pos = position(next_value)?;
+        let previous_pos = position(previous_value)?;
+
+        // Project the tip onto the opposite edge, in positions and in texture coordinates.
+        let pn = sub3(previous_pos, next_pos);
+        let pn_norm2_squared = dot3(pn, pn);
+        if pn_norm2_squared != 0 {
+            let cn = sub3(tip_pos, next_pos);
+            let cn_dot_pn = dot3(pn, cn);
+            let pn_uv = [p_uv[0].wrapping_sub(n_uv[0]), p_uv[1].wrapping_sub(n_uv[1])];
+            let n_uv_absmax = n_uv[0].wrapping_abs().max(n_uv[1].wrapping_abs());
+            if n_uv_absmax > i64::MAX / pn_norm2_squared {
+                Err(DracoError::MalformedData)?
+            }
+            let pn_uv_absmax = pn_uv[0].wrapping_abs().max(pn_uv[1].wrapping_abs());
+            if pn_uv_absmax == 0 || cn_dot_pn > i64::MAX / pn_uv_absmax {
+                Err(DracoError::MalformedData)?
+            }
+            let x_uv = [
+                n_uv[0]
+                    .wrapping_mul(pn_norm2_squared)
+                    .wrapping_add(cn_dot_pn.wrapping_mul(pn_uv[0])),
+                n_uv[1]
+                    .wrapping_mul(pn_norm2_squared)
+                    .wrapping_add(cn_dot_pn.wrapping_mul(pn_uv[1])),
+            ];
+            let pn_absmax = pn.iter().map(|v| v.wrapping_abs()).max().unwrap();
+            if cn_dot_pn > i64::MAX / pn_absmax {
+                Err(DracoError::MalformedData)?
+            }
+            let mut x_pos = [0; 3];
+            for i in 0..3 {
+                x_pos[i] =
+                    next_pos[i].wrapping_add(cn_dot_pn.wrapping_mul(pn[i]) / pn_norm2_squared);
+            }
+            let cx = sub3(tip_pos, x_pos);
+            let cx_norm2_squared = dot3(cx, cx) as u64;
+
+            // The rotated edge, scaled by the length of the projection.
+            let norm = int_sqrt(cx_norm2_squared.wrapping_mul(pn_norm2_squared as u64)) as i64;
+            let cx_uv = [pn_uv[1].wrapping_mul(norm), (-pn_uv[0]).wrapping_mul(norm)];
+            let orientation = orientations.pop().ok_or(DracoError::MalformedData)?;
+            let predicted = if orientation {
+                [
+                    x_uv[0].wrapping_add(cx_uv[0]),
+                    x_uv[1].wrapping_add(cx_uv[1]),
+                ]
+            } else {
+                [
+                    x_uv[0].wrapping_sub(cx_uv[0]),
+                    x_uv[1].wrapping_sub(cx_uv[1]),
+                ]
+            };
+            return Ok([
+                (predicted[0] / pn_norm2_squared) as i32,
+                (predicted[1] / pn_norm2_squared) as i32,
+            ]);
+        }
+    }
+
+    // Without both neighbors or valid positions, predict from a decoded neighbor.
+    let source = if next_value < p {
+        next_value
+    } else if p > 0 {
+        p - 1
+    } else {
+        return Ok([0, 0]);
+    };
+    Ok([values[source * 2], values[source * 2 + 1]])
+}
+
+/// Predicts a normal from the area weighted normals of the triangles around the vertex.
+fn normal_prediction(
+    mesh: &MeshData,
+    positions: &Positions,
+    point_ids: &[u32],
+    corner: u32,
+) -> Result<[i64; 3], DracoError> {
+    let position = |c: u32| -> Result<[i64; 3], DracoError> {
+        let value = mesh.value(c).ok_or(DracoError::MalformedData)?;
+        positions.get(*point_ids.get(value).ok_or(DracoError::MalformedData)?)
+    };
+    let center = position(corner)?;
+    let mut normal = [0i64; 3];
+    for c in mesh.table.corners_around(corner) {
+        let delta_next = sub3(position(next(c))?, center);
+        let delta_previous = sub3(position(previous(c))?, center);
+        let cross = [
+            delta_next[1]
+                .wrapping_mul(delta_previous[2])
+                .wrapping_sub(delta_next[2].wrapping_mul(delta_previous[1])),
+            delta_next[2]
+                .wrapping_mul(delta_previous[0])
+                .wrapping_sub(delta_next[0].wrapping_mul(delta_previous[2])),
+            delta_next[0]
+                .wrapping_mul(delta_previous[1])
+                .wrapping_sub(delta_next[1].wrapping_mul(delta_previous[0])),
+        ];
+        for i in 0..3 {
+            normal[i] = normal[i].wrapping_add(cross[i]);
+        }
+    }
+
+    // Keep the components small enough for 32 bit integers.
+    const UPPER_BOUND: i64 = 1 << 29;
+    let abs_sum = normal
+        .iter()
+        .fold(0i64, |sum, v| sum.wrapping_add(v.wrapping_abs()));
+    if abs_sum > UPPER_BOUND {
+        let quotient = abs_sum / UPPER_BOUND;
+        normal.iter_mut().for_each(|v| *v /= quotient);
+    }
+    Ok([
+        normal[0] as i32 as i64,
+        normal[1] as i32 as i64,
+        normal[2] as i32 as i64,
+    ])
+}
+
+fn sub3(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [
+        a[0].wrapping_sub(b[0]),
+        a[1].wrapping_sub(b[1]),
+        a[2].wrapping_sub(b[2]),
+    ]
+}
+
+fn dot3(a: [i64; 3], b: [i64; 3]) -> i64 {
+    a[0].wrapping_mul(b[0])
+        .wrapping_add(a[1].wrapping_mul(b[1]))
+        .wrapping_add(a[2].wrapping_mul(b[2]))
+}
@@ -0,0 +1,260 @@
+//! The rANS entropy coders used by Draco for symbols and single bits.
+
+use super::buffer::DecoderBuffer;
+use super::DracoError;
+
+const IO_BASE: u64 = 256;
+
+const SYMBOL_CODING_TAGGED: u8 = 0;
+const SYMBOL_CODING_RAW: u8 = 1;
+
+/// Reads the initial state that the encoder stored at the end of the data.
+/// Returns the state and the number of bytes left to read.
+fn read_init(data: &[u8], l_base: u64, allow_four_bytes: bool) -> Result<(u64, usize), DracoError> {
+    let length = data.len();
+    if length < 1 {
+        Err(DracoError::MalformedData)?
+    }
+    let last = data[length - 1];
+    let (offset, state) = match last >> 6 {
+        0 => (length - 1, (last & 0x3f) as u64),
+        1 if length >= 2 => (
+            length - 2,
+            u16::from_le_bytes([data[length - 2], data[length - 1]]) as u64 & 0x3fff,
+        ),
+        2 if length >= 3 => (
+            length - 3,
+            u32::from_le_bytes([data[length - 3], data[length - 2], data[length - 1], 0]) as u64
+                & 0x3f_ffff,
+        ),
+        3 if allow_four_bytes && length >= 4 => (
+            length - 4,
+            u32::from_le_bytes([
+                data[length - 4],
+                data[length - 3],
+                data[length - 2],
+                data[length - 1],
+            ]) as u64
+                & 0x3fff_ffff,
+        ),
+        _ => Err(DracoError::MalformedData)?,
+    };
+    let state = state + l_base;
+    if state >= l_base * IO_BASE {
+        Err(DracoError::MalformedData)?
+    }
+    Ok((state, offset))
+}
+
+/// Decodes bits that were encoded with a fixed probability of being zero.
+pub(crate) struct RAnsBitDecoder<'a> {
+    data: &'a [u8],
+    offset: usize,
+    state: u64,
+    probability_zero: u8,
+}
+
+impl<'a> RAnsBitDecoder<'a> {
+    const L_BASE: u64 = 4096;
+    const PRECISION: u64 = 256;
+
+    pub fn start(buffer: &mut DecoderBuffer<'a>) -> Result<Self, DracoError> {
+        let probability_zero = buffer.u8()?;
+        let length = buffer.varint_u32()? as usize;
+        let data = buffer.bytes(length)?;
+        let (state, offset) = read_init(data, Self::L_BASE, false)?;
+        Ok(Self {
+            data,
+            offset,
+            state,
+            probability_zero,
+        })
+    }
+
+    pub fn decode_bit(&mut self) -> bool {
+        let p = Self::PRECISION - self.probability_zero as u64;
+        if self.state < Self::L_BASE && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * IO_BASE + self.data[self.offset] as u64;
+        }
+        let quotient = self.state / Self::PRECISION;
+        let remainder = self.state % Self::PRECISION;
+        let scaled = quotient * p;
+        if remainder < p {
+            self.state = scaled + remainder;
+            true
+        } else {
+            self.state -= scaled + p;
+            false
+        }
+    }
+}
+
+/// Decodes symbols with a probability table stored before the data.
+struct RAnsSymbolDecoder<'a> {
+    precision: u64,
+    l_base: u64,
+    // Each entry is the symbol's probability and cumulative probability.
+    probabilities: Vec<(u32, u32)>,
+    lookup: Vec<u32>,
+    data: &'a [u8],
+    offset: usize,
+    state: u64,
+}
+
+impl<'a> RAnsSymbolDecoder<'a> {
+    fn create(buffer: &mut DecoderBuffer<'a>, precision_bits: u32) -> Result<Self, DracoError> {
+        let precision = 1u64 << precision_bits;
+        let num_symbols = buffer.varint_u32()? as usize;
+        // Each byte of the table describes at most 64 symbols.
+        if num_symbols > buffer.remaining() * 64 {
+            Err(DracoError::MalformedData)?
+        }
+
+        let mut table = vec![0u32; num_symbols];
+        let mut i = 0;
+        while i < num_symbols {
+            let data = buffer.u8()?;
+            // The two low bits are either the number of extra bytes, or 3 for a run of zeroes.
+            let token = data & 3;
+            if token == 3 {
+                let run = (data >> 2) as usize;
+                if i + run >= num_symbols {
+                    Err(DracoError::MalformedData)?
+                }
+                i += run + 1;
+            } else {
+                let mut probability = (data >> 2) as u32;
+                for b in 0..token as u32 {
+                    probability |= (buffer.u8()? as u32) << (8 * (b + 1) - 2);
+                }
+                table[i] = probability;
+                i += 1;
+            }
+        }
+
+        let mut probabilities = Vec::with_capacity(num_symbols);
+        let mut lookup = Vec::new();
+        if num_symbols > 0 {
+            lookup.resize(precision as usize, 0);
+            let mut cumulative = 0u64;
+            for (symbol, &probability) in table.iter().enumerate() {
+                probabilities.push((probability, cumulative as u32));
+                let end = cumulative + probability as u64;
+                if end > precision {
+                    Err(DracoError::MalformedData)?
+                }
+                for entry in &mut lookup[cumulative as usize..end as usize] {
+                    *entry = symbol as u32;
+                }
+                cumulative = end;
+            }
+            if cumulative != precision {
+                Err(DracoError::MalformedData)?
+            }
+        }
+
+        Ok(Self {
+            precision,
+            l_base: precision * 4,
+            probabilities,
+            lookup,
+            data: &[],
+            offset: 0,
+            state: 0,
+        })
+    }
+
+    fn num_symbols(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    fn start(&mut self, buffer: &mut DecoderBuffer<'a>) -> Result<(), DracoError> {
+        let length = buffer.varint()?;
+        if length > buffer.remaining() as u64 {
+            Err(DracoError::MalformedData)?
+        }
+        self.data = buffer.bytes(length as usize)?;
+        let (state, offset) = read_init(self.data, self.l_base, true)?;
+        self.state = state;
+        self.offset = offset;
+        Ok(())
+    }
+
+    fn decode_symbol(&mut self) -> u32 {
+        while self.state < self.l_base && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * IO_BASE + self.data[self.offset] as u64;
+        }
+        let quotient = self.state / self.precision;
+        let remainder = self.state % self.precision;
+        let symbol = self.lookup[remainder as usize];
+        let (probability, cumulative) = self.probabilities[symbol as usize];
+        self.state = quotient * probability as u64 + remainder - cumulative as u64;
+        symbol
+    }
+}
+
+/// The precision used for an alphabet whose largest symbol has `bit_length` bits.
+fn precision_bits(bit_length: u32) -> u32 {
+    ((3 * bit_length) / 2).clamp(12, 20)
+}
+
+/// Decodes `count` symbols that are grouped into values with `num_components` each.
+pub(crate) fn decode_symbols(
+    count: usize,
+    num_components: usize,
+    buffer: &mut DecoderBuffer,
+) -> Result<Vec<u32>, DracoError> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    match buffer.u8()? {
+        SYMBOL_CODING_TAGGED => decode_tagged_symbols(count, num_components, buffer),
+        SYMBOL_CODING_RAW => decode_raw_symbols(count, buffer),
+        _ => Err(DracoError::MalformedData),
+    }
+}
+
+fn decode_tagged_symbols(
+    count: usize,
+    num_components: usize,
+    buffer: &mut DecoderBuffer,
+) -> Result<Vec<u32>, DracoError> {
+    // Each value is preceded by a symbol with the bit length of its components.
+    let mut tags = RAnsSymbolDecoder::create(buffer, precision_bits(5))?;
+    tags.start(buffer)?;
+    if tags.num_symbols() == 0 || num_components == 0 {
+        Err(DracoError::MalformedData)?
+    }
+
+    let mut values = Vec::with_capacity((count + num_components).min(buffer.remaining()));
+    let mut bits = buffer.start_bits();
+    while values.len() < count {
+        let bit_length = tags.decode_symbol();
+        for _ in 0..num_components {
+            values.push(bits.bits(bit_length)?);
+        }
+    }
+    buffer.end_bits(bits);
+    values.truncate(count);
+    Ok(values)
+}
+
+fn decode_raw_symbols(count: usize, buffer: &mut DecoderBuffer) -> Result<Vec<u32>, DracoError> {
+    let max_bit_length = buffer.u8()? as u32;
+    if max_bit_length == 0 || max_bit_length > 18 {
+        Err(DracoError::MalformedData)?
+    }
+    let mut symbols = RAnsSymbolDecoder::create(buffer, precision_bits(max_bit_length))?;
+    if symbols.num_symbols() == 0 {
+        Err(DracoError::MalformedData)?
+    }
+    symbols.start(buffer)?;
+    // The count isn't trusted, so it isn't reserved all at once.
+    let mut values = Vec::with_capacity(count.min(buffer.remaining()));
+    for _ in 0..count {
+        values.push(symbols.decode_symbol());
+    }
+    Ok(values)
+}
@@ -0,0 +1,301 @@
+//! Transforms that combine a predicted value with the stored correction.
+
+use super::buffer::DecoderBuffer;
+use super::DracoError;
+
+pub(crate) const TRANSFORM_WRAP: i8 = 1;
+pub(crate) const TRANSFORM_OCTAHEDRON: i8 = 2;
+pub(crate) const TRANSFORM_OCTAHEDRON_CANONICALIZED: i8 = 3;
+
+/// Keeps values inside a range by wrapping the corrections around it.
+pub(crate) struct Wrap {
+    min_value: i32,
+    max_value: i32,
+    max_dif: i32,
+}
+
+impl Wrap {
+    fn decode(buffer: &mut DecoderBuffer) -> Result<Self, DracoError> {
+        let min_value = buffer.i32()?;
+        let max_value = buffer.i32()?;
+        let dif = max_value as i64 - min_value as i64;
+        if dif < 0 || dif >= i32::MAX as i64 {
+            Err(DracoError::MalformedData)?
+        }
+        Ok(Self {
+            min_value,
+            max_value,
+            max_dif: 1 + dif as i32,
+        })
+    }
+
+    fn compute_original_value(&self, predicted: &[i32], corrections: &[i32], out: &mut [i32]) {
+        for ((out, &predicted), &correction) in out.iter_mut().zip(predicted).zip(corrections) {
+            let predicted = predicted.max(self.min_value).min(self.max_value);
+            let mut value = predicted.wrapping_add(correction);
+            if value > self.max_value {
+                value -= self.max_dif;
+            } else if value < self.min_value {
+                value += self.max_dif;
+            }
+            *out = value;
+        }
+    }
+}
+
+/// Quantized octahedral coordinates of unit vectors.
+#[derive(Clone, Copy)]
+pub(crate) struct Octahedron {
+    pub max_quantized_value: i32,
+    pub max_value: i32,
+    pub center_value: i32,
+}
+
+impl Octahedron {
+    pub fn new(quantization_bits: u32) -> Result<Self, DracoError> {
+        if !(2..=30).contains(&quantization_bits) {
+            Err(DracoError::MalformedData)?
+        }
+        let max_quantized_value = (1 << quantization_bits) - 1;
+        let max_value = max_quantized_value - 1;
+        Ok(Self {
+            max_quantized_value,
+            max_value,
+            center_value: max_value / 2,
+        })
+    }
+
+    fn from_max_quantized_value(max_quantized_value: i32) -> Result<Self, DracoError> {
+        if max_quantized_value <= 0 || max_quantized_value % 2 == 0 {
+            Err(DracoError::MalformedData)?
+        }
+        Self::new(32 - max_quantized_value.leading_zeros())
+    }
+
+    /// Scales an integer vector so that the sum of its absolute components is the center value.
+    pub fn canonicalize_integer_vector(&self, vector: &mut [i64; 3]) {
+        let abs_sum = vector[0].abs() + vector[1].abs() + vector[2].abs();
+        if abs_sum == 0 {
+            vector[0] = self.center_value as i64;
+        } else {
+            let center = self.center_value as i64;
+            vector[0] = vector[0] * center / abs_sum;
+            vector[1] = vector[1] * center / abs_sum;
+            let z = center - vector[0].abs() - vector[1].abs();
+            vector[2] = if vector[2] >= 0 { z } else { -z };
+        }
+    }
+
+    pub fn integer_vector_to_coords(&self, vector: &[i64; 3]) -> [i32; 2] {
+        let [x, y, z] = [vector[0] as i32, vector[1] as i32, vector[2] as i32];
+        let (s, t) = if x >= 0 {
+            (y + self.center_value, z + self.center_value)
+        } else {
+            (
+                if y < 0 {
+                    z.abs()
+                } else {
+                    self.max_value - z.abs()
+                },
+                if z < 0 {
+                    y.abs()
+                } else {
+                    self.max_value - y.abs()
+                },
+            )
+        };
+        self.canonicalize_coords(s, t)
+    }
+
+    fn canonicalize_coords(&self, mut s: i32, mut t: i32) -> [i32; 2] {
+        let (max, center) = (self.max_value, self.center_value);
+        if (s == 0 && (t == 0 || t == max)) || (s == max && t == 0) {
+            s = max;
+            t = max;
+        } else if s == 0 && t > center {
+            t = center - (t - center);
+        } else if s == max && t < center {
+            t = center + (center - t);
+        } else if t == max && s < center {
+            s = center + (center - s);
+        } else if t == 0 && s > center {
+            s = center - (s - center);
+        }
+        [s, t]
+    }
+
+    /// Converts quantized octahedral coordinates back to a unit vector.
+    pub fn coords_to_unit_vector(&self, s: i32, t: i32) -> [f32; 3] {
+        let scale = 2.0 / self.max_value as f32;
+        let mut y = s as f32 * scale - 1.0;
+        let mut z = t as f32 * scale - 1.0;
+        let x = 1.0 - y.abs() - z.abs();
+        let x_offset = (-x).max(0.0);
+        y += if y < 0.0 { x_offset } else { -x_offset };
+        z += if z < 0.0 { x_offset } else { -x_offset };
+        let norm_squared = x * x + y * y + z * z;
+        if norm_squared < 1e-6 {
+            [0.0; 3]
+        } else {
+            let d = 1.0 / norm_squared.sqrt();
+            [x * d, y * d, z * d]
+        }
+    }
+
+    fn is_in_diamond(&self, s: i32, t: i32) -> bool {
+        s.abs() + t.abs() <= self.center_value
+    }
+
+    fn invert_diamond(&self, s: &mut i32, t: &mut i32) {
+        let (sign_s, sign_t) = if *s >= 0 && *t >= 0 {
+            (1, 1)
+        } else if *s <= 0 && *t <= 0 {
+            (-1, -1)
+        } else {
+            (if *s > 0 { 1 } else { -1 }, if *t > 0 { 1 } else { -1 })
+        };
+        let corner_s = sign_s * self.center_value;
+        let corner_t = sign_t * self.center_value;
+        let mut new_s = 2 * *s - corner_s;
+        let mut new_t = 2 * *t - corner_t;
+        if sign_s * sign_t >= 0 {
+            let temp = new_s;
+            new_s = -new_t;
+            new_t = -temp;
+        } else {
+            std::mem::swap(&mut new_s, &mut new_t);
+        }
+        *s = (new_s + corner_s) / 2;
+        *t = (new_t + corner_t) / 2;
+    }
+
+    fn mod_max(&self, x: i32) -> i32 {
+        if x > self.center_value {
+            x.wrapping_sub(self.max_quantized_value)
+        } else if x < -self.center_value {
+            x.wrapping_add(self.max_quantized_value)
+        } else {
+            x
+        }
+    }
+
+    fn compute_original_value(&self, predicted: &[i32], corrections: &[i32], out: &mut [i32]) {
+        let center = self.center_value;
+        let (mut s, mut t) = (predicted[0] - center, predicted[1] - center);
+        let in_diamond = self.is_in_diamond(s, t);
+        if !in_diamond {
+            self.invert_diamond(&mut s, &mut t);
+        }
+        let mut s = self.mod_max(s.wrapping_add(corrections[0]));
+        let mut t = self.mod_max(t.wrapping_add(corrections[1]));
+        if !in_diamond {
+            self.invert_diamond(&mut s, &mut t);
+        }
+        out[0] = s.wrapping_add(center);
+        out[1] = t.wrapping_add(center);
+    }
+
+    fn compute_canonicalized_original_value(
+        &self,
+        predicted: &[i32],
+        corrections: &[i32],
+        out: &mut [i32],
+    ) {
+        let center = self.center_value;
+        let (mut s, mut t) = (predicted[0] - center, predicted[1] - center);
+        let in_diamond = self.is_in_diamond(s, t);
+        if !in_diamond {
+            self.invert_diamond(&mut s, &mut t);
+        }
+        // Predictions are rotated into the bottom left quadrant.
+        let in_bottom_left = (s == 0 && t == 0) || (s < 0 && t <= 0);
+        let rotation = match (s.signum(), t.signum()) {
+            (0, 0) => 0,
+            (0, 1) => 3,
+            (0, _) => 1,
+            (1, -1) => 1,
+            (1, _) => 2,
+            (_, 1) => 3,
+            _ => 0,
+        };
+        if !in_bottom_left {
+            let (rs, rt) = rotate(s, t, rotation);
+            s = rs;
+            t = rt;
+        }
+        let mut s = self.mod_max(s.wrapping_add(corrections[0]));
+        let mut t = self.mod_max(t.wrapping_add(corrections[1]));
+        if !in_bottom_left {
+            let (rs, rt) = rotate(s, t, (4 - rotation) % 4);
+            s = rs;
+            t = rt;
+        }
+        if !in_diamond {
+            self.invert_diamond(&mut s, &mut t);
+        }
+        out[0] = s.wrapping_add(center);
+        out[1] = t.wrapping_add(center);
+    }
+}
+
+fn rotate(s: i32, t: i32, rotation: u32) -> (i32, i32) {
+    match rotation {
+        1 => (t, -s),
+        2 => (-s, -t),
+        3 => (-t, s),
+        _ => (s, t),
+    }
+}
+
+pub(crate) enum Transform {
+    Wrap(Wrap),
+    Octahedron(Octahedron),
+    OctahedronCanonicalized(Octahedron),
+}
+
+impl Transform {
+    pub fn decode(transform_type: i8, buffer: &mut DecoderBuffer) -> Result<Self, DracoError> {
+        Ok(match transform_type {
+            TRANSFORM_WRAP => Transform::Wrap(Wrap::decode(buffer)?),
+            TRANSFORM_OCTAHEDRON => {
+                Transform::Octahedron(Octahedron::from_max_quantized_value(buffer.i32()?)?)
+            }
+            TRANSFORM_OCTAHEDRON_CANONICALIZED => {
+                let max_quantized_value = buffer.i32()?;
+                // The center value is derived from the maximum instead.
+                buffer.i32()?;
+                Transform::OctahedronCanonicalized(Octahedron::from_max_quantized_value(
+                    max_quantized_value,
+                )?)
+            }
+            _ => Err(DracoError::MalformedData)?,
+        })
+    }
+
+    /// Whether the corrections are stored without a sign.
+    pub fn corrections_positive(transform_type: i8) -> bool {
+        transform_type == TRANSFORM_OCTAHEDRON
+            || transform_type == TRANSFORM_OCTAHEDRON_CANONICALIZED
+    }
+
+    pub fn octahedron(&self) -> Option<Octahedron> {
+        match self {
+            Transform::Wrap(_) => None,
+            Transform::Octahedron(octahedron) | Transform::OctahedronCanonicalized(octahedron) => {
+                Some(*octahedron)
+            }
+        }
+    }
+
+    pub fn compute_original_value(&self, predicted: &[i32], corrections: &[i32], out: &mut [i32]) {
+        match self {
+            Transform::Wrap(wrap) => wrap.compute_original_value(predicted, corrections, out),
+            Transform::Octahedron(octahedron) => {
+                octahedron.compute_original_value(predicted, corrections, out)
+            }
+            Transform::OctahedronCanonicalized(octahedron) => {
+                octahedron.compute_canonicalized_original_value(predicted, corrections, out)
+            }
+        }
+    }
+}
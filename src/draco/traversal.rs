@@ -0,0 +1,215 @@
+//! Mesh traversals that determine the order attribute values are stored in.
+
+use super::connectivity::EncodingData;
+use super::corner_table::*;
+use super::DracoError;
+
+const MAX_PRIORITY: usize = 3;
+
+/// Records each newly visited vertex as the next attribute value.
+struct Observer<'a> {
+    corners: &'a [u32],
+    encoding_data: &'a mut EncodingData,
+    point_ids: Vec<u32>,
+    is_face_visited: Vec<bool>,
+    is_vertex_visited: Vec<bool>,
+}
+
+impl<'a> Observer<'a> {
+    fn is_face_visited(&self, corner: u32) -> bool {
+        corner == INVALID || self.is_face_visited[(corner / 3) as usize]
+    }
+
+    fn visit_face(&mut self, corner: u32) {
+        self.is_face_visited[(corner / 3) as usize] = true;
+    }
+
+    fn is_vertex_visited(&self, vertex: u32) -> bool {
+        self.is_vertex_visited[vertex as usize]
+    }
+
+    fn visit_vertex(&mut self, vertex: u32, corner: u32) {
+        self.is_vertex_visited[vertex as usize] = true;
+        self.point_ids.push(self.corners[corner as usize]);
+        self.encoding_data.value_to_corner.push(corner);
+        self.encoding_data.vertex_to_value[vertex as usize] =
+            self.encoding_data.value_to_corner.len() as i32 - 1;
+    }
+
+    /// Visits the two other vertices of the face a traversal starts from.
+    fn visit_first_face(&mut self, table: &dyn Table, corner: u32) -> Result<(), DracoError> {
+        let next_vertex = table.vertex(next(corner));
+        let previous_vertex = table.vertex(previous(corner));
+        if next_vertex == INVALID || previous_vertex == INVALID {
+            Err(DracoError::MalformedData)?
+        }
+        if !self.is_vertex_visited(next_vertex) {
+            self.visit_vertex(next_vertex, next(corner));
+        }
+        if !self.is_vertex_visited(previous_vertex) {
+            self.visit_vertex(previous_vertex, previous(corner));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum TraversalMethod {
+    DepthFirst,
+    PredictionDegree,
+}
+
+/// Traverses every face of the table and returns the point of each visited vertex,
+/// in the order the attribute values are stored.
+pub(crate) fn traverse(
+    table: &dyn Table,
+    corners: &[u32],
+    encoding_data: &mut EncodingData,
+    method: TraversalMethod,
+) -> Result<Vec<u32>, DracoError> {
+    let mut observer = Observer {
+        corners,
+        encoding_data,
+        point_ids: Vec::with_capacity(table.num_vertices()),
+        is_face_visited: vec![false; table.num_faces()],
+        is_vertex_visited: vec![false; table.num_vertices()],
+    };
+    let mut prediction_degree = vec![0; table.num_vertices()];
+    for face in 0..table.num_faces() as u32 {
+        match method {
+            TraversalMethod::DepthFirst => depth_first(table, &mut observer, 3 * face)?,
+            TraversalMethod::PredictionDegree => {
+                prediction_degree_first(table, &mut observer, &mut prediction_degree, 3 * face)?
+            }
+        }
+    }
+    Ok(observer.point_ids)
+}
+
+fn depth_first(table: &dyn Table, observer: &mut Observer, corner: u32) -> Result<(), DracoError> {
+    if observer.is_face_visited(corner) {
+        return Ok(());
+    }
+    observer.visit_first_face(table, corner)?;
+
+    let mut stack = vec![corner];
+    while let Some(&top) = stack.last() {
+        let mut corner = top;
+        if observer.is_face_visited(corner) {
+            stack.pop();
+            continue;
+        }
+        loop {
+            if corner == INVALID {
+                Err(DracoError::MalformedData)?
+            }
+            observer.visit_face(corner);
+            let vertex = table.vertex(corner);
+            if vertex == INVALID {
+                Err(DracoError::MalformedData)?
+            }
+            if !observer.is_vertex_visited(vertex) {
+                let on_boundary = table.is_on_boundary(vertex);
+                observer.visit_vertex(vertex, corner);
+                if !on_boundary {
+                    corner = table.right_corner(corner);
+                    continue;
+                }
+            }
+
+            // The vertex was already visited or is on a boundary, try the neighboring faces.
+            let right = table.right_corner(corner);
+            let left = table.left_corner(corner);
+            let right_visited = observer.is_face_visited(right);
+            let left_visited = observer.is_face_visited(left);
+            if right_visited && left_visited {
+                stack.pop();
+                break;
+            } else if right_visited {
+                corner = left;
+            } else if left_visited {
+                corner = right;
+            } else {
+                // Traverse the right face first, then the left.
+                *stack.last_mut().unwrap() = left;
+                stack.push(right);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn prediction_degree_first(
+    table: &dyn Table,
+    observer: &mut Observer,
+    prediction_degree: &mut [u32],
+    corner: u32,
+) -> Result<(), DracoError> {
+    let mut stacks: [Vec<u32>; MAX_PRIORITY] = Default::default();
+    let mut best_priority = 0;
+
+    stacks[0].push(corner);
+    observer.visit_first_face(table, corner)?;
+    let tip = table.vertex(corner);
+    if !observer.is_vertex_visited(tip) {
+        observer.visit_vertex(tip, corner);
+    }
+
+    // Edges leading to vertices that can be predicted from more faces are traversed first.
+    let mut priority_of = |observer: &Observer, corner: u32| {
+        let tip = table.vertex(corner);
+        if observer.is_vertex_visited(tip) {
+            return 0;
+        }
+        prediction_degree[tip as usize] += 1;
+        if prediction_degree[tip as usize] > 1 {
+            1
+        } else {
+            2
+        }
+    };
+
+    while let Some(priority) = (best_priority..MAX_PRIORITY).find(|&i| !stacks[i].is_empty()) {
+        best_priority = priority;
+        let mut corner = stacks[priority].pop().unwrap();
+        if observer.is_face_visited(corner) {
+            continue;
+        }
+        loop {
+            observer.visit_face(corner);
+            let vertex = table.vertex(corner);
+            if vertex == INVALID {
+                Err(DracoError::MalformedData)?
+            }
+            if !observer.is_vertex_visited(vertex) {
+                observer.visit_vertex(vertex, corner);
+            }
+
+            let right = table.right_corner(corner);
+            let left = table.left_corner(corner);
+            let right_visited = observer.is_face_visited(right);
+            let left_visited = observer.is_face_visited(left);
+            if !left_visited {
+                let priority = priority_of(observer, left);
+                if right_visited && priority <= best_priority {
+                    corner = left;
+                    continue;
+                }
+                stacks[priority].push(left);
+                best_priority = best_priority.min(priority);
+            }
+            if !right_visited {
+                let priority = priority_of(observer, right);
+                if priority <= best_priority {
+                    corner = right;
+                    continue;
+                }
+                stacks[priority].push(right);
+                best_priority = best_priority.min(priority);
+            }
+            break;
+        }
+    }
+    Ok(())
+}
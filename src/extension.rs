@@ -0,0 +1,446 @@
+use kserde::*;
+use std::collections::HashMap;
+
+use crate::{GlTf, IndexKind, ObjectKind};
+
+/// A typed glTF extension that is stored in the `extensions` map of the object it extends.
+///
+/// Extensions that implement kserde's `Serialize` and `Deserialize` can implement
+/// `from_thing` and `to_thing` with [from_thing_via_json] and [to_thing_via_json].
+pub trait GltfExtension: Sized {
+    /// The name of the extension, as it appears in `extensionsUsed`.
+    const NAME: &'static str;
+    /// The kinds of objects this extension can be attached to.
+    const EXTENDS: &'static [ObjectKind];
+
+    /// Decodes the extension from the value stored in an `extensions` map.
+    fn from_thing(thing: &ThingOwned) -> Option<Self>;
+
+    /// Encodes the extension as a value that can be stored in an `extensions` map.
+    fn to_thing(&self) -> ThingOwned;
+}
+
+/// Decodes a `ThingOwned` into any type that can be deserialized from Json.
+pub fn from_thing_via_json<T: FromJson>(thing: &ThingOwned) -> Option<T> {
+    // kserde can't deserialize directly from a `ThingOwned` so this goes through Json.
+    T::from_json(&thing.to_json())
+}
+
+/// Encodes any type that can be serialized to Json as a `ThingOwned`.
+pub fn to_thing_via_json<T: ToJson>(value: &T) -> ThingOwned {
+    ThingOwned::from_json(&value.to_json()).unwrap()
+}
+
+/// A glTF object with extensions and application-specific extras.
+pub trait GltfProperty {
+    /// Dictionary object with extension-specific objects.
+    fn extensions(&self) -> &HashMap<String, ThingOwned>;
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned>;
+    /// Application-specific data.
+    fn extras(&self) -> Option<&ThingOwned>;
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned>;
+
+    /// Decodes the extras into a type, returning `None` if there are no extras
+    /// or they don't match the type.
+    ///
+    /// This is useful for reading custom properties exported by tools like Blender.
+    fn extras_as<T: FromJson>(&self) -> Option<T> {
+        self.extras().and_then(from_thing_via_json)
+    }
+
+    /// Replaces the extras with the encoded value.
+    fn set_extras<T: ToJson>(&mut self, extras: &T) {
+        *self.extras_mut() = Some(to_thing_via_json(extras));
+    }
+
+    /// Returns the extension if it is present and could be decoded.
+    fn extension<E: GltfExtension>(&self) -> Option<E> {
+        self.extensions().get(E::NAME).and_then(E::from_thing)
+    }
+
+    /// Adds the extension to this object, replacing any previous value.
+    /// This does not update `extensionsUsed`, see [GlTf::add_extension_used].
+    fn set_extension<E: GltfExtension>(&mut self, extension: &E) {
+        self.extensions_mut()
+            .insert(E::NAME.to_string(), extension.to_thing());
+    }
+
+    /// Decodes the extension, changes it with `f` and stores the result.
+    /// Does nothing if the extension is missing or couldn't be decoded.
+    fn update_extension<E: GltfExtension>(&mut self, f: impl FnOnce(&mut E)) {
+        if let Some(mut extension) = self.extension::<E>() {
+            f(&mut extension);
+            self.set_extension(&extension);
+        }
+    }
+
+    /// Removes the extension from this object, returning it if it could be decoded.
+    fn remove_extension<E: GltfExtension>(&mut self) -> Option<E> {
+        self.extensions_mut()
+            .remove(E::NAME)
+            .and_then(|thing| E::from_thing(&thing))
+    }
+}
+
+impl GlTf {
+    /// Adds an extension name to `extensionsUsed`, and to `extensionsRequired` if `required` is true.
+    pub fn add_extension_used(&mut self, name: &str, required: bool) {
+        if !self.extensions_used.iter().any(|e| e == name) {
+            self.extensions_used.push(name.to_string());
+        }
+        if required && !self.extensions_required.iter().any(|e| e == name) {
+            self.extensions_required.push(name.to_string());
+        }
+    }
+
+    /// Removes an extension name from `extensionsUsed` and `extensionsRequired`.
+    pub fn remove_extension_used(&mut self, name: &str) {
+        self.extensions_used.retain(|e| e != name);
+        self.extensions_required.retain(|e| e != name);
+    }
+}
+
+/// Calls `f` with each index at `path` in the Json of an extension.
+///
+/// `*` in `path` is each element of an array or each value of an object.
+/// Values that aren't indices are left as they are.
+pub(crate) fn visit_json_indices(
+    thing: &mut ThingOwned,
+    path: &[&str],
+    kind: IndexKind,
+    f: &mut dyn FnMut(IndexKind, &mut usize),
+) {
+    match (path.split_first(), thing) {
+        (None, ThingOwned::Number(number)) if *number >= 0.0 && number.fract() == 0.0 => {
+            let mut index = *number as usize;
+            f(kind, &mut index);
+            *number = index as f64;
+        }
+        (Some((&"*", rest)), ThingOwned::Array(values)) => {
+            for value in values.iter_mut() {
+                visit_json_indices(value, rest, kind, f);
+            }
+        }
+        (Some((&"*", rest)), ThingOwned::Object(values)) => {
+            for value in values.values_mut() {
+                visit_json_indices(value, rest, kind, f);
+            }
+        }
+        (Some((name, rest)), ThingOwned::Object(values)) => {
+            if let Some(value) = values.get_mut(*name) {
+                visit_json_indices(value, rest, kind, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Extensions without a schema in `extensions` that don't refer to objects by index.
+/// The lights of `KHR_lights_punctual` are in its own root extension.
+const WITHOUT_INDICES: &[&str] = &[
+    "KHR_lights_punctual",
+    "KHR_materials_emissive_strength",
+    "KHR_materials_ior",
+    "KHR_mesh_quantization",
+];
+
+#[derive(Debug)]
+pub enum ExtensionError {
+    /// The Json is incorrectly formatted or could not be parsed.
+    InvalidJSON,
+    /// An extension listed in `extensionsRequired` is not registered.
+    UnsupportedRequiredExtension(String),
+    /// A registered extension could not be decoded.
+    InvalidExtension { name: String, object: ObjectKind },
+    /// A registered extension is attached to a kind of object it does not extend.
+    UnexpectedObject { name: String, object: ObjectKind },
+    /// An object uses an extension whose indices aren't known, so objects
+    /// can't be removed or moved without breaking it.
+    UnknownIndices(String),
+}
+
+struct RegisteredExtension {
+    extends: &'static [ObjectKind],
+    is_valid: fn(&ThingOwned) -> bool,
+}
+
+/// The set of extensions that are understood when loading a glTF.
+///
+/// Registered extensions are checked when loading and can be retrieved with
+/// [GltfProperty::extension]. Unregistered extensions are left as `ThingOwned`.
+pub struct ExtensionRegistry {
+    // An extension may have a different type for each kind of object it extends.
+    extensions: HashMap<String, Vec<RegisteredExtension>>,
+}
+
+impl ExtensionRegistry {
+    /// Creates a registry with every extension enabled with a cargo feature.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        crate::extensions::register_enabled(&mut registry);
+        #[cfg(feature = "KHR_mesh_quantization")]
+        registry.register_name(crate::KHR_MESH_QUANTIZATION);
+        registry
+    }
+
+    /// Creates a registry without any extensions.
+    pub fn empty() -> Self {
+        Self {
+            extensions: HashMap::new(),
+        }
+    }
+
+    pub fn register<E: GltfExtension>(&mut self) -> &mut Self {
+        self.extensions
+            .entry(E::NAME.to_string())
+            .or_default()
+            .push(RegisteredExtension {
+                extends: E::EXTENDS,
+                is_valid: |thing| E::from_thing(thing).is_some(),
+            });
+        self
+    }
+
+    /// Registers an extension that has no objects of its own, like `KHR_mesh_quantization`,
+    /// so it can be listed in `extensionsRequired`. Objects that carry it are rejected.
+    pub fn register_name(&mut self, name: &str) -> &mut Self {
+        self.extensions.entry(name.to_string()).or_default();
+        self
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.extensions.contains_key(name)
+    }
+
+    fn check(
+        &self,
+        name: &str,
+        object: ObjectKind,
+        thing: &ThingOwned,
+    ) -> Result<(), ExtensionError> {
+        if let Some(registered) = self.extensions.get(name) {
+            let registered = registered
+                .iter()
+                .find(|r| r.extends.contains(&object))
+                .ok_or_else(|| ExtensionError::UnexpectedObject {
+                    name: name.to_string(),
+                    object,
+                })?;
+            if !(registered.is_valid)(thing) {
+                Err(ExtensionError::InvalidExtension {
+                    name: name.to_string(),
+                    object,
+                })?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for ExtensionRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GlTf {
+    /// Parses a glTF and checks its extensions against the registry.
+    pub fn from_json_with_extensions(
+        json: &str,
+        registry: &ExtensionRegistry,
+    ) -> Result<Self, ExtensionError> {
+        let gltf = GlTf::from_json(json).ok_or(ExtensionError::InvalidJSON)?;
+        gltf.check_extensions(registry)?;
+        Ok(gltf)
+    }
+
+    /// Checks that every required extension is registered and that every
+    /// registered extension is attached to an object it extends and can be decoded.
+    pub fn check_extensions(&self, registry: &ExtensionRegistry) -> Result<(), ExtensionError> {
+        for name in self.extensions_required.iter() {
+            if !registry.is_registered(name) {
+                Err(ExtensionError::UnsupportedRequiredExtension(name.clone()))?
+            }
+        }
+
+        let mut result = Ok(());
+        self.visit_extensions(&mut |object, extensions| {
+            for (name, thing) in extensions.iter() {
+                if result.is_ok() {
+                    result = registry.check(name, object, thing);
+                }
+            }
+        });
+        result
+    }
+
+    /// Checks that the indices of every extension used by an object are known,
+    /// so they're updated when objects are removed or moved.
+    pub(crate) fn check_extension_indices(&self) -> Result<(), ExtensionError> {
+        let mut result = Ok(());
+        self.visit_extensions(&mut |_, extensions| {
+            let unknown = extensions.keys().find(|name| {
+                !crate::extensions::NAMES.contains(&name.as_str())
+                    && !WITHOUT_INDICES.contains(&name.as_str())
+            });
+            if let (Some(name), Ok(())) = (unknown, &result) {
+                result = Err(ExtensionError::UnknownIndices(name.clone()));
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use kserde::ThingOwned;
+
+    /// An extension of nodes with a single number.
+    struct Marker {
+        value: f64,
+    }
+
+    impl GltfExtension for Marker {
+        const NAME: &'static str = "EXT_test_marker";
+        const EXTENDS: &'static [ObjectKind] = &[ObjectKind::Node];
+
+        fn from_thing(thing: &ThingOwned) -> Option<Self> {
+            match thing {
+                ThingOwned::Object(values) => match values.get("value") {
+                    Some(ThingOwned::Number(value)) => Some(Self { value: *value }),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+
+        fn to_thing(&self) -> ThingOwned {
+            ThingOwned::from_json(&format!(r#"{{ "value": {} }}"#, self.value)).unwrap()
+        }
+    }
+
+    fn load(json: &str, registry: &ExtensionRegistry) -> Result<GlTf, ExtensionError> {
+        GlTf::from_json_with_extensions(
+            &format!(r#"{{ "asset": {{ "version": "2.0" }}, {} }}"#, json),
+            registry,
+        )
+    }
+
+    fn marker_registry() -> ExtensionRegistry {
+        let mut registry = ExtensionRegistry::empty();
+        registry.register::<Marker>();
+        registry
+    }
+
+    #[test]
+    fn registered_extensions_are_decoded() {
+        let json = r#""extensionsRequired": ["EXT_test_marker"],
+            "nodes": [{ "extensions": { "EXT_test_marker": { "value": 2 } } }]"#;
+        let gltf = load(json, &marker_registry()).unwrap();
+        assert_eq!(gltf.nodes[0].extension::<Marker>().unwrap().value, 2.0);
+    }
+
+    #[test]
+    fn unregistered_required_extensions_are_rejected() {
+        let json = r#""extensionsRequired": ["EXT_test_marker"]"#;
+        let result = load(json, &ExtensionRegistry::empty());
+        assert!(matches!(
+            result,
+            Err(ExtensionError::UnsupportedRequiredExtension(name)) if name == Marker::NAME
+        ));
+        // Extensions that are only used don't need to be registered.
+        assert!(load(
+            r#""extensionsUsed": ["EXT_test_marker"]"#,
+            &ExtensionRegistry::empty()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn extensions_on_other_objects_are_rejected() {
+        let json = r#""materials": [{ "extensions": { "EXT_test_marker": { "value": 2 } } }]"#;
+        let result = load(json, &marker_registry());
+        assert!(matches!(
+            result,
+            Err(ExtensionError::UnexpectedObject { name, object: ObjectKind::Material })
+                if name == Marker::NAME
+        ));
+    }
+
+    #[test]
+    fn extensions_that_cant_be_decoded_are_rejected() {
+        let json = r#""nodes": [{ "extensions": { "EXT_test_marker": { "value": "2" } } }]"#;
+        let result = load(json, &marker_registry());
+        assert!(matches!(
+            result,
+            Err(ExtensionError::InvalidExtension { name, object: ObjectKind::Node })
+                if name == Marker::NAME
+        ));
+    }
+
+    #[test]
+    fn invalid_json_is_rejected() {
+        let result = GlTf::from_json_with_extensions("{", &ExtensionRegistry::empty());
+        assert!(matches!(result, Err(ExtensionError::InvalidJSON)));
+    }
+
+    #[test]
+    fn registered_names_can_be_required_but_not_attached() {
+        let mut registry = ExtensionRegistry::empty();
+        registry.register_name("EXT_test_name");
+        let json = r#""extensionsRequired": ["EXT_test_name"]"#;
+        assert!(load(json, &registry).is_ok());
+        let json = r#""extensionsRequired": ["EXT_test_name"],
+            "nodes": [{ "extensions": { "EXT_test_name": {} } }]"#;
+        assert!(matches!(
+            load(json, &registry),
+            Err(ExtensionError::UnexpectedObject {
+                object: ObjectKind::Node,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "KHR_mesh_quantization")]
+    #[test]
+    fn mesh_quantization_can_be_required() {
+        let json = r#""extensionsRequired": ["KHR_mesh_quantization"]"#;
+        assert!(load(json, &ExtensionRegistry::new()).is_ok());
+    }
+
+    #[test]
+    fn extension_indices_are_visited_without_features() {
+        let mut gltf = GlTf::from_json(
+            r#"{
+                "asset": { "version": "2.0" },
+                "textures": [{ "extensions": { "KHR_texture_basisu": { "source": 0 } } }],
+                "materials": [{ "extensions": { "KHR_materials_clearcoat": {
+                    "clearcoatTexture": { "index": 0 },
+                    "clearcoatFactor": 1
+                } } }]
+            }"#,
+        )
+        .unwrap();
+        gltf.visit_indices_mut(&mut |kind, index| {
+            if matches!(kind, IndexKind::Image | IndexKind::Texture) {
+                *index += 2;
+            }
+        });
+
+        let extension = |thing: &ThingOwned, path: &[&str]| {
+            path.iter().fold(thing.clone(), |thing, name| match thing {
+                ThingOwned::Object(mut values) => values.remove(*name).unwrap(),
+                _ => panic!("expected an object"),
+            })
+        };
+        let texture = &gltf.textures[0].extensions["KHR_texture_basisu"];
+        assert!(matches!(extension(texture, &["source"]), ThingOwned::Number(n) if n == 2.0));
+        let material = &gltf.materials[0].extensions["KHR_materials_clearcoat"];
+        let index = extension(material, &["clearcoatTexture", "index"]);
+        assert!(matches!(index, ThingOwned::Number(n) if n == 2.0));
+        // Values that aren't indices are unchanged.
+        let factor = extension(material, &["clearcoatFactor"]);
+        assert!(matches!(factor, ThingOwned::Number(n) if n == 1.0));
+    }
+}
@@ -0,0 +1,98 @@
+//! Types for the `EXT_mesh_gpu_instancing` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// glTF extension defines instance attributes for a node with a mesh.
+#[derive(Debug, Clone)]
+pub struct ExtMeshGpuInstancing {
+    /// A dictionary object, where each key corresponds to instance attribute and each value is the index of the accessor containing attribute's data. Attributes TRANSLATION, ROTATION, SCALE define instance transformation. For "TRANSLATION" the values are FLOAT_VEC3's specifying translation along the x, y, and z axes. For "ROTATION" the values are VEC4's specifying rotation as a quaternion in the order (x, y, z, w), where w is the scalar, with component type `FLOAT` or normalized integer. For "SCALE" the values are FLOAT_VEC3's specifying scaling factors along the x, y, and z axes.
+    pub attributes: HashMap<String, Index<Accessor>>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for ExtMeshGpuInstancing {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("attributes", &Sorted(&self.attributes));
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for ExtMeshGpuInstancing {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut attributes = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "attributes" => attributes = Some(<HashMap<String, Index<Accessor>>>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            attributes: attributes?,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl ExtMeshGpuInstancing {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(attributes: HashMap<String, Index<Accessor>>) -> Self {
+        Self {
+            attributes,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for ExtMeshGpuInstancing {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for ExtMeshGpuInstancing {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.attributes.values_mut() {
+            f(IndexKind::Accessor, v0.value_mut());
+        }
+    }
+}
+
+impl GltfExtension for ExtMeshGpuInstancing {
+    const NAME: &'static str = "EXT_mesh_gpu_instancing";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::Node];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
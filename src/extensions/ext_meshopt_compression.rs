@@ -0,0 +1,291 @@
+//! Types for the `EXT_meshopt_compression` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// Compressed data for bufferView.
+#[derive(Debug, Clone)]
+pub struct ExtMeshoptCompressionBufferView {
+    /// The index of the buffer with compressed data.
+    pub buffer: Index<Buffer>,
+    /// The offset into the buffer in bytes.
+    pub byte_offset: usize,
+    /// The length of the compressed data in bytes.
+    pub byte_length: usize,
+    /// The stride, in bytes.
+    pub byte_stride: usize,
+    /// The number of elements.
+    pub count: usize,
+    /// The compression mode.
+    pub mode: ExtMeshoptCompressionBufferViewMode,
+    /// The compression filter.
+    pub filter: ExtMeshoptCompressionBufferViewFilter,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for ExtMeshoptCompressionBufferView {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("buffer", &self.buffer);
+        serializer.property("byteOffset", &self.byte_offset);
+        serializer.property("byteLength", &self.byte_length);
+        serializer.property("byteStride", &self.byte_stride);
+        serializer.property("count", &self.count);
+        serializer.property("mode", &self.mode);
+        serializer.property("filter", &self.filter);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for ExtMeshoptCompressionBufferView {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut buffer = None;
+        let mut byte_offset = None;
+        let mut byte_length = None;
+        let mut byte_stride = None;
+        let mut count = None;
+        let mut mode = None;
+        let mut filter = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "buffer" => buffer = Some(<Index<Buffer>>::deserialize(deserializer)?),
+                "byteOffset" => byte_offset = Some(<usize>::deserialize(deserializer)?),
+                "byteLength" => byte_length = Some(<usize>::deserialize(deserializer)?),
+                "byteStride" => byte_stride = Some(<usize>::deserialize(deserializer)?),
+                "count" => count = Some(<usize>::deserialize(deserializer)?),
+                "mode" => mode = Some(<ExtMeshoptCompressionBufferViewMode>::deserialize(deserializer)?),
+                "filter" => filter = Some(<ExtMeshoptCompressionBufferViewFilter>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            buffer: buffer?,
+            byte_offset: byte_offset.unwrap_or(0usize),
+            byte_length: byte_length?,
+            byte_stride: byte_stride?,
+            count: count?,
+            mode: mode?,
+            filter: filter.unwrap_or(ExtMeshoptCompressionBufferViewFilter::None),
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl ExtMeshoptCompressionBufferView {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(buffer: Index<Buffer>, byte_length: usize, byte_stride: usize, count: usize, mode: ExtMeshoptCompressionBufferViewMode) -> Self {
+        Self {
+            buffer,
+            byte_offset: 0usize,
+            byte_length,
+            byte_stride,
+            count,
+            mode,
+            filter: ExtMeshoptCompressionBufferViewFilter::None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for ExtMeshoptCompressionBufferView {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for ExtMeshoptCompressionBufferView {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::Buffer, self.buffer.value_mut());
+    }
+}
+
+/// The compression filter.
+#[derive(Debug, Clone)]pub enum ExtMeshoptCompressionBufferViewFilter {
+    None,
+    Octahedral,
+    Quaternion,
+    Exponential,
+}
+
+impl<S: Serializer> Serialize<S> for ExtMeshoptCompressionBufferViewFilter {
+    fn serialize(&self, serializer: &mut S) {
+        match self {
+            Self::None => "NONE".serialize(serializer),
+            Self::Octahedral => "OCTAHEDRAL".serialize(serializer),
+            Self::Quaternion => "QUATERNION".serialize(serializer),
+            Self::Exponential => "EXPONENTIAL".serialize(serializer),
+        }
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for ExtMeshoptCompressionBufferViewFilter {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        let value = deserializer.string()?;
+        Some(match &*value {
+            "NONE" => Self::None,
+            "OCTAHEDRAL" => Self::Octahedral,
+            "QUATERNION" => Self::Quaternion,
+            "EXPONENTIAL" => Self::Exponential,
+        _ => None?
+        })
+    }
+}
+
+/// The compression mode.
+#[derive(Debug, Clone)]pub enum ExtMeshoptCompressionBufferViewMode {
+    Attributes,
+    Triangles,
+    Indices,
+}
+
+impl<S: Serializer> Serialize<S> for ExtMeshoptCompressionBufferViewMode {
+    fn serialize(&self, serializer: &mut S) {
+        match self {
+            Self::Attributes => "ATTRIBUTES".serialize(serializer),
+            Self::Triangles => "TRIANGLES".serialize(serializer),
+            Self::Indices => "INDICES".serialize(serializer),
+        }
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for ExtMeshoptCompressionBufferViewMode {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        let value = deserializer.string()?;
+        Some(match &*value {
+            "ATTRIBUTES" => Self::Attributes,
+            "TRIANGLES" => Self::Triangles,
+            "INDICES" => Self::Indices,
+        _ => None?
+        })
+    }
+}
+
+/// Compressed data for bufferView.
+#[derive(Debug, Clone)]
+pub struct ExtMeshoptCompressionBuffer {
+    /// Set to true to indicate that the buffer is only referenced by bufferViews that have EXT_meshopt_compression extension and as such doesn't need to be loaded.
+    pub fallback: bool,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for ExtMeshoptCompressionBuffer {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("fallback", &self.fallback);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for ExtMeshoptCompressionBuffer {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut fallback = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "fallback" => fallback = Some(<bool>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            fallback: fallback.unwrap_or(false),
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl ExtMeshoptCompressionBuffer {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            fallback: false,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for ExtMeshoptCompressionBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for ExtMeshoptCompressionBuffer {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for ExtMeshoptCompressionBuffer {
+    fn visit_indices_mut(&mut self, _f: &mut dyn FnMut(IndexKind, &mut usize)) {
+    }
+}
+
+impl GltfExtension for ExtMeshoptCompressionBuffer {
+    const NAME: &'static str = "EXT_meshopt_compression";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::Buffer];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
+impl GltfExtension for ExtMeshoptCompressionBufferView {
+    const NAME: &'static str = "EXT_meshopt_compression";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::BufferView];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
@@ -0,0 +1,106 @@
+//! Types for the `EXT_texture_webp` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// glTF extension to specify textures using the WebP image format.
+#[derive(Debug, Clone)]
+pub struct ExtTextureWebp {
+    /// The index of the images node which points to a WebP image.
+    pub source: Option<Index<Image>>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for ExtTextureWebp {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        if let Some(v) = self.source.as_ref() {
+           serializer.property("source", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for ExtTextureWebp {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut source = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "source" => source = Some(<Index<Image>>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            source,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl ExtTextureWebp {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            source: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for ExtTextureWebp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for ExtTextureWebp {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for ExtTextureWebp {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.source.iter_mut() {
+            f(IndexKind::Image, v0.value_mut());
+        }
+    }
+}
+
+impl GltfExtension for ExtTextureWebp {
+    const NAME: &'static str = "EXT_texture_webp";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::Texture];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
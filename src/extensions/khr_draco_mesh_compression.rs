@@ -0,0 +1,103 @@
+//! Types for the `KHR_draco_mesh_compression` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// Draco compressed geometry for a primitive.
+#[derive(Debug, Clone)]
+pub struct KhrDracoMeshCompression {
+    /// The index of the bufferView containing the compressed data.
+    pub buffer_view: Index<BufferView>,
+    /// A dictionary object, where each key corresponds to an attribute and its unique attribute id stored in the compressed geometry.
+    pub attributes: HashMap<String, usize>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrDracoMeshCompression {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("bufferView", &self.buffer_view);
+        serializer.property("attributes", &Sorted(&self.attributes));
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrDracoMeshCompression {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut buffer_view = None;
+        let mut attributes = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "bufferView" => buffer_view = Some(<Index<BufferView>>::deserialize(deserializer)?),
+                "attributes" => attributes = Some(<HashMap<String, usize>>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            buffer_view: buffer_view?,
+            attributes: attributes?,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrDracoMeshCompression {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(buffer_view: Index<BufferView>, attributes: HashMap<String, usize>) -> Self {
+        Self {
+            buffer_view,
+            attributes,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for KhrDracoMeshCompression {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrDracoMeshCompression {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::BufferView, self.buffer_view.value_mut());
+    }
+}
+
+impl GltfExtension for KhrDracoMeshCompression {
+    const NAME: &'static str = "KHR_draco_mesh_compression";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::MeshPrimitive];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
@@ -0,0 +1,144 @@
+//! Types for the `KHR_materials_clearcoat` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// glTF extension that defines the clearcoat material layer.
+#[derive(Debug, Clone)]
+pub struct KhrMaterialsClearcoat {
+    /// The clearcoat layer intensity.
+    pub clearcoat_factor: f32,
+    /// The clearcoat layer intensity texture.
+    pub clearcoat_texture: Option<TextureInfo>,
+    /// The clearcoat layer roughness.
+    pub clearcoat_roughness_factor: f32,
+    /// The clearcoat layer roughness texture.
+    pub clearcoat_roughness_texture: Option<TextureInfo>,
+    /// The clearcoat normal map texture.
+    pub clearcoat_normal_texture: Option<MaterialNormalTextureInfo>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrMaterialsClearcoat {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("clearcoatFactor", &self.clearcoat_factor);
+        if let Some(v) = self.clearcoat_texture.as_ref() {
+           serializer.property("clearcoatTexture", v);
+        }
+        serializer.property("clearcoatRoughnessFactor", &self.clearcoat_roughness_factor);
+        if let Some(v) = self.clearcoat_roughness_texture.as_ref() {
+           serializer.property("clearcoatRoughnessTexture", v);
+        }
+        if let Some(v) = self.clearcoat_normal_texture.as_ref() {
+           serializer.property("clearcoatNormalTexture", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrMaterialsClearcoat {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut clearcoat_factor = None;
+        let mut clearcoat_texture = None;
+        let mut clearcoat_roughness_factor = None;
+        let mut clearcoat_roughness_texture = None;
+        let mut clearcoat_normal_texture = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "clearcoatFactor" => clearcoat_factor = Some(<f32>::deserialize(deserializer)?),
+                "clearcoatTexture" => clearcoat_texture = Some(<TextureInfo>::deserialize(deserializer)?),
+                "clearcoatRoughnessFactor" => clearcoat_roughness_factor = Some(<f32>::deserialize(deserializer)?),
+                "clearcoatRoughnessTexture" => clearcoat_roughness_texture = Some(<TextureInfo>::deserialize(deserializer)?),
+                "clearcoatNormalTexture" => clearcoat_normal_texture = Some(<MaterialNormalTextureInfo>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            clearcoat_factor: clearcoat_factor.unwrap_or(0f32),
+            clearcoat_texture,
+            clearcoat_roughness_factor: clearcoat_roughness_factor.unwrap_or(0f32),
+            clearcoat_roughness_texture,
+            clearcoat_normal_texture,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrMaterialsClearcoat {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            clearcoat_factor: 0f32,
+            clearcoat_texture: None,
+            clearcoat_roughness_factor: 0f32,
+            clearcoat_roughness_texture: None,
+            clearcoat_normal_texture: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for KhrMaterialsClearcoat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for KhrMaterialsClearcoat {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrMaterialsClearcoat {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.clearcoat_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.clearcoat_roughness_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.clearcoat_normal_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+    }
+}
+
+impl GltfExtension for KhrMaterialsClearcoat {
+    const NAME: &'static str = "KHR_materials_clearcoat";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::Material];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
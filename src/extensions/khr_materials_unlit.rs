@@ -0,0 +1,94 @@
+//! Types for the `KHR_materials_unlit` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// glTF extension that defines the unlit material model.
+#[derive(Debug, Clone)]
+pub struct KhrMaterialsUnlit {
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrMaterialsUnlit {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrMaterialsUnlit {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrMaterialsUnlit {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for KhrMaterialsUnlit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for KhrMaterialsUnlit {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrMaterialsUnlit {
+    fn visit_indices_mut(&mut self, _f: &mut dyn FnMut(IndexKind, &mut usize)) {
+    }
+}
+
+impl GltfExtension for KhrMaterialsUnlit {
+    const NAME: &'static str = "KHR_materials_unlit";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::Material];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
@@ -0,0 +1,362 @@
+//! Types for the `KHR_materials_variants` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// glTF extension that defines a material variations for mesh primitives
+#[derive(Debug, Clone)]
+pub struct KhrMaterialsVariantsMeshPrimitive {
+    /// A list of material to variant mappings
+    pub mappings: Vec<KhrMaterialsVariantsMapping>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrMaterialsVariantsMeshPrimitive {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("mappings", &self.mappings);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrMaterialsVariantsMeshPrimitive {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut mappings = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "mappings" => mappings = Some(<Vec<KhrMaterialsVariantsMapping>>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            mappings: mappings?,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrMaterialsVariantsMeshPrimitive {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(mappings: Vec<KhrMaterialsVariantsMapping>) -> Self {
+        Self {
+            mappings,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for KhrMaterialsVariantsMeshPrimitive {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrMaterialsVariantsMeshPrimitive {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.mappings.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+    }
+}
+
+/// A mapping from a list of variants to the material the primitive uses for them.
+#[derive(Debug, Clone)]
+pub struct KhrMaterialsVariantsMapping {
+    /// An array of index values that reference variants defined in the glTF root's extension object.
+    pub variants: Vec<Index<KhrMaterialsVariantsVariant>>,
+    /// A reference to the material associated with the given array of variants.
+    pub material: Index<Material>,
+    /// The optional user-defined name of this variant material mapping.  This is not necessarily unique.
+    pub name: Option<String>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrMaterialsVariantsMapping {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("variants", &self.variants);
+        serializer.property("material", &self.material);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrMaterialsVariantsMapping {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut variants = None;
+        let mut material = None;
+        let mut name = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "variants" => variants = Some(<Vec<Index<KhrMaterialsVariantsVariant>>>::deserialize(deserializer)?),
+                "material" => material = Some(<Index<Material>>::deserialize(deserializer)?),
+                "name" => name = Some(<String>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            variants: variants?,
+            material: material?,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrMaterialsVariantsMapping {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(variants: Vec<Index<KhrMaterialsVariantsVariant>>, material: Index<Material>) -> Self {
+        Self {
+            variants,
+            material,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for KhrMaterialsVariantsMapping {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrMaterialsVariantsMapping {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.variants.iter_mut() {
+            f(IndexKind::KhrMaterialsVariantsVariant, v0.value_mut());
+        }
+        f(IndexKind::Material, self.material.value_mut());
+    }
+}
+
+/// glTF extension that defines a material variations for mesh primitives
+#[derive(Debug, Clone)]
+pub struct KhrMaterialsVariantsGlTf {
+    pub variants: Vec<KhrMaterialsVariantsVariant>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrMaterialsVariantsGlTf {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("variants", &self.variants);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrMaterialsVariantsGlTf {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut variants = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "variants" => variants = Some(<Vec<KhrMaterialsVariantsVariant>>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            variants: variants?,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrMaterialsVariantsGlTf {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(variants: Vec<KhrMaterialsVariantsVariant>) -> Self {
+        Self {
+            variants,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for KhrMaterialsVariantsGlTf {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrMaterialsVariantsGlTf {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.variants.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+    }
+}
+
+/// An object defining a valid material variant
+#[derive(Debug, Clone)]
+pub struct KhrMaterialsVariantsVariant {
+    /// The name of the material variant
+    pub name: String,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrMaterialsVariantsVariant {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("name", &self.name);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrMaterialsVariantsVariant {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut name = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "name" => name = Some(<String>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            name: name?,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrMaterialsVariantsVariant {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for KhrMaterialsVariantsVariant {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrMaterialsVariantsVariant {
+    fn visit_indices_mut(&mut self, _f: &mut dyn FnMut(IndexKind, &mut usize)) {
+    }
+}
+
+impl GltfExtension for KhrMaterialsVariantsGlTf {
+    const NAME: &'static str = "KHR_materials_variants";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::GlTf];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
+impl GltfExtension for KhrMaterialsVariantsMeshPrimitive {
+    const NAME: &'static str = "KHR_materials_variants";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::MeshPrimitive];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
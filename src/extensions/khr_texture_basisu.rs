@@ -0,0 +1,96 @@
+//! Types for the `KHR_texture_basisu` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// glTF extension to specify textures using the KTX v2 images with Basis Universal supercompression.
+#[derive(Debug, Clone)]
+pub struct KhrTextureBasisu {
+    /// The index of the image which points to a KTX v2 resource with Basis Universal supercompression.
+    pub source: Index<Image>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrTextureBasisu {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("source", &self.source);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrTextureBasisu {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut source = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "source" => source = Some(<Index<Image>>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            source: source?,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrTextureBasisu {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(source: Index<Image>) -> Self {
+        Self {
+            source,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for KhrTextureBasisu {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrTextureBasisu {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::Image, self.source.value_mut());
+    }
+}
+
+impl GltfExtension for KhrTextureBasisu {
+    const NAME: &'static str = "KHR_texture_basisu";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::Texture];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
@@ -0,0 +1,124 @@
+//! Types for the `KHR_texture_transform` extension.
+
+use kserde::*;
+
+use crate::*;
+use std::collections::HashMap;
+
+/// glTF extension that enables shifting and scaling UV coordinates on a per-texture basis
+#[derive(Debug, Clone)]
+pub struct KhrTextureTransform {
+    /// The offset of the UV coordinate origin as a factor of the texture dimensions.
+    pub offset: [f32; 2],
+    /// Rotate the UVs by this many radians counter-clockwise around the origin.
+    pub rotation: f32,
+    /// The scale factor applied to the components of the UV coordinates.
+    pub scale: [f32; 2],
+    /// Overrides the textureInfo texCoord value if supplied, and if this extension is supported.
+    pub tex_coord: Option<usize>,
+    /// Dictionary object with extension-specific objects.
+    pub extensions: HashMap<String, ThingOwned>,
+    /// Application-specific data.
+    pub extras: Option<ThingOwned>,
+}
+
+impl<S: Serializer> Serialize<S> for KhrTextureTransform {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        serializer.property("offset", &self.offset);
+        serializer.property("rotation", &self.rotation);
+        serializer.property("scale", &self.scale);
+        if let Some(v) = self.tex_coord.as_ref() {
+           serializer.property("texCoord", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        serializer.end_object();
+    }
+}
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for KhrTextureTransform {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut offset = None;
+        let mut rotation = None;
+        let mut scale = None;
+        let mut tex_coord = None;
+        let mut extensions = None;
+        let mut extras = None;
+
+        while let Some(property) = deserializer.has_property() {
+             match &*property {
+                "offset" => offset = Some(<[f32; 2]>::deserialize(deserializer)?),
+                "rotation" => rotation = Some(<f32>::deserialize(deserializer)?),
+                "scale" => scale = Some(<[f32; 2]>::deserialize(deserializer)?),
+                "texCoord" => tex_coord = Some(<usize>::deserialize(deserializer)?),
+                "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
+                "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            offset: offset.unwrap_or([0f32, 0f32, ]),
+            rotation: rotation.unwrap_or(0f32),
+            scale: scale.unwrap_or([1f32, 1f32, ]),
+            tex_coord,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+        })
+    }
+}
+
+impl KhrTextureTransform {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            offset: [0f32, 0f32, ],
+            rotation: 0f32,
+            scale: [1f32, 1f32, ],
+            tex_coord: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for KhrTextureTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for KhrTextureTransform {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for KhrTextureTransform {
+    fn visit_indices_mut(&mut self, _f: &mut dyn FnMut(IndexKind, &mut usize)) {
+    }
+}
+
+impl GltfExtension for KhrTextureTransform {
+    const NAME: &'static str = "KHR_texture_transform";
+    const EXTENDS: &'static [ObjectKind] = &[ObjectKind::TextureInfo, ObjectKind::MaterialNormalTextureInfo, ObjectKind::MaterialOcclusionTextureInfo];
+    fn from_thing(thing: &ThingOwned) -> Option<Self> {
+        from_thing_via_json(thing)
+    }
+    fn to_thing(&self) -> ThingOwned {
+        to_thing_via_json(self)
+    }
+}
+
@@ -0,0 +1,124 @@
+//! Typed extensions. Each is enabled with a cargo feature of the same name.
+
+use crate::{IndexKind, ObjectKind};
+
+#[cfg(feature = "EXT_mesh_gpu_instancing")]
+pub mod ext_mesh_gpu_instancing;
+#[cfg(feature = "EXT_mesh_gpu_instancing")]
+pub use ext_mesh_gpu_instancing::ExtMeshGpuInstancing;
+#[cfg(feature = "EXT_meshopt_compression")]
+pub mod ext_meshopt_compression;
+#[cfg(feature = "EXT_meshopt_compression")]
+pub use ext_meshopt_compression::ExtMeshoptCompressionBuffer;
+#[cfg(feature = "EXT_meshopt_compression")]
+pub use ext_meshopt_compression::ExtMeshoptCompressionBufferView;
+#[cfg(feature = "EXT_texture_webp")]
+pub mod ext_texture_webp;
+#[cfg(feature = "EXT_texture_webp")]
+pub use ext_texture_webp::ExtTextureWebp;
+#[cfg(feature = "KHR_draco_mesh_compression")]
+pub mod khr_draco_mesh_compression;
+#[cfg(feature = "KHR_draco_mesh_compression")]
+pub use khr_draco_mesh_compression::KhrDracoMeshCompression;
+#[cfg(feature = "KHR_materials_clearcoat")]
+pub mod khr_materials_clearcoat;
+#[cfg(feature = "KHR_materials_clearcoat")]
+pub use khr_materials_clearcoat::KhrMaterialsClearcoat;
+#[cfg(feature = "KHR_materials_unlit")]
+pub mod khr_materials_unlit;
+#[cfg(feature = "KHR_materials_unlit")]
+pub use khr_materials_unlit::KhrMaterialsUnlit;
+#[cfg(feature = "KHR_materials_variants")]
+pub mod khr_materials_variants;
+#[cfg(feature = "KHR_materials_variants")]
+pub use khr_materials_variants::KhrMaterialsVariantsGlTf;
+#[cfg(feature = "KHR_materials_variants")]
+pub use khr_materials_variants::KhrMaterialsVariantsMeshPrimitive;
+#[cfg(feature = "KHR_texture_basisu")]
+pub mod khr_texture_basisu;
+#[cfg(feature = "KHR_texture_basisu")]
+pub use khr_texture_basisu::KhrTextureBasisu;
+#[cfg(feature = "KHR_texture_transform")]
+pub mod khr_texture_transform;
+#[cfg(feature = "KHR_texture_transform")]
+pub use khr_texture_transform::KhrTextureTransform;
+#[cfg(feature = "MSFT_texture_dds")]
+pub mod msft_texture_dds;
+#[cfg(feature = "MSFT_texture_dds")]
+pub use msft_texture_dds::MsftTextureDds;
+
+/// Registers every extension enabled with a cargo feature.
+#[allow(unused_variables)]
+pub(crate) fn register_enabled(registry: &mut crate::ExtensionRegistry) {
+    #[cfg(feature = "EXT_mesh_gpu_instancing")]
+    registry.register::<ExtMeshGpuInstancing>();
+    #[cfg(feature = "EXT_meshopt_compression")]
+    registry.register::<ExtMeshoptCompressionBuffer>();
+    #[cfg(feature = "EXT_meshopt_compression")]
+    registry.register::<ExtMeshoptCompressionBufferView>();
+    #[cfg(feature = "EXT_texture_webp")]
+    registry.register::<ExtTextureWebp>();
+    #[cfg(feature = "KHR_draco_mesh_compression")]
+    registry.register::<KhrDracoMeshCompression>();
+    #[cfg(feature = "KHR_materials_clearcoat")]
+    registry.register::<KhrMaterialsClearcoat>();
+    #[cfg(feature = "KHR_materials_unlit")]
+    registry.register::<KhrMaterialsUnlit>();
+    #[cfg(feature = "KHR_materials_variants")]
+    registry.register::<KhrMaterialsVariantsGlTf>();
+    #[cfg(feature = "KHR_materials_variants")]
+    registry.register::<KhrMaterialsVariantsMeshPrimitive>();
+    #[cfg(feature = "KHR_texture_basisu")]
+    registry.register::<KhrTextureBasisu>();
+    #[cfg(feature = "KHR_texture_transform")]
+    registry.register::<KhrTextureTransform>();
+    #[cfg(feature = "MSFT_texture_dds")]
+    registry.register::<MsftTextureDds>();
+}
+
+/// The extensions with a schema, whose indices are known even if their features are disabled.
+pub(crate) const NAMES: &[&str] = &[
+    "EXT_mesh_gpu_instancing",
+    "EXT_meshopt_compression",
+    "EXT_texture_webp",
+    "KHR_draco_mesh_compression",
+    "KHR_materials_clearcoat",
+    "KHR_materials_unlit",
+    "KHR_materials_variants",
+    "KHR_texture_basisu",
+    "KHR_texture_transform",
+    "MSFT_texture_dds",
+];
+
+/// The indices in each extension, as the extension's name, the kind of object it extends,
+/// the Json path to the index and the kind of object the index refers to.
+/// `*` in a path is each element of an array or each value of an object.
+const INDICES: &[(&str, ObjectKind, &[&str], IndexKind)] = &[
+    ("EXT_mesh_gpu_instancing", ObjectKind::Node, &["attributes", "*"], IndexKind::Accessor),
+    ("EXT_meshopt_compression", ObjectKind::BufferView, &["buffer"], IndexKind::Buffer),
+    ("EXT_texture_webp", ObjectKind::Texture, &["source"], IndexKind::Image),
+    ("KHR_draco_mesh_compression", ObjectKind::MeshPrimitive, &["bufferView"], IndexKind::BufferView),
+    ("KHR_materials_clearcoat", ObjectKind::Material, &["clearcoatTexture", "index"], IndexKind::Texture),
+    ("KHR_materials_clearcoat", ObjectKind::Material, &["clearcoatRoughnessTexture", "index"], IndexKind::Texture),
+    ("KHR_materials_clearcoat", ObjectKind::Material, &["clearcoatNormalTexture", "index"], IndexKind::Texture),
+    ("KHR_materials_variants", ObjectKind::MeshPrimitive, &["mappings", "*", "variants", "*"], IndexKind::KhrMaterialsVariantsVariant),
+    ("KHR_materials_variants", ObjectKind::MeshPrimitive, &["mappings", "*", "material"], IndexKind::Material),
+    ("KHR_texture_basisu", ObjectKind::Texture, &["source"], IndexKind::Image),
+    ("MSFT_texture_dds", ObjectKind::Texture, &["source"], IndexKind::Image),
+];
+
+/// Calls `f` with every index in the known extensions of an object,
+/// whether or not their features are enabled.
+pub(crate) fn visit_indices_mut(
+    object: ObjectKind,
+    extensions: &mut std::collections::HashMap<String, kserde::ThingOwned>,
+    f: &mut dyn FnMut(IndexKind, &mut usize),
+) {
+    for (name, extends, path, kind) in INDICES {
+        if *extends == object {
+            if let Some(extension) = extensions.get_mut(*name) {
+                crate::visit_json_indices(extension, path, *kind, f);
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Copying a node and its descendants into a document of their own.
+
+use crate::*;
+
+/// The transform of the root node of an extracted subtree.
+#[derive(Debug, Clone)]
+pub enum RootTransform {
+    /// The root keeps its own transform, relative to its former parent.
+    Local,
+    /// The transforms of the root's ancestors are baked into its transform,
+    /// so the subtree is placed where it was in the scene.
+    World,
+    /// The root's transform is removed, placing the subtree at the origin.
+    Identity,
+}
+
+#[derive(Debug)]
+pub enum ExtractError {
+    /// The index doesn't refer to a node.
+    MissingNode(NodeIndex),
+    /// An object uses an extension whose indices aren't known.
+    Extension(ExtensionError),
+}
+
+impl GlTf {
+    /// Creates a document with a single scene of a node and its descendants, along with
+    /// everything they use and the animation channels that target them.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers. The returned buffers only
+    /// contain the data the new document uses. Nodes outside the subtree that are joints
+    /// of its skins are copied too, but aren't part of the scene.
+    ///
+    /// Returns an error if the node doesn't exist, or if an object uses an extension whose
+    /// indices aren't known, see [GlTf::prune].
+    pub fn extract_subtree(
+        &self,
+        buffers: &[Vec<u8>],
+        node: NodeIndex,
+        root_transform: RootTransform,
+    ) -> Result<(GlTf, Vec<Vec<u8>>), ExtractError> {
+        if self.get(node).is_none() {
+            Err(ExtractError::MissingNode(node))?
+        }
+        let world = match root_transform {
+            RootTransform::World => self.world_matrices().get(node.value()).copied(),
+            _ => None,
+        };
+
+        let mut gltf = self.clone();
+        let mut buffers = buffers.to_vec();
+        gltf.scenes = vec![Scene {
+            nodes: vec![node],
+            ..Default::default()
+        }];
+        gltf.scene = Some(Index::new(0));
+        gltf.prune(
+            &mut buffers,
+            &PruneOptions {
+                compact_buffers: true,
+            },
+        )
+        .map_err(ExtractError::Extension)?;
+
+        let root = gltf.scenes[0].nodes[0];
+        // An ancestor that's kept as a joint of a skin can't be the root's parent too.
+        if let Some(parent) = gltf.node_parents()[root.value()] {
+            gltf.nodes[parent.value()].children.retain(|&c| c != root);
+        }
+        let root = &mut gltf.nodes[root.value()];
+        match root_transform {
+            RootTransform::Local => {}
+            RootTransform::World | RootTransform::Identity => {
+                root.matrix = world;
+                root.translation = None;
+                root.rotation = None;
+                root.scale = None;
+            }
+        }
+        Ok((gltf, buffers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+
+    #[test]
+    fn extracted_root_is_detached_from_kept_joints() {
+        let (mut gltf, buffers) = fox();
+        let missing = NodeIndex::new(gltf.nodes.len());
+        assert!(matches!(
+            gltf.extract_subtree(&buffers, missing, RootTransform::Local),
+            Err(ExtractError::MissingNode(node)) if node == missing
+        ));
+
+        // Make the skinned mesh a child of one of its own joints.
+        let mesh = gltf.nodes.iter().position(|n| n.skin.is_some()).unwrap();
+        let mesh = NodeIndex::new(mesh);
+        let joint = gltf.skins[0].joints[1];
+        for node in gltf.nodes.iter_mut() {
+            node.children.retain(|&c| c != mesh);
+        }
+        gltf.nodes[joint.value()].children.push(mesh);
+        let world = world_positions(&gltf, &buffers);
+
+        let (extracted, extracted_buffers) = gltf
+            .extract_subtree(&buffers, mesh, RootTransform::World)
+            .unwrap();
+        let root = extracted.scenes[0].nodes[0];
+        assert!(extracted.nodes[root.value()].skin.is_some());
+        assert!(extracted.node_parents()[root.value()].is_none());
+        assert_eq!(extracted.skins[0].joints.len(), gltf.skins[0].joints.len());
+        assert_close(
+            &world_positions(&extracted, &extracted_buffers),
+            &world,
+            1e-4,
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use kserde::*;
 
+use crate::{Attributes, GltfProperty, Index, RootObject, Sorted, VisitIndices};
 use std::collections::HashMap;
 
 /// The root object for a glTF asset.
@@ -32,7 +33,7 @@ pub struct GlTf {
     /// An array of samplers.
     pub samplers: Vec<Sampler>,
     /// The index of the default scene.
-    pub scene: Option<usize>,
+    pub scene: Option<Index<Scene>>,
     /// An array of scenes.
     pub scenes: Vec<Scene>,
     /// An array of skins.
@@ -61,18 +62,22 @@ impl<S: Serializer> Serialize<S> for GlTf {
         serializer.property("meshes", &self.meshes);
         serializer.property("nodes", &self.nodes);
         serializer.property("samplers", &self.samplers);
-        serializer.property("scene", &self.scene);
+        if let Some(v) = self.scene.as_ref() {
+           serializer.property("scene", v);
+        }
         serializer.property("scenes", &self.scenes);
         serializer.property("skins", &self.skins);
         serializer.property("textures", &self.textures);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for GlTf {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut extensions_used = None;
         let mut extensions_required = None;
         let mut accessors = None;
@@ -108,7 +113,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for GlTf {
                 "meshes" => meshes = Some(<Vec<Mesh>>::deserialize(deserializer)?),
                 "nodes" => nodes = Some(<Vec<Node>>::deserialize(deserializer)?),
                 "samplers" => samplers = Some(<Vec<Sampler>>::deserialize(deserializer)?),
-                "scene" => scene = Some(<usize>::deserialize(deserializer)?),
+                "scene" => scene = Some(<Index<Scene>>::deserialize(deserializer)?),
                 "scenes" => scenes = Some(<Vec<Scene>>::deserialize(deserializer)?),
                 "skins" => skins = Some(<Vec<Skin>>::deserialize(deserializer)?),
                 "textures" => textures = Some(<Vec<Texture>>::deserialize(deserializer)?),
@@ -119,36 +124,127 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for GlTf {
         }
 
         Some(Self {
-            extensions_used: extensions_used.unwrap_or_else(|| Vec::new()),
-            extensions_required: extensions_required.unwrap_or_else(|| Vec::new()),
-            accessors: accessors.unwrap_or_else(|| Vec::new()),
-            animations: animations.unwrap_or_else(|| Vec::new()),
+            extensions_used: extensions_used.unwrap_or_default(),
+            extensions_required: extensions_required.unwrap_or_default(),
+            accessors: accessors.unwrap_or_default(),
+            animations: animations.unwrap_or_default(),
             asset: asset?,
-            buffers: buffers.unwrap_or_else(|| Vec::new()),
-            buffer_views: buffer_views.unwrap_or_else(|| Vec::new()),
-            cameras: cameras.unwrap_or_else(|| Vec::new()),
-            images: images.unwrap_or_else(|| Vec::new()),
-            materials: materials.unwrap_or_else(|| Vec::new()),
-            meshes: meshes.unwrap_or_else(|| Vec::new()),
-            nodes: nodes.unwrap_or_else(|| Vec::new()),
-            samplers: samplers.unwrap_or_else(|| Vec::new()),
-            scene: scene,
-            scenes: scenes.unwrap_or_else(|| Vec::new()),
-            skins: skins.unwrap_or_else(|| Vec::new()),
-            textures: textures.unwrap_or_else(|| Vec::new()),
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            buffers: buffers.unwrap_or_default(),
+            buffer_views: buffer_views.unwrap_or_default(),
+            cameras: cameras.unwrap_or_default(),
+            images: images.unwrap_or_default(),
+            materials: materials.unwrap_or_default(),
+            meshes: meshes.unwrap_or_default(),
+            nodes: nodes.unwrap_or_default(),
+            samplers: samplers.unwrap_or_default(),
+            scene,
+            scenes: scenes.unwrap_or_default(),
+            skins: skins.unwrap_or_default(),
+            textures: textures.unwrap_or_default(),
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl GlTf {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(asset: Asset) -> Self {
+        Self {
+            extensions_used: Vec::new(),
+            extensions_required: Vec::new(),
+            accessors: Vec::new(),
+            animations: Vec::new(),
+            asset,
+            buffers: Vec::new(),
+            buffer_views: Vec::new(),
+            cameras: Vec::new(),
+            images: Vec::new(),
+            materials: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            samplers: Vec::new(),
+            scene: None,
+            scenes: Vec::new(),
+            skins: Vec::new(),
+            textures: Vec::new(),
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for GlTf {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for GlTf {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.accessors.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.animations.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        self.asset.visit_indices_mut(f);
+        for v0 in self.buffers.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.buffer_views.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.cameras.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.images.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.materials.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.meshes.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.nodes.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.samplers.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.scene.iter_mut() {
+            f(IndexKind::Scene, v0.value_mut());
+        }
+        for v0 in self.scenes.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.skins.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.textures.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::GlTf, &mut self.extensions, f);
+    }
+}
+
 /// A texture and its sampler.
 #[derive(Debug, Clone)]
 pub struct Texture {
     /// The index of the sampler used by this texture. When undefined, a sampler with repeat wrapping and auto filtering should be used.
-    pub sampler: Option<usize>,
+    pub sampler: Option<Index<Sampler>>,
     /// The index of the image used by this texture. When undefined, it is expected that an extension or other mechanism will supply an alternate texture source, otherwise behavior is undefined.
-    pub source: Option<usize>,
+    pub source: Option<Index<Image>>,
     /// The user-defined name of this object.
     pub name: Option<String>,
     /// Dictionary object with extension-specific objects.
@@ -160,17 +256,25 @@ pub struct Texture {
 impl<S: Serializer> Serialize<S> for Texture {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("sampler", &self.sampler);
-        serializer.property("source", &self.source);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.sampler.as_ref() {
+           serializer.property("sampler", v);
+        }
+        if let Some(v) = self.source.as_ref() {
+           serializer.property("source", v);
+        }
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Texture {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut sampler = None;
         let mut source = None;
         let mut name = None;
@@ -179,8 +283,8 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Texture {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "sampler" => sampler = Some(<usize>::deserialize(deserializer)?),
-                "source" => source = Some(<usize>::deserialize(deserializer)?),
+                "sampler" => sampler = Some(<Index<Sampler>>::deserialize(deserializer)?),
+                "source" => source = Some(<Index<Image>>::deserialize(deserializer)?),
                 "name" => name = Some(<String>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -189,24 +293,70 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Texture {
         }
 
         Some(Self {
-            sampler: sampler,
-            source: source,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            sampler,
+            source,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Texture {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            sampler: None,
+            source: None,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for Texture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for Texture {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Texture {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.sampler.iter_mut() {
+            f(IndexKind::Sampler, v0.value_mut());
+        }
+        for v0 in self.source.iter_mut() {
+            f(IndexKind::Image, v0.value_mut());
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Texture, &mut self.extensions, f);
+    }
+}
+
 /// Joints and matrices defining a skin.
 #[derive(Debug, Clone)]
 pub struct Skin {
     /// The index of the accessor containing the floating-point 4x4 inverse-bind matrices.  The default is that each matrix is a 4x4 identity matrix, which implies that inverse-bind matrices were pre-applied.
-    pub inverse_bind_matrices: Option<usize>,
+    pub inverse_bind_matrices: Option<Index<Accessor>>,
     /// The index of the node used as a skeleton root.
-    pub skeleton: Option<usize>,
+    pub skeleton: Option<Index<Node>>,
     /// Indices of skeleton nodes, used as joints in this skin.
-    pub joints: Vec<usize>,
+    pub joints: Vec<Index<Node>>,
     /// The user-defined name of this object.
     pub name: Option<String>,
     /// Dictionary object with extension-specific objects.
@@ -218,18 +368,26 @@ pub struct Skin {
 impl<S: Serializer> Serialize<S> for Skin {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("inverseBindMatrices", &self.inverse_bind_matrices);
-        serializer.property("skeleton", &self.skeleton);
+        if let Some(v) = self.inverse_bind_matrices.as_ref() {
+           serializer.property("inverseBindMatrices", v);
+        }
+        if let Some(v) = self.skeleton.as_ref() {
+           serializer.property("skeleton", v);
+        }
         serializer.property("joints", &self.joints);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Skin {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut inverse_bind_matrices = None;
         let mut skeleton = None;
         let mut joints = None;
@@ -239,9 +397,9 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Skin {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "inverseBindMatrices" => inverse_bind_matrices = Some(<usize>::deserialize(deserializer)?),
-                "skeleton" => skeleton = Some(<usize>::deserialize(deserializer)?),
-                "joints" => joints = Some(<Vec<usize>>::deserialize(deserializer)?),
+                "inverseBindMatrices" => inverse_bind_matrices = Some(<Index<Accessor>>::deserialize(deserializer)?),
+                "skeleton" => skeleton = Some(<Index<Node>>::deserialize(deserializer)?),
+                "joints" => joints = Some(<Vec<Index<Node>>>::deserialize(deserializer)?),
                 "name" => name = Some(<String>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -250,21 +408,65 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Skin {
         }
 
         Some(Self {
-            inverse_bind_matrices: inverse_bind_matrices,
-            skeleton: skeleton,
+            inverse_bind_matrices,
+            skeleton,
             joints: joints?,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Skin {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(joints: Vec<Index<Node>>) -> Self {
+        Self {
+            inverse_bind_matrices: None,
+            skeleton: None,
+            joints,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for Skin {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Skin {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.inverse_bind_matrices.iter_mut() {
+            f(IndexKind::Accessor, v0.value_mut());
+        }
+        for v0 in self.skeleton.iter_mut() {
+            f(IndexKind::Node, v0.value_mut());
+        }
+        for v0 in self.joints.iter_mut() {
+            f(IndexKind::Node, v0.value_mut());
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Skin, &mut self.extensions, f);
+    }
+}
+
 /// The root nodes of a scene.
 #[derive(Debug, Clone)]
 pub struct Scene {
     /// The indices of each root node.
-    pub nodes: Vec<usize>,
+    pub nodes: Vec<Index<Node>>,
     /// The user-defined name of this object.
     pub name: Option<String>,
     /// Dictionary object with extension-specific objects.
@@ -277,15 +479,19 @@ impl<S: Serializer> Serialize<S> for Scene {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
         serializer.property("nodes", &self.nodes);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Scene {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut nodes = None;
         let mut name = None;
         let mut extensions = None;
@@ -293,7 +499,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Scene {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "nodes" => nodes = Some(<Vec<usize>>::deserialize(deserializer)?),
+                "nodes" => nodes = Some(<Vec<Index<Node>>>::deserialize(deserializer)?),
                 "name" => name = Some(<String>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -302,14 +508,56 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Scene {
         }
 
         Some(Self {
-            nodes: nodes.unwrap_or_else(|| Vec::new()),
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            nodes: nodes.unwrap_or_default(),
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Scene {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for Scene {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Scene {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.nodes.iter_mut() {
+            f(IndexKind::Node, v0.value_mut());
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Scene, &mut self.extensions, f);
+    }
+}
+
 /// Texture sampler properties for filtering and wrapping modes.
 #[derive(Debug, Clone)]
 pub struct Sampler {
@@ -332,19 +580,27 @@ pub struct Sampler {
 impl<S: Serializer> Serialize<S> for Sampler {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("magFilter", &self.mag_filter);
-        serializer.property("minFilter", &self.min_filter);
+        if let Some(v) = self.mag_filter.as_ref() {
+           serializer.property("magFilter", v);
+        }
+        if let Some(v) = self.min_filter.as_ref() {
+           serializer.property("minFilter", v);
+        }
         serializer.property("wrapS", &self.wrap_s);
         serializer.property("wrapT", &self.wrap_t);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Sampler {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut mag_filter = None;
         let mut min_filter = None;
         let mut wrap_s = None;
@@ -367,17 +623,59 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Sampler {
         }
 
         Some(Self {
-            mag_filter: mag_filter,
-            min_filter: min_filter,
-            wrap_s: wrap_s.map_or_else(|| SamplerWrapS::Repeat, |m| m),
-            wrap_t: wrap_t.map_or_else(|| SamplerWrapT::Repeat, |m| m),
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            mag_filter,
+            min_filter,
+            wrap_s: wrap_s.unwrap_or(SamplerWrapS::Repeat),
+            wrap_t: wrap_t.unwrap_or(SamplerWrapT::Repeat),
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Sampler {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            mag_filter: None,
+            min_filter: None,
+            wrap_s: SamplerWrapS::Repeat,
+            wrap_t: SamplerWrapT::Repeat,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for Sampler {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Sampler {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        crate::extensions::visit_indices_mut(ObjectKind::Sampler, &mut self.extensions, f);
+    }
+}
+
 /// t wrapping mode.
 #[derive(Debug, Clone)]pub enum SamplerWrapT {
     ClampToEdge = 33071,
@@ -500,15 +798,15 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for SamplerMagFilter {
 #[derive(Debug, Clone)]
 pub struct Node {
     /// The index of the camera referenced by this node.
-    pub camera: Option<usize>,
+    pub camera: Option<Index<Camera>>,
     /// The indices of this node's children.
-    pub children: Vec<usize>,
+    pub children: Vec<Index<Node>>,
     /// The index of the skin referenced by this node.
-    pub skin: Option<usize>,
+    pub skin: Option<Index<Skin>>,
     /// A floating-point 4x4 transformation matrix stored in column-major order.
     pub matrix: Option<[f32; 16]>,
     /// The index of the mesh in this node.
-    pub mesh: Option<usize>,
+    pub mesh: Option<Index<Mesh>>,
     /// The node's unit quaternion rotation in the order (x, y, z, w), where w is the scalar.
     pub rotation: Option<[f32; 4]>,
     /// The node's non-uniform scale, given as the scaling factors along the x, y, and z axes.
@@ -528,24 +826,42 @@ pub struct Node {
 impl<S: Serializer> Serialize<S> for Node {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("camera", &self.camera);
+        if let Some(v) = self.camera.as_ref() {
+           serializer.property("camera", v);
+        }
         serializer.property("children", &self.children);
-        serializer.property("skin", &self.skin);
-        serializer.property("matrix", &self.matrix);
-        serializer.property("mesh", &self.mesh);
-        serializer.property("rotation", &self.rotation);
-        serializer.property("scale", &self.scale);
-        serializer.property("translation", &self.translation);
+        if let Some(v) = self.skin.as_ref() {
+           serializer.property("skin", v);
+        }
+        if let Some(v) = self.matrix.as_ref() {
+           serializer.property("matrix", v);
+        }
+        if let Some(v) = self.mesh.as_ref() {
+           serializer.property("mesh", v);
+        }
+        if let Some(v) = self.rotation.as_ref() {
+           serializer.property("rotation", v);
+        }
+        if let Some(v) = self.scale.as_ref() {
+           serializer.property("scale", v);
+        }
+        if let Some(v) = self.translation.as_ref() {
+           serializer.property("translation", v);
+        }
         serializer.property("weights", &self.weights);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Node {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut camera = None;
         let mut children = None;
         let mut skin = None;
@@ -561,11 +877,11 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Node {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "camera" => camera = Some(<usize>::deserialize(deserializer)?),
-                "children" => children = Some(<Vec<usize>>::deserialize(deserializer)?),
-                "skin" => skin = Some(<usize>::deserialize(deserializer)?),
+                "camera" => camera = Some(<Index<Camera>>::deserialize(deserializer)?),
+                "children" => children = Some(<Vec<Index<Node>>>::deserialize(deserializer)?),
+                "skin" => skin = Some(<Index<Skin>>::deserialize(deserializer)?),
                 "matrix" => matrix = Some(<[f32; 16]>::deserialize(deserializer)?),
-                "mesh" => mesh = Some(<usize>::deserialize(deserializer)?),
+                "mesh" => mesh = Some(<Index<Mesh>>::deserialize(deserializer)?),
                 "rotation" => rotation = Some(<[f32; 4]>::deserialize(deserializer)?),
                 "scale" => scale = Some(<[f32; 3]>::deserialize(deserializer)?),
                 "translation" => translation = Some(<[f32; 3]>::deserialize(deserializer)?),
@@ -578,22 +894,81 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Node {
         }
 
         Some(Self {
-            camera: camera,
-            children: children.unwrap_or_else(|| Vec::new()),
-            skin: skin,
-            matrix: if translation.is_none() && rotation.is_none() && scale.is_none() {Some(matrix.clone().map_or_else(|| [1f32, 0f32, 0f32, 0f32, 0f32, 1f32, 0f32, 0f32, 0f32, 0f32, 1f32, 0f32, 0f32, 0f32, 0f32, 1f32, ], |m| m))} else { None },
-            mesh: mesh,
-            rotation: if matrix.is_none() {Some(rotation.clone().map_or_else(|| [0f32, 0f32, 0f32, 1f32, ], |m| m))} else { None },
-            scale: if matrix.is_none() {Some(scale.clone().map_or_else(|| [1f32, 1f32, 1f32, ], |m| m))} else { None },
-            translation: if matrix.is_none() {Some(translation.clone().map_or_else(|| [0f32, 0f32, 0f32, ], |m| m))} else { None },
-            weights: weights.unwrap_or_else(|| Vec::new()),
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            camera,
+            children: children.unwrap_or_default(),
+            skin,
+            matrix: if translation.is_none() && rotation.is_none() && scale.is_none() { Some(matrix.unwrap_or([1f32, 0f32, 0f32, 0f32, 0f32, 1f32, 0f32, 0f32, 0f32, 0f32, 1f32, 0f32, 0f32, 0f32, 0f32, 1f32, ])) } else { None },
+            mesh,
+            rotation: if matrix.is_none() { Some(rotation.unwrap_or([0f32, 0f32, 0f32, 1f32, ])) } else { None },
+            scale: if matrix.is_none() { Some(scale.unwrap_or([1f32, 1f32, 1f32, ])) } else { None },
+            translation: if matrix.is_none() { Some(translation.unwrap_or([0f32, 0f32, 0f32, ])) } else { None },
+            weights: weights.unwrap_or_default(),
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Node {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            camera: None,
+            children: Vec::new(),
+            skin: None,
+            matrix: None,
+            mesh: None,
+            rotation: None,
+            scale: None,
+            translation: None,
+            weights: Vec::new(),
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for Node {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Node {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.camera.iter_mut() {
+            f(IndexKind::Camera, v0.value_mut());
+        }
+        for v0 in self.children.iter_mut() {
+            f(IndexKind::Node, v0.value_mut());
+        }
+        for v0 in self.skin.iter_mut() {
+            f(IndexKind::Skin, v0.value_mut());
+        }
+        for v0 in self.mesh.iter_mut() {
+            f(IndexKind::Mesh, v0.value_mut());
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Node, &mut self.extensions, f);
+    }
+}
+
 /// A set of primitives to be rendered.  A node can contain one mesh.  A node's transform places the mesh in the scene.
 #[derive(Debug, Clone)]
 pub struct Mesh {
@@ -614,15 +989,19 @@ impl<S: Serializer> Serialize<S> for Mesh {
         serializer.begin_object();
         serializer.property("primitives", &self.primitives);
         serializer.property("weights", &self.weights);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Mesh {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut primitives = None;
         let mut weights = None;
         let mut name = None;
@@ -642,27 +1021,64 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Mesh {
 
         Some(Self {
             primitives: primitives?,
-            weights: weights.unwrap_or_else(|| Vec::new()),
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            weights: weights.unwrap_or_default(),
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Mesh {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(primitives: Vec<MeshPrimitive>) -> Self {
+        Self {
+            primitives,
+            weights: Vec::new(),
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for Mesh {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Mesh {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.primitives.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Mesh, &mut self.extensions, f);
+    }
+}
+
 /// Geometry to be rendered with the given material.
 #[derive(Debug, Clone)]
 pub struct MeshPrimitive {
     /// A dictionary object, where each key corresponds to mesh attribute semantic and each value is the index of the accessor containing attribute's data.
-    pub attributes: HashMap<String, usize>,
+    pub attributes: Attributes,
     /// The index of the accessor that contains the indices.
-    pub indices: Option<usize>,
+    pub indices: Option<Index<Accessor>>,
     /// The index of the material to apply to this primitive when rendering.
-    pub material: Option<usize>,
+    pub material: Option<Index<Material>>,
     /// The type of primitives to render.
     pub mode: MeshPrimitiveMode,
     /// An array of Morph Targets, each  Morph Target is a dictionary mapping attributes (only `POSITION`, `NORMAL`, and `TANGENT` supported) to their deviations in the Morph Target.
-    pub targets: Vec<HashMap<String, usize>>,
+    pub targets: Vec<Attributes>,
     /// Dictionary object with extension-specific objects.
     pub extensions: HashMap<String, ThingOwned>,
     /// Application-specific data.
@@ -673,18 +1089,24 @@ impl<S: Serializer> Serialize<S> for MeshPrimitive {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
         serializer.property("attributes", &self.attributes);
-        serializer.property("indices", &self.indices);
-        serializer.property("material", &self.material);
+        if let Some(v) = self.indices.as_ref() {
+           serializer.property("indices", v);
+        }
+        if let Some(v) = self.material.as_ref() {
+           serializer.property("material", v);
+        }
         serializer.property("mode", &self.mode);
         serializer.property("targets", &self.targets);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MeshPrimitive {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut attributes = None;
         let mut indices = None;
         let mut material = None;
@@ -695,11 +1117,11 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MeshPrimitive {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "attributes" => attributes = Some(<HashMap<String, usize>>::deserialize(deserializer)?),
-                "indices" => indices = Some(<usize>::deserialize(deserializer)?),
-                "material" => material = Some(<usize>::deserialize(deserializer)?),
+                "attributes" => attributes = Some(<Attributes>::deserialize(deserializer)?),
+                "indices" => indices = Some(<Index<Accessor>>::deserialize(deserializer)?),
+                "material" => material = Some(<Index<Material>>::deserialize(deserializer)?),
                 "mode" => mode = Some(<MeshPrimitiveMode>::deserialize(deserializer)?),
-                "targets" => targets = Some(<Vec<HashMap<String, usize>>>::deserialize(deserializer)?),
+                "targets" => targets = Some(<Vec<Attributes>>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
                 _ => {}
@@ -708,16 +1130,66 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MeshPrimitive {
 
         Some(Self {
             attributes: attributes?,
-            indices: indices,
-            material: material,
-            mode: mode.map_or_else(|| MeshPrimitiveMode::Triangles, |m| m),
-            targets: targets.unwrap_or_else(|| Vec::new()),
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            indices,
+            material,
+            mode: mode.unwrap_or(MeshPrimitiveMode::Triangles),
+            targets: targets.unwrap_or_default(),
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl MeshPrimitive {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(attributes: Attributes) -> Self {
+        Self {
+            attributes,
+            indices: None,
+            material: None,
+            mode: MeshPrimitiveMode::Triangles,
+            targets: Vec::new(),
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for MeshPrimitive {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for MeshPrimitive {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.attributes.values_mut() {
+            f(IndexKind::Accessor, v0.value_mut());
+        }
+        for v0 in self.indices.iter_mut() {
+            f(IndexKind::Accessor, v0.value_mut());
+        }
+        for v0 in self.material.iter_mut() {
+            f(IndexKind::Material, v0.value_mut());
+        }
+        for v0 in self.targets.iter_mut() {
+            for v1 in v0.values_mut() {
+                f(IndexKind::Accessor, v1.value_mut());
+            }
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::MeshPrimitive, &mut self.extensions, f);
+    }
+}
+
 /// The type of primitives to render.
 #[derive(Debug, Clone)]pub enum MeshPrimitiveMode {
     Points = 0,
@@ -788,13 +1260,25 @@ pub struct Material {
 impl<S: Serializer> Serialize<S> for Material {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
-        serializer.property("pbrMetallicRoughness", &self.pbr_metallic_roughness);
-        serializer.property("normalTexture", &self.normal_texture);
-        serializer.property("occlusionTexture", &self.occlusion_texture);
-        serializer.property("emissiveTexture", &self.emissive_texture);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
+        if let Some(v) = self.pbr_metallic_roughness.as_ref() {
+           serializer.property("pbrMetallicRoughness", v);
+        }
+        if let Some(v) = self.normal_texture.as_ref() {
+           serializer.property("normalTexture", v);
+        }
+        if let Some(v) = self.occlusion_texture.as_ref() {
+           serializer.property("occlusionTexture", v);
+        }
+        if let Some(v) = self.emissive_texture.as_ref() {
+           serializer.property("emissiveTexture", v);
+        }
         serializer.property("emissiveFactor", &self.emissive_factor);
         serializer.property("alphaMode", &self.alpha_mode);
         serializer.property("alphaCutoff", &self.alpha_cutoff);
@@ -804,7 +1288,7 @@ impl<S: Serializer> Serialize<S> for Material {
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Material {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut name = None;
         let mut extensions = None;
         let mut extras = None;
@@ -835,21 +1319,79 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Material {
         }
 
         Some(Self {
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
-            pbr_metallic_roughness: pbr_metallic_roughness,
-            normal_texture: normal_texture,
-            occlusion_texture: occlusion_texture,
-            emissive_texture: emissive_texture,
-            emissive_factor: emissive_factor.map_or_else(|| [0f32, 0f32, 0f32, ], |m| m),
-            alpha_mode: alpha_mode.map_or_else(|| MaterialAlphaMode::Opaque, |m| m),
-            alpha_cutoff: alpha_cutoff.map_or_else(|| 0.5f32, |m| m),
-            double_sided: double_sided.map_or_else(|| false, |m| m),
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
+            pbr_metallic_roughness,
+            normal_texture,
+            occlusion_texture,
+            emissive_texture,
+            emissive_factor: emissive_factor.unwrap_or([0f32, 0f32, 0f32, ]),
+            alpha_mode: alpha_mode.unwrap_or(MaterialAlphaMode::Opaque),
+            alpha_cutoff: alpha_cutoff.unwrap_or(0.5f32),
+            double_sided: double_sided.unwrap_or(false),
         })
     }
 }
 
+impl Material {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+            pbr_metallic_roughness: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            emissive_factor: [0f32, 0f32, 0f32, ],
+            alpha_mode: MaterialAlphaMode::Opaque,
+            alpha_cutoff: 0.5f32,
+            double_sided: false,
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for Material {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Material {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.pbr_metallic_roughness.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.normal_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.occlusion_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.emissive_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Material, &mut self.extensions, f);
+    }
+}
+
 /// The alpha rendering mode of the material.
 #[derive(Debug, Clone)]pub enum MaterialAlphaMode {
     /// The alpha value is ignored and the rendered output is fully opaque.
@@ -885,7 +1427,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialAlphaMode {
 #[derive(Debug, Clone)]
 pub struct MaterialOcclusionTextureInfo {
     /// The index of the texture.
-    pub index: usize,
+    pub index: Index<Texture>,
     /// The set index of texture's TEXCOORD attribute used for texture coordinate mapping.
     pub tex_coord: usize,
     /// A scalar multiplier controlling the amount of occlusion applied.
@@ -902,14 +1444,16 @@ impl<S: Serializer> Serialize<S> for MaterialOcclusionTextureInfo {
         serializer.property("index", &self.index);
         serializer.property("texCoord", &self.tex_coord);
         serializer.property("strength", &self.strength);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialOcclusionTextureInfo {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut index = None;
         let mut tex_coord = None;
         let mut strength = None;
@@ -918,7 +1462,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialOcclusionTextureInf
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "index" => index = Some(<usize>::deserialize(deserializer)?),
+                "index" => index = Some(<Index<Texture>>::deserialize(deserializer)?),
                 "texCoord" => tex_coord = Some(<usize>::deserialize(deserializer)?),
                 "strength" => strength = Some(<f32>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
@@ -929,19 +1473,54 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialOcclusionTextureInf
 
         Some(Self {
             index: index?,
-            tex_coord: tex_coord.map_or_else(|| 0usize, |m| m),
-            strength: strength.map_or_else(|| 1f32, |m| m),
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            tex_coord: tex_coord.unwrap_or(0usize),
+            strength: strength.unwrap_or(1f32),
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl MaterialOcclusionTextureInfo {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(index: Index<Texture>) -> Self {
+        Self {
+            index,
+            tex_coord: 0usize,
+            strength: 1f32,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for MaterialOcclusionTextureInfo {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for MaterialOcclusionTextureInfo {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::Texture, self.index.value_mut());
+        crate::extensions::visit_indices_mut(ObjectKind::MaterialOcclusionTextureInfo, &mut self.extensions, f);
+    }
+}
+
 /// The normal map texture.
 #[derive(Debug, Clone)]
 pub struct MaterialNormalTextureInfo {
     /// The index of the texture.
-    pub index: usize,
+    pub index: Index<Texture>,
     /// The set index of texture's TEXCOORD attribute used for texture coordinate mapping.
     pub tex_coord: usize,
     /// The scalar multiplier applied to each normal vector of the normal texture.
@@ -958,14 +1537,16 @@ impl<S: Serializer> Serialize<S> for MaterialNormalTextureInfo {
         serializer.property("index", &self.index);
         serializer.property("texCoord", &self.tex_coord);
         serializer.property("scale", &self.scale);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialNormalTextureInfo {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut index = None;
         let mut tex_coord = None;
         let mut scale = None;
@@ -974,7 +1555,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialNormalTextureInfo {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "index" => index = Some(<usize>::deserialize(deserializer)?),
+                "index" => index = Some(<Index<Texture>>::deserialize(deserializer)?),
                 "texCoord" => tex_coord = Some(<usize>::deserialize(deserializer)?),
                 "scale" => scale = Some(<f32>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
@@ -985,14 +1566,49 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialNormalTextureInfo {
 
         Some(Self {
             index: index?,
-            tex_coord: tex_coord.map_or_else(|| 0usize, |m| m),
-            scale: scale.map_or_else(|| 1f32, |m| m),
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            tex_coord: tex_coord.unwrap_or(0usize),
+            scale: scale.unwrap_or(1f32),
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl MaterialNormalTextureInfo {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(index: Index<Texture>) -> Self {
+        Self {
+            index,
+            tex_coord: 0usize,
+            scale: 1f32,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for MaterialNormalTextureInfo {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for MaterialNormalTextureInfo {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::Texture, self.index.value_mut());
+        crate::extensions::visit_indices_mut(ObjectKind::MaterialNormalTextureInfo, &mut self.extensions, f);
+    }
+}
+
 /// A set of parameter values that are used to define the metallic-roughness material model from Physically-Based Rendering (PBR) methodology. When not specified, all the default values of `pbrMetallicRoughness` apply.
 #[derive(Debug, Clone)]
 pub struct MaterialPbrMetallicRoughness {
@@ -1016,18 +1632,24 @@ impl<S: Serializer> Serialize<S> for MaterialPbrMetallicRoughness {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
         serializer.property("baseColorFactor", &self.base_color_factor);
-        serializer.property("baseColorTexture", &self.base_color_texture);
+        if let Some(v) = self.base_color_texture.as_ref() {
+           serializer.property("baseColorTexture", v);
+        }
         serializer.property("metallicFactor", &self.metallic_factor);
         serializer.property("roughnessFactor", &self.roughness_factor);
-        serializer.property("metallicRoughnessTexture", &self.metallic_roughness_texture);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.metallic_roughness_texture.as_ref() {
+           serializer.property("metallicRoughnessTexture", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialPbrMetallicRoughness {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut base_color_factor = None;
         let mut base_color_texture = None;
         let mut metallic_factor = None;
@@ -1050,22 +1672,70 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for MaterialPbrMetallicRoughnes
         }
 
         Some(Self {
-            base_color_factor: base_color_factor.map_or_else(|| [1f32, 1f32, 1f32, 1f32, ], |m| m),
-            base_color_texture: base_color_texture,
-            metallic_factor: metallic_factor.map_or_else(|| 1f32, |m| m),
-            roughness_factor: roughness_factor.map_or_else(|| 1f32, |m| m),
-            metallic_roughness_texture: metallic_roughness_texture,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            base_color_factor: base_color_factor.unwrap_or([1f32, 1f32, 1f32, 1f32, ]),
+            base_color_texture,
+            metallic_factor: metallic_factor.unwrap_or(1f32),
+            roughness_factor: roughness_factor.unwrap_or(1f32),
+            metallic_roughness_texture,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl MaterialPbrMetallicRoughness {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            base_color_factor: [1f32, 1f32, 1f32, 1f32, ],
+            base_color_texture: None,
+            metallic_factor: 1f32,
+            roughness_factor: 1f32,
+            metallic_roughness_texture: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for MaterialPbrMetallicRoughness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for MaterialPbrMetallicRoughness {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for MaterialPbrMetallicRoughness {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.base_color_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.metallic_roughness_texture.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::MaterialPbrMetallicRoughness, &mut self.extensions, f);
+    }
+}
+
 /// The base color texture.
 #[derive(Debug, Clone)]
 pub struct TextureInfo {
     /// The index of the texture.
-    pub index: usize,
+    pub index: Index<Texture>,
     /// The set index of texture's TEXCOORD attribute used for texture coordinate mapping.
     pub tex_coord: usize,
     /// Dictionary object with extension-specific objects.
@@ -1079,14 +1749,16 @@ impl<S: Serializer> Serialize<S> for TextureInfo {
         serializer.begin_object();
         serializer.property("index", &self.index);
         serializer.property("texCoord", &self.tex_coord);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for TextureInfo {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut index = None;
         let mut tex_coord = None;
         let mut extensions = None;
@@ -1094,7 +1766,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for TextureInfo {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "index" => index = Some(<usize>::deserialize(deserializer)?),
+                "index" => index = Some(<Index<Texture>>::deserialize(deserializer)?),
                 "texCoord" => tex_coord = Some(<usize>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -1104,14 +1776,48 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for TextureInfo {
 
         Some(Self {
             index: index?,
-            tex_coord: tex_coord.map_or_else(|| 0usize, |m| m),
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            tex_coord: tex_coord.unwrap_or(0usize),
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
-/// Image data used to create a texture. Image can be referenced by URI or `bufferView` index. `mimeType` is required in the latter case.
+impl TextureInfo {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(index: Index<Texture>) -> Self {
+        Self {
+            index,
+            tex_coord: 0usize,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for TextureInfo {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for TextureInfo {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::Texture, self.index.value_mut());
+        crate::extensions::visit_indices_mut(ObjectKind::TextureInfo, &mut self.extensions, f);
+    }
+}
+
+/// Image data used to create a texture. Image can be referenced by URI or `bufferView` index. `mimeType` is required in the latter case.
 #[derive(Debug, Clone)]
 pub struct Image {
     /// The uri of the image.
@@ -1119,7 +1825,7 @@ pub struct Image {
     /// The image's MIME type. Required if `bufferView` is defined.
     pub mime_type: Option<ImageMimeType>,
     /// The index of the bufferView that contains the image. Use this instead of the image's uri property.
-    pub buffer_view: Option<usize>,
+    pub buffer_view: Option<Index<BufferView>>,
     /// The user-defined name of this object.
     pub name: Option<String>,
     /// Dictionary object with extension-specific objects.
@@ -1131,18 +1837,28 @@ pub struct Image {
 impl<S: Serializer> Serialize<S> for Image {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("uri", &self.uri);
-        serializer.property("mimeType", &self.mime_type);
-        serializer.property("bufferView", &self.buffer_view);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.uri.as_ref() {
+           serializer.property("uri", v);
+        }
+        if let Some(v) = self.mime_type.as_ref() {
+           serializer.property("mimeType", v);
+        }
+        if let Some(v) = self.buffer_view.as_ref() {
+           serializer.property("bufferView", v);
+        }
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Image {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut uri = None;
         let mut mime_type = None;
         let mut buffer_view = None;
@@ -1154,7 +1870,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Image {
              match &*property {
                 "uri" => uri = Some(<String>::deserialize(deserializer)?),
                 "mimeType" => mime_type = Some(<ImageMimeType>::deserialize(deserializer)?),
-                "bufferView" => buffer_view = Some(<usize>::deserialize(deserializer)?),
+                "bufferView" => buffer_view = Some(<Index<BufferView>>::deserialize(deserializer)?),
                 "name" => name = Some(<String>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -1163,20 +1879,66 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Image {
         }
 
         Some(Self {
-            uri: uri,
-            mime_type: mime_type,
-            buffer_view: buffer_view,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            uri,
+            mime_type,
+            buffer_view,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Image {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new() -> Self {
+        Self {
+            uri: None,
+            mime_type: None,
+            buffer_view: None,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl Default for Image {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfProperty for Image {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Image {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.buffer_view.iter_mut() {
+            f(IndexKind::BufferView, v0.value_mut());
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Image, &mut self.extensions, f);
+    }
+}
+
 /// The image's MIME type. Required if `bufferView` is defined.
 #[derive(Debug, Clone)]pub enum ImageMimeType {
     ImageJpeg,
     ImagePng,
+    /// Any other value, such as the types that extensions add.
+    Other(String),
 }
 
 impl<S: Serializer> Serialize<S> for ImageMimeType {
@@ -1184,6 +1946,7 @@ impl<S: Serializer> Serialize<S> for ImageMimeType {
         match self {
             Self::ImageJpeg => "image/jpeg".serialize(serializer),
             Self::ImagePng => "image/png".serialize(serializer),
+            Self::Other(value) => value.serialize(serializer),
         }
     }
 }
@@ -1193,7 +1956,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for ImageMimeType {
         Some(match &*value {
             "image/jpeg" => Self::ImageJpeg,
             "image/png" => Self::ImagePng,
-        _ => None?
+        _ => Self::Other(value.to_string()),
         })
     }
 }
@@ -1225,15 +1988,19 @@ impl<S: Serializer> Serialize<S> for Camera {
            serializer.property("perspective", v);
         }
         serializer.property("type", &self.type_);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Camera {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut orthographic = None;
         let mut perspective = None;
         let mut type_ = None;
@@ -1254,16 +2021,57 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Camera {
         }
 
         Some(Self {
-            orthographic: if perspective.is_none() {orthographic.clone()} else { None },
-            perspective: if orthographic.is_none() {perspective.clone()} else { None },
+            orthographic: if perspective.is_none() { orthographic.clone() } else { None },
+            perspective: if orthographic.is_none() { perspective.clone() } else { None },
             type_: type_?,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Camera {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(type_: CameraType) -> Self {
+        Self {
+            orthographic: None,
+            perspective: None,
+            type_,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for Camera {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Camera {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.orthographic.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.perspective.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Camera, &mut self.extensions, f);
+    }
+}
+
 /// Specifies if the camera uses a perspective or orthographic projection.
 #[derive(Debug, Clone)]pub enum CameraType {
     Perspective,
@@ -1309,18 +2117,24 @@ pub struct CameraPerspective {
 impl<S: Serializer> Serialize<S> for CameraPerspective {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("aspectRatio", &self.aspect_ratio);
+        if let Some(v) = self.aspect_ratio.as_ref() {
+           serializer.property("aspectRatio", v);
+        }
         serializer.property("yfov", &self.yfov);
-        serializer.property("zfar", &self.zfar);
+        if let Some(v) = self.zfar.as_ref() {
+           serializer.property("zfar", v);
+        }
         serializer.property("znear", &self.znear);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for CameraPerspective {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut aspect_ratio = None;
         let mut yfov = None;
         let mut zfar = None;
@@ -1341,16 +2155,51 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for CameraPerspective {
         }
 
         Some(Self {
-            aspect_ratio: aspect_ratio,
+            aspect_ratio,
             yfov: yfov?,
-            zfar: zfar,
+            zfar,
             znear: znear?,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl CameraPerspective {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(yfov: f32, znear: f32) -> Self {
+        Self {
+            aspect_ratio: None,
+            yfov,
+            zfar: None,
+            znear,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for CameraPerspective {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for CameraPerspective {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        crate::extensions::visit_indices_mut(ObjectKind::CameraPerspective, &mut self.extensions, f);
+    }
+}
+
 /// An orthographic camera containing properties to create an orthographic projection matrix.
 #[derive(Debug, Clone)]
 pub struct CameraOrthographic {
@@ -1375,14 +2224,16 @@ impl<S: Serializer> Serialize<S> for CameraOrthographic {
         serializer.property("ymag", &self.ymag);
         serializer.property("zfar", &self.zfar);
         serializer.property("znear", &self.znear);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for CameraOrthographic {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut xmag = None;
         let mut ymag = None;
         let mut zfar = None;
@@ -1407,17 +2258,52 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for CameraOrthographic {
             ymag: ymag?,
             zfar: zfar?,
             znear: znear?,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl CameraOrthographic {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(xmag: f32, ymag: f32, zfar: f32, znear: f32) -> Self {
+        Self {
+            xmag,
+            ymag,
+            zfar,
+            znear,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for CameraOrthographic {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for CameraOrthographic {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        crate::extensions::visit_indices_mut(ObjectKind::CameraOrthographic, &mut self.extensions, f);
+    }
+}
+
 /// A view into a buffer generally representing a subset of the buffer.
 #[derive(Debug, Clone)]
 pub struct BufferView {
     /// The index of the buffer.
-    pub buffer: usize,
+    pub buffer: Index<Buffer>,
     /// The offset into the buffer in bytes.
     pub byte_offset: usize,
     /// The length of the bufferView in bytes.
@@ -1440,17 +2326,25 @@ impl<S: Serializer> Serialize<S> for BufferView {
         serializer.property("buffer", &self.buffer);
         serializer.property("byteOffset", &self.byte_offset);
         serializer.property("byteLength", &self.byte_length);
-        serializer.property("byteStride", &self.byte_stride);
-        serializer.property("target", &self.target);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.byte_stride.as_ref() {
+           serializer.property("byteStride", v);
+        }
+        if let Some(v) = self.target.as_ref() {
+           serializer.property("target", v);
+        }
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for BufferView {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut buffer = None;
         let mut byte_offset = None;
         let mut byte_length = None;
@@ -1462,7 +2356,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for BufferView {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "buffer" => buffer = Some(<usize>::deserialize(deserializer)?),
+                "buffer" => buffer = Some(<Index<Buffer>>::deserialize(deserializer)?),
                 "byteOffset" => byte_offset = Some(<usize>::deserialize(deserializer)?),
                 "byteLength" => byte_length = Some(<usize>::deserialize(deserializer)?),
                 "byteStride" => byte_stride = Some(<usize>::deserialize(deserializer)?),
@@ -1476,17 +2370,55 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for BufferView {
 
         Some(Self {
             buffer: buffer?,
-            byte_offset: byte_offset.map_or_else(|| 0usize, |m| m),
+            byte_offset: byte_offset.unwrap_or(0usize),
             byte_length: byte_length?,
-            byte_stride: byte_stride,
-            target: target,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            byte_stride,
+            target,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl BufferView {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(buffer: Index<Buffer>, byte_length: usize) -> Self {
+        Self {
+            buffer,
+            byte_offset: 0usize,
+            byte_length,
+            byte_stride: None,
+            target: None,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for BufferView {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for BufferView {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::Buffer, self.buffer.value_mut());
+        crate::extensions::visit_indices_mut(ObjectKind::BufferView, &mut self.extensions, f);
+    }
+}
+
 /// The target that the GPU buffer should be bound to.
 #[derive(Debug, Clone)]pub enum BufferViewTarget {
     ArrayBuffer = 34962,
@@ -1530,17 +2462,23 @@ pub struct Buffer {
 impl<S: Serializer> Serialize<S> for Buffer {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("uri", &self.uri);
+        if let Some(v) = self.uri.as_ref() {
+           serializer.property("uri", v);
+        }
         serializer.property("byteLength", &self.byte_length);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Buffer {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut uri = None;
         let mut byte_length = None;
         let mut name = None;
@@ -1559,15 +2497,49 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Buffer {
         }
 
         Some(Self {
-            uri: uri,
+            uri,
             byte_length: byte_length?,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Buffer {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(byte_length: usize) -> Self {
+        Self {
+            uri: None,
+            byte_length,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for Buffer {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Buffer {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        crate::extensions::visit_indices_mut(ObjectKind::Buffer, &mut self.extensions, f);
+    }
+}
+
 /// Metadata about the glTF asset.
 #[derive(Debug, Clone)]
 pub struct Asset {
@@ -1588,18 +2560,26 @@ pub struct Asset {
 impl<S: Serializer> Serialize<S> for Asset {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("copyright", &self.copyright);
-        serializer.property("generator", &self.generator);
+        if let Some(v) = self.copyright.as_ref() {
+           serializer.property("copyright", v);
+        }
+        if let Some(v) = self.generator.as_ref() {
+           serializer.property("generator", v);
+        }
         serializer.property("version", &self.version);
-        serializer.property("minVersion", &self.min_version);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.min_version.as_ref() {
+           serializer.property("minVersion", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Asset {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut copyright = None;
         let mut generator = None;
         let mut version = None;
@@ -1620,16 +2600,51 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Asset {
         }
 
         Some(Self {
-            copyright: copyright,
-            generator: generator,
+            copyright,
+            generator,
             version: version?,
-            min_version: min_version,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            min_version,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Asset {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(version: String) -> Self {
+        Self {
+            copyright: None,
+            generator: None,
+            version,
+            min_version: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for Asset {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Asset {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        crate::extensions::visit_indices_mut(ObjectKind::Asset, &mut self.extensions, f);
+    }
+}
+
 /// A keyframe animation.
 #[derive(Debug, Clone)]
 pub struct Animation {
@@ -1650,15 +2665,19 @@ impl<S: Serializer> Serialize<S> for Animation {
         serializer.begin_object();
         serializer.property("channels", &self.channels);
         serializer.property("samplers", &self.samplers);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Animation {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut channels = None;
         let mut samplers = None;
         let mut name = None;
@@ -1679,22 +2698,62 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Animation {
         Some(Self {
             channels: channels?,
             samplers: samplers?,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Animation {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(channels: Vec<AnimationChannel>, samplers: Vec<AnimationSampler>) -> Self {
+        Self {
+            channels,
+            samplers,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for Animation {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Animation {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.channels.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        for v0 in self.samplers.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Animation, &mut self.extensions, f);
+    }
+}
+
 /// Combines input and output accessors with an interpolation algorithm to define a keyframe graph (but not its target).
 #[derive(Debug, Clone)]
 pub struct AnimationSampler {
     /// The index of an accessor containing keyframe input values, e.g., time.
-    pub input: usize,
+    pub input: Index<Accessor>,
     /// Interpolation algorithm.
     pub interpolation: AnimationSamplerInterpolation,
     /// The index of an accessor, containing keyframe output values.
-    pub output: usize,
+    pub output: Index<Accessor>,
     /// Dictionary object with extension-specific objects.
     pub extensions: HashMap<String, ThingOwned>,
     /// Application-specific data.
@@ -1707,14 +2766,16 @@ impl<S: Serializer> Serialize<S> for AnimationSampler {
         serializer.property("input", &self.input);
         serializer.property("interpolation", &self.interpolation);
         serializer.property("output", &self.output);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationSampler {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut input = None;
         let mut interpolation = None;
         let mut output = None;
@@ -1723,9 +2784,9 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationSampler {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "input" => input = Some(<usize>::deserialize(deserializer)?),
+                "input" => input = Some(<Index<Accessor>>::deserialize(deserializer)?),
                 "interpolation" => interpolation = Some(<AnimationSamplerInterpolation>::deserialize(deserializer)?),
-                "output" => output = Some(<usize>::deserialize(deserializer)?),
+                "output" => output = Some(<Index<Accessor>>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
                 _ => {}
@@ -1734,14 +2795,50 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationSampler {
 
         Some(Self {
             input: input?,
-            interpolation: interpolation.map_or_else(|| AnimationSamplerInterpolation::Linear, |m| m),
+            interpolation: interpolation.unwrap_or(AnimationSamplerInterpolation::Linear),
             output: output?,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl AnimationSampler {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(input: Index<Accessor>, output: Index<Accessor>) -> Self {
+        Self {
+            input,
+            interpolation: AnimationSamplerInterpolation::Linear,
+            output,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for AnimationSampler {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for AnimationSampler {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::Accessor, self.input.value_mut());
+        f(IndexKind::Accessor, self.output.value_mut());
+        crate::extensions::visit_indices_mut(ObjectKind::AnimationSampler, &mut self.extensions, f);
+    }
+}
+
 /// Interpolation algorithm.
 #[derive(Debug, Clone)]pub enum AnimationSamplerInterpolation {
     /// The animated values are linearly interpolated between keyframes. When targeting a rotation, spherical linear interpolation (slerp) should be used to interpolate quaternions. The number output of elements must equal the number of input elements.
@@ -1777,7 +2874,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationSamplerInterpolati
 #[derive(Debug, Clone)]
 pub struct AnimationChannel {
     /// The index of a sampler in this animation used to compute the value for the target.
-    pub sampler: usize,
+    pub sampler: Index<AnimationSampler>,
     /// The index of the node and TRS property to target.
     pub target: AnimationChannelTarget,
     /// Dictionary object with extension-specific objects.
@@ -1791,14 +2888,16 @@ impl<S: Serializer> Serialize<S> for AnimationChannel {
         serializer.begin_object();
         serializer.property("sampler", &self.sampler);
         serializer.property("target", &self.target);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationChannel {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut sampler = None;
         let mut target = None;
         let mut extensions = None;
@@ -1806,7 +2905,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationChannel {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "sampler" => sampler = Some(<usize>::deserialize(deserializer)?),
+                "sampler" => sampler = Some(<Index<AnimationSampler>>::deserialize(deserializer)?),
                 "target" => target = Some(<AnimationChannelTarget>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -1817,17 +2916,52 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationChannel {
         Some(Self {
             sampler: sampler?,
             target: target?,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl AnimationChannel {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(sampler: Index<AnimationSampler>, target: AnimationChannelTarget) -> Self {
+        Self {
+            sampler,
+            target,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for AnimationChannel {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for AnimationChannel {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::AnimationSampler, self.sampler.value_mut());
+        self.target.visit_indices_mut(f);
+        crate::extensions::visit_indices_mut(ObjectKind::AnimationChannel, &mut self.extensions, f);
+    }
+}
+
 /// The index of the node and TRS property to target.
 #[derive(Debug, Clone)]
 pub struct AnimationChannelTarget {
     /// The index of the node to target.
-    pub node: Option<usize>,
+    pub node: Option<Index<Node>>,
     /// The name of the node's TRS property to modify, or the "weights" of the Morph Targets it instantiates. For the "translation" property, the values that are provided by the sampler are the translation along the x, y, and z axes. For the "rotation" property, the values are a quaternion in the order (x, y, z, w), where w is the scalar. For the "scale" property, the values are the scaling factors along the x, y, and z axes.
     pub path: AnimationChannelTargetPath,
     /// Dictionary object with extension-specific objects.
@@ -1839,16 +2973,20 @@ pub struct AnimationChannelTarget {
 impl<S: Serializer> Serialize<S> for AnimationChannelTarget {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("node", &self.node);
+        if let Some(v) = self.node.as_ref() {
+           serializer.property("node", v);
+        }
         serializer.property("path", &self.path);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationChannelTarget {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut node = None;
         let mut path = None;
         let mut extensions = None;
@@ -1856,7 +2994,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationChannelTarget {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "node" => node = Some(<usize>::deserialize(deserializer)?),
+                "node" => node = Some(<Index<Node>>::deserialize(deserializer)?),
                 "path" => path = Some(<AnimationChannelTargetPath>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -1865,14 +3003,50 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationChannelTarget {
         }
 
         Some(Self {
-            node: node,
+            node,
             path: path?,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl AnimationChannelTarget {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(path: AnimationChannelTargetPath) -> Self {
+        Self {
+            node: None,
+            path,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for AnimationChannelTarget {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for AnimationChannelTarget {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.node.iter_mut() {
+            f(IndexKind::Node, v0.value_mut());
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::AnimationChannelTarget, &mut self.extensions, f);
+    }
+}
+
 /// The name of the node's TRS property to modify, or the "weights" of the Morph Targets it instantiates. For the "translation" property, the values that are provided by the sampler are the translation along the x, y, and z axes. For the "rotation" property, the values are a quaternion in the order (x, y, z, w), where w is the scalar. For the "scale" property, the values are the scaling factors along the x, y, and z axes.
 #[derive(Debug, Clone)]pub enum AnimationChannelTargetPath {
     Translation,
@@ -1908,7 +3082,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AnimationChannelTargetPath
 #[derive(Debug, Clone)]
 pub struct Accessor {
     /// The index of the bufferView.
-    pub buffer_view: Option<usize>,
+    pub buffer_view: Option<Index<BufferView>>,
     /// The offset relative to the start of the bufferView in bytes.
     pub byte_offset: usize,
     /// The datatype of components in the attribute.
@@ -1936,7 +3110,9 @@ pub struct Accessor {
 impl<S: Serializer> Serialize<S> for Accessor {
     fn serialize(&self, serializer: &mut S) {
         serializer.begin_object();
-        serializer.property("bufferView", &self.buffer_view);
+        if let Some(v) = self.buffer_view.as_ref() {
+           serializer.property("bufferView", v);
+        }
         serializer.property("byteOffset", &self.byte_offset);
         serializer.property("componentType", &self.component_type);
         serializer.property("normalized", &self.normalized);
@@ -1944,16 +3120,22 @@ impl<S: Serializer> Serialize<S> for Accessor {
         serializer.property("type", &self.type_);
         serializer.property("max", &self.max);
         serializer.property("min", &self.min);
-        serializer.property("sparse", &self.sparse);
-        serializer.property("name", &self.name);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        if let Some(v) = self.sparse.as_ref() {
+           serializer.property("sparse", v);
+        }
+        if let Some(v) = self.name.as_ref() {
+           serializer.property("name", v);
+        }
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Accessor {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut buffer_view = None;
         let mut byte_offset = None;
         let mut component_type = None;
@@ -1969,7 +3151,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Accessor {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "bufferView" => buffer_view = Some(<usize>::deserialize(deserializer)?),
+                "bufferView" => buffer_view = Some(<Index<BufferView>>::deserialize(deserializer)?),
                 "byteOffset" => byte_offset = Some(<usize>::deserialize(deserializer)?),
                 "componentType" => component_type = Some(<AccessorComponentType>::deserialize(deserializer)?),
                 "normalized" => normalized = Some(<bool>::deserialize(deserializer)?),
@@ -1986,22 +3168,69 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Accessor {
         }
 
         Some(Self {
-            buffer_view: buffer_view,
-            byte_offset: byte_offset.map_or_else(|| 0usize, |m| m),
+            buffer_view,
+            byte_offset: byte_offset.unwrap_or(0usize),
             component_type: component_type?,
-            normalized: normalized.map_or_else(|| false, |m| m),
+            normalized: normalized.unwrap_or(false),
             count: count?,
             type_: type_?,
-            max: max.unwrap_or_else(|| Vec::new()),
-            min: min.unwrap_or_else(|| Vec::new()),
-            sparse: sparse,
-            name: name,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            max: max.unwrap_or_default(),
+            min: min.unwrap_or_default(),
+            sparse,
+            name,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl Accessor {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(component_type: AccessorComponentType, count: usize, type_: AccessorType) -> Self {
+        Self {
+            buffer_view: None,
+            byte_offset: 0usize,
+            component_type,
+            normalized: false,
+            count,
+            type_,
+            max: Vec::new(),
+            min: Vec::new(),
+            sparse: None,
+            name: None,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for Accessor {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for Accessor {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        for v0 in self.buffer_view.iter_mut() {
+            f(IndexKind::BufferView, v0.value_mut());
+        }
+        for v0 in self.sparse.iter_mut() {
+            v0.visit_indices_mut(f);
+        }
+        crate::extensions::visit_indices_mut(ObjectKind::Accessor, &mut self.extensions, f);
+    }
+}
+
 /// Sparse storage of attributes that deviate from their initialization value.
 #[derive(Debug, Clone)]
 pub struct AccessorSparse {
@@ -2023,14 +3252,16 @@ impl<S: Serializer> Serialize<S> for AccessorSparse {
         serializer.property("count", &self.count);
         serializer.property("indices", &self.indices);
         serializer.property("values", &self.values);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparse {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut count = None;
         let mut indices = None;
         let mut values = None;
@@ -2052,17 +3283,53 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparse {
             count: count?,
             indices: indices?,
             values: values?,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl AccessorSparse {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(count: usize, indices: AccessorSparseIndices, values: AccessorSparseValues) -> Self {
+        Self {
+            count,
+            indices,
+            values,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for AccessorSparse {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for AccessorSparse {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        self.indices.visit_indices_mut(f);
+        self.values.visit_indices_mut(f);
+        crate::extensions::visit_indices_mut(ObjectKind::AccessorSparse, &mut self.extensions, f);
+    }
+}
+
 /// Array of size `count` times number of components, storing the displaced accessor attributes pointed by `indices`. Substituted values must have the same `componentType` and number of components as the base accessor.
 #[derive(Debug, Clone)]
 pub struct AccessorSparseValues {
     /// The index of the bufferView with sparse values. Referenced bufferView can't have ARRAY_BUFFER or ELEMENT_ARRAY_BUFFER target.
-    pub buffer_view: usize,
+    pub buffer_view: Index<BufferView>,
     /// The offset relative to the start of the bufferView in bytes. Must be aligned.
     pub byte_offset: usize,
     /// Dictionary object with extension-specific objects.
@@ -2076,14 +3343,16 @@ impl<S: Serializer> Serialize<S> for AccessorSparseValues {
         serializer.begin_object();
         serializer.property("bufferView", &self.buffer_view);
         serializer.property("byteOffset", &self.byte_offset);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparseValues {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut buffer_view = None;
         let mut byte_offset = None;
         let mut extensions = None;
@@ -2091,7 +3360,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparseValues {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "bufferView" => buffer_view = Some(<usize>::deserialize(deserializer)?),
+                "bufferView" => buffer_view = Some(<Index<BufferView>>::deserialize(deserializer)?),
                 "byteOffset" => byte_offset = Some(<usize>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
                 "extras" => extras = Some(<ThingOwned>::deserialize(deserializer)?),
@@ -2101,18 +3370,52 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparseValues {
 
         Some(Self {
             buffer_view: buffer_view?,
-            byte_offset: byte_offset.map_or_else(|| 0usize, |m| m),
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            byte_offset: byte_offset.unwrap_or(0usize),
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl AccessorSparseValues {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(buffer_view: Index<BufferView>) -> Self {
+        Self {
+            buffer_view,
+            byte_offset: 0usize,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for AccessorSparseValues {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for AccessorSparseValues {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::BufferView, self.buffer_view.value_mut());
+        crate::extensions::visit_indices_mut(ObjectKind::AccessorSparseValues, &mut self.extensions, f);
+    }
+}
+
 /// Index array of size `count` that points to those accessor attributes that deviate from their initialization value. Indices must strictly increase.
 #[derive(Debug, Clone)]
 pub struct AccessorSparseIndices {
     /// The index of the bufferView with sparse indices. Referenced bufferView can't have ARRAY_BUFFER or ELEMENT_ARRAY_BUFFER target.
-    pub buffer_view: usize,
+    pub buffer_view: Index<BufferView>,
     /// The offset relative to the start of the bufferView in bytes. Must be aligned.
     pub byte_offset: usize,
     /// The indices data type.
@@ -2129,14 +3432,16 @@ impl<S: Serializer> Serialize<S> for AccessorSparseIndices {
         serializer.property("bufferView", &self.buffer_view);
         serializer.property("byteOffset", &self.byte_offset);
         serializer.property("componentType", &self.component_type);
-        serializer.property("extensions", &self.extensions);
-        serializer.property("extras", &self.extras);
+        serializer.property("extensions", &Sorted(&self.extensions));
+        if let Some(v) = self.extras.as_ref() {
+           serializer.property("extras", &Sorted(v));
+        }
         serializer.end_object();
     }
 }
 impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparseIndices {
     fn deserialize(deserializer: &mut D) -> Option<Self> {
-        deserializer.begin_object().then(|| {})?;
+        deserializer.begin_object().then_some(())?;
         let mut buffer_view = None;
         let mut byte_offset = None;
         let mut component_type = None;
@@ -2145,7 +3450,7 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparseIndices {
 
         while let Some(property) = deserializer.has_property() {
              match &*property {
-                "bufferView" => buffer_view = Some(<usize>::deserialize(deserializer)?),
+                "bufferView" => buffer_view = Some(<Index<BufferView>>::deserialize(deserializer)?),
                 "byteOffset" => byte_offset = Some(<usize>::deserialize(deserializer)?),
                 "componentType" => component_type = Some(<AccessorSparseIndicesComponentType>::deserialize(deserializer)?),
                 "extensions" => extensions = Some(<HashMap<String, ThingOwned>>::deserialize(deserializer)?),
@@ -2156,14 +3461,49 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorSparseIndices {
 
         Some(Self {
             buffer_view: buffer_view?,
-            byte_offset: byte_offset.map_or_else(|| 0usize, |m| m),
+            byte_offset: byte_offset.unwrap_or(0usize),
             component_type: component_type?,
-            extensions: extensions.unwrap_or_else(|| HashMap::new()),
-            extras: extras,
+            extensions: extensions.unwrap_or_default(),
+            extras,
         })
     }
 }
 
+impl AccessorSparseIndices {
+    /// Creates the object from its required properties. The rest are empty or the schema's default.
+    pub fn new(buffer_view: Index<BufferView>, component_type: AccessorSparseIndicesComponentType) -> Self {
+        Self {
+            buffer_view,
+            byte_offset: 0usize,
+            component_type,
+            extensions: HashMap::new(),
+            extras: None,
+        }
+    }
+}
+
+impl GltfProperty for AccessorSparseIndices {
+    fn extensions(&self) -> &HashMap<String, ThingOwned> {
+        &self.extensions
+    }
+    fn extensions_mut(&mut self) -> &mut HashMap<String, ThingOwned> {
+        &mut self.extensions
+    }
+    fn extras(&self) -> Option<&ThingOwned> {
+        self.extras.as_ref()
+    }
+    fn extras_mut(&mut self) -> &mut Option<ThingOwned> {
+        &mut self.extras
+    }
+}
+
+impl VisitIndices for AccessorSparseIndices {
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize)) {
+        f(IndexKind::BufferView, self.buffer_view.value_mut());
+        crate::extensions::visit_indices_mut(ObjectKind::AccessorSparseIndices, &mut self.extensions, f);
+    }
+}
+
 /// The indices data type.
 #[derive(Debug, Clone)]pub enum AccessorSparseIndicesComponentType {
     UnsignedByte = 5121,
@@ -2269,3 +3609,434 @@ impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for AccessorComponentType {
     }
 }
 
+/// The kinds of glTF objects that can be extended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    GlTf,
+    Texture,
+    Skin,
+    Scene,
+    Sampler,
+    Node,
+    Mesh,
+    MeshPrimitive,
+    Material,
+    MaterialOcclusionTextureInfo,
+    MaterialNormalTextureInfo,
+    MaterialPbrMetallicRoughness,
+    TextureInfo,
+    Image,
+    Camera,
+    CameraPerspective,
+    CameraOrthographic,
+    BufferView,
+    Buffer,
+    Asset,
+    Animation,
+    AnimationSampler,
+    AnimationChannel,
+    AnimationChannelTarget,
+    Accessor,
+    AccessorSparse,
+    AccessorSparseValues,
+    AccessorSparseIndices,
+}
+
+impl GlTf {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::GlTf, &self.extensions);
+        for v in self.accessors.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.animations.iter() {
+            v.visit_extensions(f);
+        }
+        self.asset.visit_extensions(f);
+        for v in self.buffers.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.buffer_views.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.cameras.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.images.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.materials.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.meshes.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.nodes.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.samplers.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.scenes.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.skins.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.textures.iter() {
+            v.visit_extensions(f);
+        }
+    }
+}
+
+impl Texture {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Texture, &self.extensions);
+    }
+}
+
+impl Skin {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Skin, &self.extensions);
+    }
+}
+
+impl Scene {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Scene, &self.extensions);
+    }
+}
+
+impl Sampler {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Sampler, &self.extensions);
+    }
+}
+
+impl Node {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Node, &self.extensions);
+    }
+}
+
+impl Mesh {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Mesh, &self.extensions);
+        for v in self.primitives.iter() {
+            v.visit_extensions(f);
+        }
+    }
+}
+
+impl MeshPrimitive {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::MeshPrimitive, &self.extensions);
+    }
+}
+
+impl Material {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Material, &self.extensions);
+        for v in self.pbr_metallic_roughness.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.normal_texture.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.occlusion_texture.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.emissive_texture.iter() {
+            v.visit_extensions(f);
+        }
+    }
+}
+
+impl MaterialOcclusionTextureInfo {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::MaterialOcclusionTextureInfo, &self.extensions);
+    }
+}
+
+impl MaterialNormalTextureInfo {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::MaterialNormalTextureInfo, &self.extensions);
+    }
+}
+
+impl MaterialPbrMetallicRoughness {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::MaterialPbrMetallicRoughness, &self.extensions);
+        for v in self.base_color_texture.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.metallic_roughness_texture.iter() {
+            v.visit_extensions(f);
+        }
+    }
+}
+
+impl TextureInfo {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::TextureInfo, &self.extensions);
+    }
+}
+
+impl Image {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Image, &self.extensions);
+    }
+}
+
+impl Camera {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Camera, &self.extensions);
+        for v in self.orthographic.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.perspective.iter() {
+            v.visit_extensions(f);
+        }
+    }
+}
+
+impl CameraPerspective {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::CameraPerspective, &self.extensions);
+    }
+}
+
+impl CameraOrthographic {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::CameraOrthographic, &self.extensions);
+    }
+}
+
+impl BufferView {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::BufferView, &self.extensions);
+    }
+}
+
+impl Buffer {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Buffer, &self.extensions);
+    }
+}
+
+impl Asset {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Asset, &self.extensions);
+    }
+}
+
+impl Animation {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Animation, &self.extensions);
+        for v in self.channels.iter() {
+            v.visit_extensions(f);
+        }
+        for v in self.samplers.iter() {
+            v.visit_extensions(f);
+        }
+    }
+}
+
+impl AnimationSampler {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::AnimationSampler, &self.extensions);
+    }
+}
+
+impl AnimationChannel {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::AnimationChannel, &self.extensions);
+        self.target.visit_extensions(f);
+    }
+}
+
+impl AnimationChannelTarget {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::AnimationChannelTarget, &self.extensions);
+    }
+}
+
+impl Accessor {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::Accessor, &self.extensions);
+        for v in self.sparse.iter() {
+            v.visit_extensions(f);
+        }
+    }
+}
+
+impl AccessorSparse {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::AccessorSparse, &self.extensions);
+        self.indices.visit_extensions(f);
+        self.values.visit_extensions(f);
+    }
+}
+
+impl AccessorSparseValues {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::AccessorSparseValues, &self.extensions);
+    }
+}
+
+impl AccessorSparseIndices {
+    pub(crate) fn visit_extensions(&self, f: &mut dyn FnMut(ObjectKind, &HashMap<String, ThingOwned>)) {
+        f(ObjectKind::AccessorSparseIndices, &self.extensions);
+    }
+}
+
+pub type AccessorIndex = Index<Accessor>;
+pub type AnimationIndex = Index<Animation>;
+pub type BufferIndex = Index<Buffer>;
+pub type BufferViewIndex = Index<BufferView>;
+pub type CameraIndex = Index<Camera>;
+pub type ImageIndex = Index<Image>;
+pub type MaterialIndex = Index<Material>;
+pub type MeshIndex = Index<Mesh>;
+pub type NodeIndex = Index<Node>;
+pub type SamplerIndex = Index<Sampler>;
+pub type SceneIndex = Index<Scene>;
+pub type SkinIndex = Index<Skin>;
+pub type TextureIndex = Index<Texture>;
+pub type AnimationSamplerIndex = Index<AnimationSampler>;
+
+impl RootObject for Accessor {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.accessors
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.accessors
+    }
+}
+
+impl RootObject for Animation {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.animations
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.animations
+    }
+}
+
+impl RootObject for Buffer {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.buffers
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.buffers
+    }
+}
+
+impl RootObject for BufferView {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.buffer_views
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.buffer_views
+    }
+}
+
+impl RootObject for Camera {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.cameras
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.cameras
+    }
+}
+
+impl RootObject for Image {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.images
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.images
+    }
+}
+
+impl RootObject for Material {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.materials
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.materials
+    }
+}
+
+impl RootObject for Mesh {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.meshes
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.meshes
+    }
+}
+
+impl RootObject for Node {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.nodes
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.nodes
+    }
+}
+
+impl RootObject for Sampler {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.samplers
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.samplers
+    }
+}
+
+impl RootObject for Scene {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.scenes
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.scenes
+    }
+}
+
+impl RootObject for Skin {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.skins
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.skins
+    }
+}
+
+impl RootObject for Texture {
+    fn list(gltf: &GlTf) -> &Vec<Self> {
+        &gltf.textures
+    }
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self> {
+        &mut gltf.textures
+    }
+}
+
+/// The kinds of objects an index can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexKind {
+    Scene,
+    Node,
+    Camera,
+    Skin,
+    Mesh,
+    Accessor,
+    Sampler,
+    Image,
+    Texture,
+    BufferView,
+    Material,
+    Buffer,
+    AnimationSampler,
+    KhrMaterialsVariantsVariant,
+}
+
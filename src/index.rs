@@ -0,0 +1,177 @@
+use kserde::*;
+use std::marker::PhantomData;
+
+use crate::{GlTf, IndexKind};
+
+/// The index of an object of type `T`, usually in one of the lists at the root of the glTF.
+///
+/// Indices are stored as plain integers in Json. The type only exists so an index of one kind
+/// of object can't be used where another kind is expected.
+pub struct Index<T> {
+    index: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Index<T> {
+    pub const fn new(index: usize) -> Self {
+        Self {
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The position of the object in its list.
+    pub const fn value(self) -> usize {
+        self.index
+    }
+
+    /// The position of the object in its list, to change what the index refers to
+    /// without knowing its type.
+    pub fn value_mut(&mut self) -> &mut usize {
+        &mut self.index
+    }
+}
+
+// These are implemented by hand because deriving them would require `T` to implement them too.
+impl<T> Clone for Index<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Index<T> {}
+
+impl<T> PartialEq for Index<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Index<T> {}
+
+impl<T> PartialOrd for Index<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Index<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> std::hash::Hash for Index<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+impl<T> std::fmt::Debug for Index<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.index.fmt(f)
+    }
+}
+
+impl<T> From<usize> for Index<T> {
+    fn from(index: usize) -> Self {
+        Self::new(index)
+    }
+}
+
+impl<S: Serializer, T> Serialize<S> for Index<T> {
+    fn serialize(&self, serializer: &mut S) {
+        self.index.serialize(serializer)
+    }
+}
+
+impl<'a, D: Deserializer<'a>, T> Deserialize<'a, D> for Index<T> {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        Some(Self::new(<usize>::deserialize(deserializer)?))
+    }
+}
+
+/// An object stored in one of the lists at the root of the glTF.
+pub trait RootObject: Sized {
+    fn list(gltf: &GlTf) -> &Vec<Self>;
+    fn list_mut(gltf: &mut GlTf) -> &mut Vec<Self>;
+}
+
+/// An object that can list the indices it contains, including those in nested objects
+/// and in the extensions in `extensions`, whether or not their features are enabled.
+pub trait VisitIndices {
+    /// Calls `f` with the kind of object each index refers to and a reference to change it.
+    fn visit_indices_mut(&mut self, f: &mut dyn FnMut(IndexKind, &mut usize));
+}
+
+impl GlTf {
+    /// Returns the object at an index, or `None` if the index is out of bounds.
+    pub fn get<T: RootObject>(&self, index: Index<T>) -> Option<&T> {
+        T::list(self).get(index.value())
+    }
+
+    pub fn get_mut<T: RootObject>(&mut self, index: Index<T>) -> Option<&mut T> {
+        T::list_mut(self).get_mut(index.value())
+    }
+
+    /// Appends an object to its list and returns its index.
+    pub fn push<T: RootObject>(&mut self, object: T) -> Index<T> {
+        let list = T::list_mut(self);
+        list.push(object);
+        Index::new(list.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use kserde::ThingOwned;
+
+    #[test]
+    fn pushed_objects_can_be_found() {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        let first = gltf.push(Camera::new(CameraType::Perspective));
+        let second = gltf.push(Node::default());
+        let third = gltf.push(Node::default());
+        assert_eq!((first.value(), second.value(), third.value()), (0, 0, 1));
+
+        gltf.get_mut(third).unwrap().name = Some("third".to_string());
+        assert_eq!(gltf.get(third).unwrap().name.as_deref(), Some("third"));
+        assert!(gltf.get(second).unwrap().name.is_none());
+        assert!(gltf.get(NodeIndex::new(2)).is_none());
+        assert!(gltf.get_mut(CameraIndex::new(1)).is_none());
+    }
+
+    #[test]
+    fn indices_in_objects_and_extensions_are_visited() {
+        let mut texture = Texture {
+            sampler: Some(SamplerIndex::new(1)),
+            source: Some(ImageIndex::new(2)),
+            ..Default::default()
+        };
+        texture.extensions.insert(
+            "KHR_texture_basisu".to_string(),
+            ThingOwned::from_json(r#"{ "source": 3 }"#).unwrap(),
+        );
+        let mut visited = Vec::new();
+        texture.visit_indices_mut(&mut |kind, index| {
+            visited.push((kind, *index));
+            *index += 10;
+        });
+        assert_eq!(
+            visited,
+            [
+                (IndexKind::Sampler, 1),
+                (IndexKind::Image, 2),
+                (IndexKind::Image, 3)
+            ]
+        );
+        assert_eq!(texture.sampler, Some(SamplerIndex::new(11)));
+        assert_eq!(texture.source, Some(ImageIndex::new(12)));
+        let source = match &texture.extensions["KHR_texture_basisu"] {
+            ThingOwned::Object(extension) => extension.get("source"),
+            _ => None,
+        };
+        assert!(matches!(source, Some(ThingOwned::Number(n)) if *n == 13.0));
+    }
+}
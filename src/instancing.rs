@@ -0,0 +1,224 @@
+//! Nodes that draw their mesh many times with `EXT_mesh_gpu_instancing`.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+use crate::extensions::ext_mesh_gpu_instancing::*;
+use crate::*;
+
+impl ExtMeshGpuInstancing {
+    /// The accessor with the translation of each instance.
+    pub fn translation(&self) -> Option<AccessorIndex> {
+        self.attributes.get("TRANSLATION").copied()
+    }
+
+    /// The accessor with the rotation quaternion of each instance.
+    pub fn rotation(&self) -> Option<AccessorIndex> {
+        self.attributes.get("ROTATION").copied()
+    }
+
+    /// The accessor with the scale of each instance.
+    pub fn scale(&self) -> Option<AccessorIndex> {
+        self.attributes.get("SCALE").copied()
+    }
+}
+
+/// The `N` components of the `i`th vector in `values`, or `default` if it's missing.
+fn vector<const N: usize>(values: &[f32], i: usize, default: [f32; N]) -> [f32; N] {
+    values
+        .get(i * N..(i + 1) * N)
+        .map_or(default, |v| v.try_into().unwrap())
+}
+
+/// Iterates over the world transform of each instance of a node.
+pub struct InstanceTransforms {
+    world: [f32; 16],
+    translations: Vec<f32>,
+    rotations: Vec<f32>,
+    scales: Vec<f32>,
+    count: usize,
+    index: usize,
+}
+
+impl Iterator for InstanceTransforms {
+    type Item = [f32; 16];
+
+    fn next(&mut self) -> Option<[f32; 16]> {
+        if self.index >= self.count {
+            return None;
+        }
+        let i = self.index;
+        self.index += 1;
+        let instance = compose_matrix(
+            vector(&self.translations, i, [0.0; 3]),
+            vector(&self.rotations, i, [0.0, 0.0, 0.0, 1.0]),
+            vector(&self.scales, i, [1.0; 3]),
+        );
+        Some(multiply_matrices(&self.world, &instance))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl GlTf {
+    /// Returns the transform of each instance of a node combined with the node's world transform.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers.
+    /// Missing attributes are treated as the identity. The iterator is empty if the node
+    /// doesn't use `EXT_mesh_gpu_instancing`.
+    pub fn instance_transforms(
+        &self,
+        node: NodeIndex,
+        buffers: &[Vec<u8>],
+    ) -> Result<InstanceTransforms, AccessorError> {
+        let extension = self
+            .get(node)
+            .and_then(|n| n.extension::<ExtMeshGpuInstancing>());
+        let mut instances = InstanceTransforms {
+            world: IDENTITY_MATRIX,
+            translations: Vec::new(),
+            rotations: Vec::new(),
+            scales: Vec::new(),
+            count: 0,
+            index: 0,
+        };
+        let extension = match extension {
+            Some(extension) => extension,
+            None => return Ok(instances),
+        };
+
+        instances.world = self.world_matrices()[node.value()];
+        let mut count = None;
+        for (accessor, values) in [
+            (extension.translation(), &mut instances.translations),
+            (extension.rotation(), &mut instances.rotations),
+            (extension.scale(), &mut instances.scales),
+        ] {
+            if let Some(accessor) = accessor {
+                *values = self.read_accessor_f32(accessor, buffers)?;
+                let accessor_count = self.accessors[accessor.value()].count;
+                count = Some(count.map_or(accessor_count, |c: usize| c.min(accessor_count)));
+            }
+        }
+        instances.count = count.unwrap_or(0);
+        Ok(instances)
+    }
+}
+
+/// Creates a node that draws a mesh once for each instance.
+///
+/// ```ignore
+/// let node = InstancedNodeBuilder::new(mesh)
+///     .instance([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], [1.0; 3])
+///     .instance([2.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], [0.5; 3])
+///     .build(&mut gltf, &mut buffers)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct InstancedNodeBuilder {
+    mesh: MeshIndex,
+    translations: Vec<f32>,
+    rotations: Vec<f32>,
+    scales: Vec<f32>,
+}
+
+impl InstancedNodeBuilder {
+    pub fn new(mesh: MeshIndex) -> Self {
+        Self {
+            mesh,
+            translations: Vec::new(),
+            rotations: Vec::new(),
+            scales: Vec::new(),
+        }
+    }
+
+    /// Adds an instance with a translation, a rotation quaternion `[x, y, z, w]` and a scale.
+    pub fn instance(mut self, translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) -> Self {
+        self.translations.extend_from_slice(&translation);
+        self.rotations.extend_from_slice(&rotation);
+        self.scales.extend_from_slice(&scale);
+        self
+    }
+
+    /// Adds the node and its accessors to the glTF and returns the node's index.
+    ///
+    /// The accessors are appended to the first buffer in `buffers`. Rotations and scales
+    /// are only stored if some instance isn't the identity. The node isn't added to a scene.
+    /// Returns `None` without changing the glTF if there are no instances,
+    /// as accessors can't be empty.
+    pub fn build(self, gltf: &mut GlTf, buffers: &mut Vec<Vec<u8>>) -> Option<NodeIndex> {
+        if self.translations.is_empty() {
+            return None;
+        }
+        let mut attributes = HashMap::new();
+        let rotated = self
+            .rotations
+            .chunks_exact(4)
+            .any(|r| r != [0.0, 0.0, 0.0, 1.0]);
+        let scaled = self.scales.iter().any(|&s| s != 1.0);
+        for (name, values, type_, stored) in [
+            ("TRANSLATION", &self.translations, AccessorType::Vec3, true),
+            ("ROTATION", &self.rotations, AccessorType::Vec4, rotated),
+            ("SCALE", &self.scales, AccessorType::Vec3, scaled),
+        ] {
+            if stored {
+                let accessor = gltf.push_accessor(
+                    buffers,
+                    values,
+                    type_,
+                    AccessorComponentType::Float,
+                    false,
+                    None,
+                );
+                attributes.insert(name.to_string(), accessor);
+            }
+        }
+
+        let mut node = Node {
+            mesh: Some(self.mesh),
+            ..Default::default()
+        };
+        node.set_extension(&ExtMeshGpuInstancing::new(attributes));
+        gltf.add_extension_used(ExtMeshGpuInstancing::NAME, false);
+        Some(gltf.push(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn built_instances_round_trip() {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        let mut buffers = Vec::new();
+        let mesh = gltf.push(Mesh::new(Vec::new()));
+        assert!(InstancedNodeBuilder::new(mesh)
+            .build(&mut gltf, &mut buffers)
+            .is_none());
+        assert!(gltf.accessors.is_empty() && gltf.extensions_used.is_empty());
+
+        let rotation = [
+            0.0,
+            0.0,
+            std::f32::consts::FRAC_1_SQRT_2,
+            std::f32::consts::FRAC_1_SQRT_2,
+        ];
+        let node = InstancedNodeBuilder::new(mesh)
+            .instance([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], [1.0; 3])
+            .instance([0.0; 3], rotation, [2.0; 3])
+            .build(&mut gltf, &mut buffers)
+            .unwrap();
+        let transforms: Vec<[f32; 16]> =
+            gltf.instance_transforms(node, &buffers).unwrap().collect();
+        assert_eq!(transforms.len(), 2);
+        assert_eq!(
+            transforms[0],
+            compose_matrix([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], [1.0; 3])
+        );
+        assert_eq!(transforms[1], compose_matrix([0.0; 3], rotation, [2.0; 3]));
+    }
+}
@@ -0,0 +1,333 @@
+//! Deterministic Json output, so saving an unchanged glTF produces identical bytes.
+
+use kserde::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{GlTf, Index};
+
+/// Serializes maps with their keys sorted, including the objects nested in Json values.
+///
+/// The generated serialization code wraps every map and Json value in this.
+pub(crate) struct Sorted<'a, T>(pub &'a T);
+
+pub(crate) trait SerializeSorted<S: Serializer> {
+    fn serialize_sorted(&self, serializer: &mut S);
+}
+
+impl<S: Serializer, T: SerializeSorted<S>> Serialize<S> for Sorted<'_, T> {
+    fn serialize(&self, serializer: &mut S) {
+        self.0.serialize_sorted(serializer)
+    }
+}
+
+impl<S: Serializer> SerializeSorted<S> for ThingOwned {
+    fn serialize_sorted(&self, serializer: &mut S) {
+        match self {
+            ThingOwned::Object(object) => object.serialize_sorted(serializer),
+            ThingOwned::Array(array) => array.serialize_sorted(serializer),
+            _ => self.serialize(serializer),
+        }
+    }
+}
+
+impl<S: Serializer, T: SerializeSorted<S>> SerializeSorted<S> for HashMap<String, T> {
+    fn serialize_sorted(&self, serializer: &mut S) {
+        let mut keys: Vec<&String> = self.keys().collect();
+        keys.sort();
+        serializer.begin_object();
+        for key in keys {
+            serializer.property(key, &Sorted(&self[key]));
+        }
+        serializer.end_object();
+    }
+}
+
+impl<S: Serializer, T: SerializeSorted<S>> SerializeSorted<S> for Vec<T> {
+    fn serialize_sorted(&self, serializer: &mut S) {
+        serializer.begin_array();
+        for value in self {
+            serializer.value(&Sorted(value));
+        }
+        serializer.end_array();
+    }
+}
+
+impl<S: Serializer, T: SerializeSorted<S>> SerializeSorted<S> for Option<T> {
+    fn serialize_sorted(&self, serializer: &mut S) {
+        match self {
+            Some(value) => value.serialize_sorted(serializer),
+            None => serializer.null(),
+        }
+    }
+}
+
+impl<S: Serializer> SerializeSorted<S> for usize {
+    fn serialize_sorted(&self, serializer: &mut S) {
+        self.serialize(serializer)
+    }
+}
+
+impl<S: Serializer, T> SerializeSorted<S> for Index<T> {
+    fn serialize_sorted(&self, serializer: &mut S) {
+        self.serialize(serializer)
+    }
+}
+
+/// Writes Json, optionally indented, with numbers in their shortest form that reads back
+/// as the same value.
+pub struct JsonWriter {
+    output: String,
+    /// The number of spaces to indent each level by, or `None` to write everything on one line.
+    indent: Option<usize>,
+    /// Whether each open object or array has no entries yet.
+    empty: Vec<bool>,
+}
+
+impl JsonWriter {
+    pub fn new(indent: Option<usize>) -> Self {
+        Self {
+            output: String::new(),
+            indent,
+            empty: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+
+    fn new_line(&mut self) {
+        if let Some(indent) = self.indent {
+            self.output.push('\n');
+            for _ in 0..indent * self.empty.len() {
+                self.output.push(' ');
+            }
+        }
+    }
+
+    fn begin_entry(&mut self) {
+        if let Some(empty) = self.empty.last_mut() {
+            if !*empty {
+                self.output.push(',');
+            }
+            *empty = false;
+        }
+        self.new_line();
+    }
+
+    fn end(&mut self, close: char) {
+        if self.empty.pop() == Some(false) {
+            self.new_line();
+        }
+        self.output.push(close);
+    }
+}
+
+impl Serializer for JsonWriter {
+    fn begin_object(&mut self) {
+        self.output.push('{');
+        self.empty.push(true);
+    }
+
+    fn end_object(&mut self) {
+        self.end('}');
+    }
+
+    fn begin_array(&mut self) {
+        self.output.push('[');
+        self.empty.push(true);
+    }
+
+    fn end_array(&mut self) {
+        self.end(']');
+    }
+
+    fn property<V: Serialize<Self>>(&mut self, name: &str, value: &V) {
+        self.begin_entry();
+        self.string(name);
+        self.output
+            .push_str(if self.indent.is_some() { ": " } else { ":" });
+        value.serialize(self);
+    }
+
+    fn value<V: Serialize<Self>>(&mut self, value: &V) {
+        self.begin_entry();
+        value.serialize(self);
+    }
+
+    fn string(&mut self, s: &str) {
+        self.output.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.output.push_str("\\\""),
+                '\\' => self.output.push_str("\\\\"),
+                '\n' => self.output.push_str("\\n"),
+                '\r' => self.output.push_str("\\r"),
+                '\t' => self.output.push_str("\\t"),
+                c if (c as u32) < 0x20 => write!(self.output, "\\u{:04x}", c as u32).unwrap(),
+                c => self.output.push(c),
+            }
+        }
+        self.output.push('"');
+    }
+
+    fn bool(&mut self, b: bool) {
+        self.output.push_str(if b { "true" } else { "false" });
+    }
+
+    fn i64(&mut self, i: i64) {
+        write!(self.output, "{}", i).unwrap();
+    }
+
+    fn f64(&mut self, n: f64) {
+        if !n.is_finite() {
+            // Json has no representation for these.
+            self.null();
+        } else if n as f32 as f64 == n {
+            // Most glTF numbers are `f32`, which are written in the shortest form of the `f32`
+            // rather than with the digits of the widened `f64`.
+            write_number(&mut self.output, n as f32);
+        } else {
+            write_number(&mut self.output, n);
+        }
+    }
+
+    fn null(&mut self) {
+        self.output.push_str("null");
+    }
+}
+
+/// Writes a number in the shortest form that parses to the same value, with an exponent
+/// if it's very large or small.
+fn write_number<N: std::fmt::Display + std::fmt::LowerExp + Into<f64> + Copy>(
+    output: &mut String,
+    n: N,
+) {
+    let magnitude = n.into().abs();
+    if magnitude == 0.0 || (1e-5..1e16).contains(&magnitude) {
+        write!(output, "{}", n).unwrap();
+    } else {
+        write!(output, "{:e}", n).unwrap();
+    }
+}
+
+impl GlTf {
+    /// Serializes the glTF to Json, indenting each level by `indent` spaces if it's `Some`.
+    ///
+    /// Keys are in a fixed order and numbers are written in their shortest form,
+    /// so saving a glTF that was loaded and not changed produces identical output.
+    pub fn to_json_formatted(&self, indent: Option<usize>) -> String {
+        let mut writer = JsonWriter::new(indent);
+        self.serialize(&mut writer);
+        writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_models::*;
+    use crate::{
+        Accessor, AccessorComponentType, AccessorType, Material, MaterialAlphaMode,
+        MaterialPbrMetallicRoughness, Sampler, SamplerWrapS, SamplerWrapT,
+    };
+
+    fn write(json: &str, indent: Option<usize>) -> String {
+        let mut writer = JsonWriter::new(indent);
+        Sorted(&ThingOwned::from_json(json).unwrap()).serialize(&mut writer);
+        writer.finish()
+    }
+
+    #[test]
+    fn keys_are_sorted() {
+        let json = write(
+            r#"{ "b": 1, "c": { "z": 2, "y": 3 }, "a": [{ "e": 4, "d": 5 }] }"#,
+            None,
+        );
+        assert_eq!(json, r#"{"a":[{"d":5,"e":4}],"b":1,"c":{"y":3,"z":2}}"#);
+        assert_eq!(write(&json, None), json);
+    }
+
+    #[test]
+    fn indented_output() {
+        let json = write(r#"{ "b": [1, { "c": null }], "a": {} }"#, Some(2));
+        let expected =
+            "{\n  \"a\": {},\n  \"b\": [\n    1,\n    {\n      \"c\": null\n    }\n  ]\n}";
+        assert_eq!(json, expected);
+        assert_eq!(write(&json, Some(2)), json);
+    }
+
+    #[test]
+    fn numbers_are_shortest() {
+        let mut thing =
+            ThingOwned::from_json("[0.1, 1e-7, 1e20, 0.30000000000000004, 3, -0.5]").unwrap();
+        if let ThingOwned::Array(values) = &mut thing {
+            // Widened `f32`s are written with the digits of the `f32`.
+            values.push(ThingOwned::Number(0.1f32 as f64));
+        }
+        let mut writer = JsonWriter::new(None);
+        Sorted(&thing).serialize(&mut writer);
+        let json = writer.finish();
+        assert_eq!(json, "[0.1,1e-7,1e20,0.30000000000000004,3,-0.5,0.1]");
+        assert_eq!(write(&json, None), json);
+    }
+
+    #[test]
+    fn nulls_in_extras_are_kept() {
+        let (mut gltf, _) = cube();
+        // Missing values are left out.
+        assert!(!gltf.to_json_formatted(None).contains("null"));
+
+        gltf.extras = ThingOwned::from_json(r#"{ "a": null, "b": [null] }"#);
+        let json = gltf.to_json_formatted(None);
+        assert!(json.contains(r#""extras":{"a":null,"b":[null]}"#));
+        let reloaded = GlTf::from_json(&json).unwrap();
+        assert_eq!(reloaded.to_json_formatted(None), json);
+    }
+
+    #[test]
+    fn defaults_come_from_the_schema() {
+        let gltf = GlTf::from_json(
+            r#"{
+                "asset": {"version": "2.0"},
+                "accessors": [{"componentType": 5126, "count": 1, "type": "SCALAR"}],
+                "materials": [{"pbrMetallicRoughness": {}}],
+                "samplers": [{}]
+            }"#,
+        )
+        .unwrap();
+        let pbr = gltf.materials[0].pbr_metallic_roughness.clone().unwrap();
+        let material = &gltf.materials[0];
+        for (sampler, pbr, material, accessor) in [
+            (&gltf.samplers[0], &pbr, material, &gltf.accessors[0]),
+            (
+                &Sampler::default(),
+                &MaterialPbrMetallicRoughness::default(),
+                &Material::new(),
+                &Accessor::new(AccessorComponentType::Float, 1, AccessorType::Scalar),
+            ),
+        ] {
+            assert!(matches!(sampler.wrap_s, SamplerWrapS::Repeat));
+            assert!(matches!(sampler.wrap_t, SamplerWrapT::Repeat));
+            assert_eq!(pbr.base_color_factor, [1.0; 4]);
+            assert_eq!((pbr.metallic_factor, pbr.roughness_factor), (1.0, 1.0));
+            assert!(matches!(material.alpha_mode, MaterialAlphaMode::Opaque));
+            assert_eq!(material.alpha_cutoff, 0.5);
+            assert!(!accessor.normalized);
+            assert_eq!(accessor.byte_offset, 0);
+        }
+    }
+
+    #[test]
+    fn models_round_trip() {
+        for (gltf, _) in [cube(), fox()] {
+            // The maps of the reloaded glTF can be in another order, as each has its own hasher.
+            for indent in [None, Some(2)] {
+                let json = gltf.to_json_formatted(indent);
+                let reloaded = GlTf::from_json(&json).unwrap();
+                assert_eq!(reloaded.to_json_formatted(indent), json);
+            }
+        }
+    }
+}
@@ -3,10 +3,59 @@
 //! This crate is auto-generated from the specification's Json Schema,
 //! so some comments may not exactly match the Rust names.
 
+mod accessor;
+mod bake;
+mod builder;
+mod dedup;
+#[cfg(feature = "KHR_draco_mesh_compression")]
+pub mod draco;
+mod extension;
+mod extract;
+pub mod extensions;
 mod glb;
 mod gltf_json;
+mod index;
+#[cfg(feature = "EXT_mesh_gpu_instancing")]
+mod instancing;
+mod json;
+mod merge;
+#[cfg(feature = "EXT_meshopt_compression")]
+pub mod meshopt;
+mod normals;
+mod optimize;
+mod prune;
+#[cfg(feature = "KHR_mesh_quantization")]
+mod quantization;
+mod semantic;
+mod tangents;
+#[cfg(test)]
+mod test_models;
+mod texture;
+mod transform;
+mod triangles;
+#[cfg(feature = "KHR_materials_variants")]
+mod variants;
+mod weld;
 
+pub use accessor::*;
+pub use builder::*;
+pub use extension::*;
+pub use extract::*;
 pub use glb::*;
 pub use gltf_json::*;
+pub use index::*;
+#[cfg(feature = "EXT_mesh_gpu_instancing")]
+pub use instancing::*;
+pub use json::*;
+pub use merge::*;
+pub use normals::*;
+pub use optimize::*;
+pub use prune::*;
+#[cfg(feature = "KHR_mesh_quantization")]
+pub use quantization::*;
+pub use semantic::*;
+pub use texture::*;
+pub use transform::*;
+pub use weld::*;
 
 pub use kserde::{FromJson, ToJson};
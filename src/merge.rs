@@ -0,0 +1,400 @@
+//! Combining several glTF documents into one.
+
+use kserde::ThingOwned;
+
+use crate::prune::{new_indices, retain_used, MeshoptData};
+use crate::*;
+
+/// What happens to the scenes of the document that's merged in.
+#[derive(Debug, Clone)]
+pub enum MergeScenes {
+    /// Adds the nodes of each scene to the scene with the same index,
+    /// and appends the scenes this document doesn't have. If only the other document has
+    /// scenes, this document's root nodes are added to its default scene first.
+    Merge,
+    /// Adds the root nodes of the default scene as children of a new node with this matrix,
+    /// which is added to this document's default scene. The other scenes are dropped.
+    UnderNode([f32; 16]),
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// Appends the data of the merged buffers to the first buffer instead of keeping
+    /// them as separate buffers. Buffers whose data isn't loaded are kept separate.
+    pub concatenate_buffers: bool,
+    pub scenes: MergeScenes,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            concatenate_buffers: true,
+            scenes: MergeScenes::Merge,
+        }
+    }
+}
+
+/// The root nodes of the default scene, or of every node if there are no scenes.
+fn default_roots(gltf: &GlTf) -> Vec<NodeIndex> {
+    match gltf.scenes.get(gltf.scene.map_or(0, |s| s.value())) {
+        Some(scene) => scene.nodes.clone(),
+        None if gltf.scenes.is_empty() => gltf
+            .node_parents()
+            .iter()
+            .enumerate()
+            .filter(|(_, parent)| parent.is_none())
+            .map(|(node, _)| NodeIndex::new(node))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+impl GlTf {
+    /// Appends the objects of another document, updating its indices to refer to their new
+    /// positions, and combines the extensions each document uses.
+    ///
+    /// `buffers` and `other_buffers` hold the data for each of the documents' buffers.
+    /// The asset information of this document is kept. The root extensions of the other
+    /// document are added, and the lights of `KHR_lights_punctual` and the variants of
+    /// `KHR_materials_variants` are appended to this document's.
+    ///
+    /// Returns an error without changing anything if an object in either document uses
+    /// an extension whose indices aren't known, as they can't be updated.
+    pub fn merge(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mut other: GlTf,
+        mut other_buffers: Vec<Vec<u8>>,
+        options: &MergeOptions,
+    ) -> Result<(), ExtensionError> {
+        self.check_extension_indices()?;
+        other.check_extension_indices()?;
+        buffers.resize(self.buffers.len(), Vec::new());
+        other_buffers.resize(other.buffers.len(), Vec::new());
+
+        let roots = default_roots(self);
+        let other_roots = default_roots(&other);
+        let variants = self.append_root_list(&mut other, "KHR_materials_variants", "variants");
+        let lights = self.append_root_list(&mut other, "KHR_lights_punctual", "lights");
+        for node in other.nodes.iter_mut() {
+            if let Some(ThingOwned::Object(light)) = node.extensions.get_mut("KHR_lights_punctual")
+            {
+                if let Some(ThingOwned::Number(light)) = light.get_mut("light") {
+                    *light += lights as f64;
+                }
+            }
+        }
+        for (name, extension) in other.extensions.drain() {
+            self.extensions.entry(name).or_insert(extension);
+        }
+        other.visit_indices_mut(&mut |kind, index| {
+            *index += match kind {
+                IndexKind::Accessor => self.accessors.len(),
+                IndexKind::Buffer => self.buffers.len(),
+                IndexKind::BufferView => self.buffer_views.len(),
+                IndexKind::Camera => self.cameras.len(),
+                IndexKind::Image => self.images.len(),
+                IndexKind::Material => self.materials.len(),
+                IndexKind::Mesh => self.meshes.len(),
+                IndexKind::Node => self.nodes.len(),
+                IndexKind::Sampler => self.samplers.len(),
+                IndexKind::Skin => self.skins.len(),
+                IndexKind::Texture => self.textures.len(),
+                IndexKind::KhrMaterialsVariantsVariant => variants,
+                // Scenes are merged by position, and animation samplers are relative
+                // to their animation.
+                IndexKind::Scene | IndexKind::AnimationSampler => 0,
+            };
+        });
+        let other_roots: Vec<NodeIndex> = other_roots
+            .into_iter()
+            .map(|node| NodeIndex::new(node.value() + self.nodes.len()))
+            .collect();
+        let first_merged_buffer = self.buffers.len();
+
+        self.accessors.append(&mut other.accessors);
+        self.animations.append(&mut other.animations);
+        self.buffers.append(&mut other.buffers);
+        buffers.append(&mut other_buffers);
+        self.buffer_views.append(&mut other.buffer_views);
+        self.cameras.append(&mut other.cameras);
+        self.images.append(&mut other.images);
+        self.materials.append(&mut other.materials);
+        self.meshes.append(&mut other.meshes);
+        self.nodes.append(&mut other.nodes);
+        self.samplers.append(&mut other.samplers);
+        self.skins.append(&mut other.skins);
+        self.textures.append(&mut other.textures);
+        for name in &other.extensions_used {
+            let required = other.extensions_required.contains(name);
+            self.add_extension_used(name, required);
+        }
+
+        match options.scenes {
+            MergeScenes::Merge if other.scenes.is_empty() => {
+                self.add_to_default_scene(&other_roots);
+            }
+            MergeScenes::Merge => {
+                // Without scenes every node was shown, so this document's nodes
+                // are added to the default scene before the other's.
+                if self.scenes.is_empty() {
+                    self.scenes.push(Scene {
+                        nodes: roots,
+                        ..Default::default()
+                    });
+                    self.scene = Some(SceneIndex::new(0));
+                }
+                for (i, mut scene) in other.scenes.into_iter().enumerate() {
+                    match self.scenes.get_mut(i) {
+                        Some(existing) => existing.nodes.append(&mut scene.nodes),
+                        None => self.scenes.push(scene),
+                    }
+                }
+                self.scene = self.scene.or(other.scene);
+            }
+            MergeScenes::UnderNode(matrix) => {
+                let node = self.push(Node {
+                    children: other_roots,
+                    matrix: Some(matrix),
+                    ..Default::default()
+                });
+                self.add_to_default_scene(&[node]);
+            }
+        }
+
+        if options.concatenate_buffers {
+            self.concatenate_buffers(buffers, first_merged_buffer);
+        }
+        Ok(())
+    }
+
+    /// Adds nodes to the default scene, if there are any scenes.
+    fn add_to_default_scene(&mut self, nodes: &[NodeIndex]) {
+        let scene = self.scene.map_or(0, |s| s.value());
+        if let Some(scene) = self.scenes.get_mut(scene) {
+            scene.nodes.extend_from_slice(nodes);
+        }
+    }
+
+    /// Appends the list `name` in the other document's root extension `extension` to the list
+    /// in this document's, and returns the length this document's list had.
+    fn append_root_list(&mut self, other: &mut GlTf, extension: &str, name: &str) -> usize {
+        let other_list = match other.extensions.get_mut(extension) {
+            Some(ThingOwned::Object(other_extension)) => other_extension.remove(name),
+            _ => None,
+        };
+        let list = match self.extensions.get_mut(extension) {
+            Some(ThingOwned::Object(extension)) => extension.get_mut(name),
+            _ => None,
+        };
+        match (list, other_list) {
+            (Some(ThingOwned::Array(list)), Some(ThingOwned::Array(mut other_list))) => {
+                let count = list.len();
+                list.append(&mut other_list);
+                count
+            }
+            (Some(ThingOwned::Array(list)), _) => list.len(),
+            (_, Some(other_list)) => {
+                // This document has no list, so the other's is used as it is.
+                let extension = match self.extensions.get_mut(extension) {
+                    Some(ThingOwned::Object(extension)) => extension,
+                    _ => match other.extensions.get_mut(extension) {
+                        Some(ThingOwned::Object(extension)) => extension,
+                        _ => return 0,
+                    },
+                };
+                extension.insert(name.to_string(), other_list);
+                0
+            }
+            (_, None) => 0,
+        }
+    }
+
+    /// Appends the data of the buffers from `first` on to the first buffer, aligned to 4 bytes,
+    /// and removes them. Buffers whose data isn't in `buffers` are kept.
+    fn concatenate_buffers(&mut self, buffers: &mut Vec<Vec<u8>>, first: usize) {
+        let is_loaded = |gltf: &GlTf, buffers: &[Vec<u8>], b: usize| {
+            buffers[b].len() >= gltf.buffers[b].byte_length
+        };
+        if self.buffers.is_empty() || !is_loaded(self, buffers, 0) {
+            return;
+        }
+
+        // The new offset of the start of each buffer in the first buffer.
+        let mut offsets = vec![None; self.buffers.len()];
+        for b in first.max(1)..self.buffers.len() {
+            if !is_loaded(self, buffers, b) {
+                continue;
+            }
+            let data = std::mem::take(&mut buffers[b]);
+            let target = &mut buffers[0];
+            target.resize((target.len() + 3) & !3, 0);
+            offsets[b] = Some(target.len());
+            target.extend_from_slice(&data[..self.buffers[b].byte_length]);
+        }
+        self.buffers[0].byte_length = buffers[0].len();
+
+        let offset_of = |b: usize| offsets.get(b).copied().flatten();
+        for view in self.buffer_views.iter_mut() {
+            if let Some(offset) = offset_of(view.buffer.value()) {
+                view.buffer = BufferIndex::new(0);
+                view.byte_offset += offset;
+            }
+            if let Some(mut compressed) = MeshoptData::get(view) {
+                if let Some(offset) = offset_of(compressed.buffer) {
+                    compressed.buffer = 0;
+                    compressed.byte_offset += offset;
+                    compressed.set(view);
+                }
+            }
+        }
+
+        let kept: Vec<bool> = offsets.iter().map(|o| o.is_none()).collect();
+        let new = new_indices(&kept);
+        retain_used(&mut self.buffers, &kept);
+        retain_used(buffers, &kept);
+        self.visit_indices_mut(&mut |kind, index| {
+            if kind == IndexKind::Buffer {
+                *index = new.get(*index).copied().unwrap_or(*index);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+    use kserde::ThingOwned;
+
+    fn lights(gltf: &GlTf) -> usize {
+        match &gltf.extensions["KHR_lights_punctual"] {
+            ThingOwned::Object(extension) => match &extension["lights"] {
+                ThingOwned::Array(lights) => lights.len(),
+                _ => panic!("expected an array"),
+            },
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn merge_round_trips_models() {
+        let (fox, fox_buffers) = fox();
+        let (cube, cube_buffers) = cube();
+        let (mut gltf, mut buffers) = (fox.clone(), fox_buffers.clone());
+        let empty = GlTf::new(Asset::new("2.0".to_string()));
+        gltf.merge(&mut buffers, empty, Vec::new(), &MergeOptions::default())
+            .unwrap();
+        assert_eq!(gltf.to_json_formatted(None), fox.to_json_formatted(None));
+
+        gltf.merge(
+            &mut buffers,
+            cube.clone(),
+            cube_buffers.clone(),
+            &MergeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(gltf.buffers.len(), 1);
+        let mut expected = world_positions(&fox, &fox_buffers);
+        expected.extend(world_positions(&cube, &cube_buffers));
+        assert_close(&world_positions(&gltf, &buffers), &expected, 0.0);
+
+        // Nothing is left unused by the merged scene.
+        let merged = gltf.to_json_formatted(None);
+        gltf.prune(&mut buffers, &PruneOptions::default()).unwrap();
+        assert_eq!(gltf.to_json_formatted(None), merged);
+    }
+
+    #[test]
+    fn merge_appends_root_lists() {
+        let document = |lights: &str| {
+            GlTf::from_json(&format!(
+                r#"{{
+                    "asset": {{ "version": "2.0" }},
+                    "extensions": {{ "KHR_lights_punctual": {{ "lights": [{}] }} }},
+                    "nodes": [{{ "extensions": {{ "KHR_lights_punctual": {{ "light": 0 }} }} }}],
+                    "scenes": [{{ "nodes": [0] }}]
+                }}"#,
+                lights
+            ))
+            .unwrap()
+        };
+        let mut gltf = document(r#"{ "type": "point" }, { "type": "spot" }"#);
+        let other = document(r#"{ "type": "directional" }"#);
+        gltf.merge(&mut Vec::new(), other, Vec::new(), &MergeOptions::default())
+            .unwrap();
+        assert_eq!(lights(&gltf), 3);
+        let light = match &gltf.nodes[1].extensions["KHR_lights_punctual"] {
+            ThingOwned::Object(extension) => &extension["light"],
+            _ => panic!("expected an object"),
+        };
+        assert!(matches!(light, ThingOwned::Number(n) if *n == 2.0));
+        assert_eq!(gltf.scenes[0].nodes, vec![Index::new(0), Index::new(1)]);
+    }
+
+    #[test]
+    fn merge_into_document_without_scenes() {
+        let (mut gltf, mut buffers) = cube();
+        gltf.scenes.clear();
+        gltf.scene = None;
+        let roots = gltf.nodes.len();
+        let (other, other_buffers) = cube();
+        let other_roots = other.scenes[0].nodes.clone();
+        gltf.merge(&mut buffers, other, other_buffers, &MergeOptions::default())
+            .unwrap();
+        let mut expected: Vec<NodeIndex> = (0..roots).map(Index::new).collect();
+        expected.extend(other_roots.iter().map(|n| Index::new(n.value() + roots)));
+        assert_eq!(gltf.scenes.len(), 1);
+        assert_eq!(gltf.scenes[0].nodes, expected);
+    }
+
+    #[test]
+    fn merge_refuses_unknown_extensions() {
+        let (mut gltf, mut buffers) = cube();
+        let (mut other, other_buffers) = cube();
+        other.nodes[0]
+            .extensions
+            .insert("EXT_unknown".to_string(), ThingOwned::Null);
+        let json = gltf.to_json_formatted(None);
+        let result = gltf.merge(&mut buffers, other, other_buffers, &MergeOptions::default());
+        assert!(matches!(result, Err(ExtensionError::UnknownIndices(_))));
+        assert_eq!(gltf.to_json_formatted(None), json);
+    }
+
+    #[test]
+    fn merge_moves_meshopt_data() {
+        // Compressed data is moved whether or not `EXT_meshopt_compression` is enabled.
+        let document = GlTf::from_json(
+            r#"{
+                "asset": { "version": "2.0" },
+                "buffers": [{ "byteLength": 6 }],
+                "bufferViews": [{
+                    "buffer": 0,
+                    "byteLength": 2,
+                    "extensions": { "EXT_meshopt_compression": {
+                        "buffer": 0, "byteOffset": 2, "byteLength": 4,
+                        "byteStride": 2, "count": 1, "mode": "ATTRIBUTES"
+                    } }
+                }]
+            }"#,
+        )
+        .unwrap();
+        let mut gltf = document.clone();
+        let mut buffers = vec![vec![1; 6]];
+        gltf.merge(
+            &mut buffers,
+            document,
+            vec![vec![2; 6]],
+            &MergeOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(gltf.buffers.len(), 1);
+        assert_eq!(buffers[0].len(), 14);
+        let compressed = crate::prune::MeshoptData::get(&gltf.buffer_views[1]).unwrap();
+        assert_eq!(compressed.buffer, 0);
+        assert_eq!(compressed.byte_offset, 10);
+        assert_eq!(buffers[0][10..14], [2; 4]);
+        assert_eq!(gltf.buffer_views[1].byte_offset, 8);
+    }
+}
@@ -0,0 +1,854 @@
+//! Decoding for buffer views compressed with `EXT_meshopt_compression`.
+//!
+//! This is a port of the decoders from [meshoptimizer](https://github.com/zeux/meshoptimizer)
+//! as described by the extension's specification.
+
+use crate::extensions::ext_meshopt_compression::*;
+use crate::{BufferIndex, GlTf, GltfExtension, GltfProperty};
+
+#[derive(Debug)]
+pub enum MeshoptError {
+    /// The header byte doesn't match the expected codec.
+    IncorrectHeader,
+    /// The codec version is newer than this decoder supports.
+    UnsupportedVersion(u8),
+    /// The compressed data is truncated or malformed.
+    MalformedData,
+    /// The byte stride isn't valid for the mode or filter.
+    InvalidStride(usize),
+    /// The extension refers to a buffer that doesn't exist or is too short.
+    MissingBufferData(BufferIndex),
+}
+
+const VERTEX_HEADER: u8 = 0xa0;
+const INDEX_HEADER: u8 = 0xe0;
+const SEQUENCE_HEADER: u8 = 0xd0;
+
+const VERTEX_BLOCK_SIZE_BYTES: usize = 8192;
+const VERTEX_BLOCK_MAX_SIZE: usize = 256;
+const BYTE_GROUP_SIZE: usize = 16;
+const BYTE_GROUP_DECODE_LIMIT: usize = 24;
+const TAIL_MAX_SIZE: usize = 32;
+
+/// Decodes a buffer encoded with the `ATTRIBUTES` mode.
+pub fn decode_vertex_buffer(
+    count: usize,
+    stride: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, MeshoptError> {
+    if stride == 0 || stride > 256 || stride % 4 != 0 {
+        Err(MeshoptError::InvalidStride(stride))?
+    }
+
+    let header = *data.first().ok_or(MeshoptError::MalformedData)?;
+    if header & 0xf0 != VERTEX_HEADER {
+        Err(MeshoptError::IncorrectHeader)?
+    }
+    let version = header & 0x0f;
+    if version > 0 {
+        Err(MeshoptError::UnsupportedVersion(version))?
+    }
+
+    // The first vertex is stored at the end of the data, padded to at least `TAIL_MAX_SIZE`.
+    let tail_size = stride.max(TAIL_MAX_SIZE);
+    if data.len() < 1 + tail_size {
+        Err(MeshoptError::MalformedData)?
+    }
+    let mut last_vertex = data[data.len() - stride..].to_vec();
+
+    // Every 16 vertices take at least a header bit for each byte of the stride, so counts
+    // the data can't hold are rejected before the output is allocated.
+    let min_bits = count.div_ceil(BYTE_GROUP_SIZE).checked_mul(stride);
+    if min_bits.map_or(true, |bits| bits / 8 > data.len()) {
+        Err(MeshoptError::MalformedData)?
+    }
+    let size = count
+        .checked_mul(stride)
+        .ok_or(MeshoptError::MalformedData)?;
+
+    let mut block_size = (VERTEX_BLOCK_SIZE_BYTES / stride) & !(BYTE_GROUP_SIZE - 1);
+    block_size = block_size.min(VERTEX_BLOCK_MAX_SIZE);
+
+    let mut output = vec![0; size];
+    let mut position = 1;
+    let mut vertex_offset = 0;
+    while vertex_offset < count {
+        let block_count = block_size.min(count - vertex_offset);
+        position = decode_vertex_block(
+            data,
+            position,
+            &mut output[vertex_offset * stride..(vertex_offset + block_count) * stride],
+            block_count,
+            stride,
+            &mut last_vertex,
+        )?;
+        vertex_offset += block_count;
+    }
+
+    if data.len() - position != tail_size {
+        Err(MeshoptError::MalformedData)?
+    }
+    Ok(output)
+}
+
+fn decode_vertex_block(
+    data: &[u8],
+    mut position: usize,
+    output: &mut [u8],
+    count: usize,
+    stride: usize,
+    last_vertex: &mut [u8],
+) -> Result<usize, MeshoptError> {
+    let mut buffer = [0; VERTEX_BLOCK_MAX_SIZE];
+    let count_aligned = (count + BYTE_GROUP_SIZE - 1) & !(BYTE_GROUP_SIZE - 1);
+
+    // Each byte of the vertex is stored as a separate stream of deltas.
+    for k in 0..stride {
+        position = decode_bytes(data, position, &mut buffer[..count_aligned])?;
+
+        let mut previous = last_vertex[k];
+        for i in 0..count {
+            let value = unzigzag8(buffer[i]).wrapping_add(previous);
+            output[i * stride + k] = value;
+            previous = value;
+        }
+    }
+
+    last_vertex.copy_from_slice(&output[(count - 1) * stride..count * stride]);
+    Ok(position)
+}
+
+fn decode_bytes(data: &[u8], position: usize, buffer: &mut [u8]) -> Result<usize, MeshoptError> {
+    // Two bits per group, rounded up to a whole byte.
+    let header_size = (buffer.len() / BYTE_GROUP_SIZE).div_ceil(4);
+    if data.len() - position < header_size {
+        Err(MeshoptError::MalformedData)?
+    }
+    let header = &data[position..position + header_size];
+    let mut position = position + header_size;
+
+    for (group, buffer) in buffer.chunks_exact_mut(BYTE_GROUP_SIZE).enumerate() {
+        if data.len() - position < BYTE_GROUP_DECODE_LIMIT {
+            Err(MeshoptError::MalformedData)?
+        }
+        let bits_log2 = (header[group / 4] >> ((group % 4) * 2)) & 3;
+        position = decode_bytes_group(data, position, buffer, bits_log2);
+    }
+    Ok(position)
+}
+
+/// Decodes a group of 16 bytes stored with 0, 2, 4 or 8 bits each.
+/// Values that don't fit in the smaller sizes are stored in full after the packed bits.
+/// The caller ensures there are at least `BYTE_GROUP_DECODE_LIMIT` bytes available.
+fn decode_bytes_group(data: &[u8], position: usize, buffer: &mut [u8], bits_log2: u8) -> usize {
+    match bits_log2 {
+        0 => {
+            buffer.iter_mut().for_each(|b| *b = 0);
+            position
+        }
+        3 => {
+            buffer.copy_from_slice(&data[position..position + BYTE_GROUP_SIZE]);
+            position + BYTE_GROUP_SIZE
+        }
+        _ => {
+            let bits = 1 << bits_log2;
+            let packed_size = BYTE_GROUP_SIZE * bits / 8;
+            let escape = (1u8 << bits) - 1;
+            let mut variable = position + packed_size;
+            for (i, value) in buffer.iter_mut().enumerate() {
+                let byte = data[position + i * bits / 8];
+                let shift = 8 - bits - (i * bits) % 8;
+                let encoded = (byte >> shift) & escape;
+                if encoded == escape {
+                    *value = data[variable];
+                    variable += 1;
+                } else {
+                    *value = encoded;
+                }
+            }
+            variable
+        }
+    }
+}
+
+fn unzigzag8(v: u8) -> u8 {
+    (0u8.wrapping_sub(v & 1)) ^ (v >> 1)
+}
+
+fn write_index(output: &mut [u8], i: usize, index_size: usize, value: u32) {
+    if index_size == 2 {
+        output[i * 2..i * 2 + 2].copy_from_slice(&(value as u16).to_le_bytes());
+    } else {
+        output[i * 4..i * 4 + 4].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn decode_vbyte(data: &[u8], position: &mut usize) -> u32 {
+    let lead = data[*position];
+    *position += 1;
+    if lead < 128 {
+        return lead as u32;
+    }
+
+    // Up to 4 extra bytes. This always terminates, which is important for malformed data.
+    let mut result = (lead & 127) as u32;
+    let mut shift = 7;
+    for _ in 0..4 {
+        let group = data[*position];
+        *position += 1;
+        result |= ((group & 127) as u32) << shift;
+        shift += 7;
+        if group < 128 {
+            break;
+        }
+    }
+    result
+}
+
+fn decode_index(data: &[u8], position: &mut usize, last: u32) -> u32 {
+    let v = decode_vbyte(data, position);
+    let delta = (v >> 1) ^ 0u32.wrapping_sub(v & 1);
+    last.wrapping_add(delta)
+}
+
+/// Decodes a buffer encoded with the `TRIANGLES` mode.
+pub fn decode_index_buffer(
+    count: usize,
+    index_size: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, MeshoptError> {
+    if index_size != 2 && index_size != 4 {
+        Err(MeshoptError::InvalidStride(index_size))?
+    }
+    if count % 3 != 0 {
+        Err(MeshoptError::MalformedData)?
+    }
+
+    // The smallest valid encoding is the header, 1 byte per triangle, and a 16 byte table.
+    if data.len() < 1 + count / 3 + 16 {
+        Err(MeshoptError::MalformedData)?
+    }
+    if data[0] & 0xf0 != INDEX_HEADER {
+        Err(MeshoptError::IncorrectHeader)?
+    }
+    let version = data[0] & 0x0f;
+    if version > 1 {
+        Err(MeshoptError::UnsupportedVersion(version))?
+    }
+
+    let mut edge_fifo = [[u32::MAX; 2]; 16];
+    let mut vertex_fifo = [u32::MAX; 16];
+    let mut edge_fifo_offset = 0;
+    let mut vertex_fifo_offset = 0;
+
+    let mut next = 0u32;
+    let mut last = 0u32;
+    let fec_max = if version >= 1 { 13 } else { 15 };
+
+    let mut position = 1 + count / 3;
+    // Triangle data must end before the 16 byte table at the end.
+    let data_safe_end = data.len() - 16;
+    let code_aux_table = &data[data_safe_end..];
+
+    let mut output = vec![0; count * index_size];
+
+    // The fifos must be updated exactly as the encoder updated them.
+    let push_vertex = |fifo: &mut [u32; 16], offset: &mut usize, v: u32, condition: bool| {
+        fifo[*offset] = v;
+        *offset = (*offset + condition as usize) & 15;
+    };
+    let push_edge = |fifo: &mut [[u32; 2]; 16], offset: &mut usize, a: u32, b: u32| {
+        fifo[*offset] = [a, b];
+        *offset = (*offset + 1) & 15;
+    };
+
+    // The codes start after the header, one for each triangle.
+    for (code, i) in (1..).zip((0..count).step_by(3)) {
+        // Each triangle reads at most 16 bytes: 1 for the aux code and 5 for each free index.
+        if position > data_safe_end {
+            Err(MeshoptError::MalformedData)?
+        }
+
+        let code_tri = data[code];
+
+        let (a, b, c) = if code_tri < 0xf0 {
+            let fe = (code_tri >> 4) as usize;
+            let [a, b] = edge_fifo[(edge_fifo_offset + 15 - fe) & 15];
+            let fec = (code_tri & 15) as u32;
+
+            if fec < fec_max {
+                let c = if fec == 0 {
+                    next
+                } else {
+                    vertex_fifo[(vertex_fifo_offset + 15 - fec as usize) & 15]
+                };
+                let fec0 = fec == 0;
+                next += fec0 as u32;
+
+                push_vertex(&mut vertex_fifo, &mut vertex_fifo_offset, c, fec0);
+                push_edge(&mut edge_fifo, &mut edge_fifo_offset, c, b);
+                push_edge(&mut edge_fifo, &mut edge_fifo_offset, a, c);
+                (a, b, c)
+            } else {
+                // 13 and 14 encode a delta of -1 and 1 from the last free index.
+                let c = if fec != 15 {
+                    last.wrapping_add(fec.wrapping_sub(fec ^ 3))
+                } else {
+                    decode_index(data, &mut position, last)
+                };
+                last = c;
+
+                push_vertex(&mut vertex_fifo, &mut vertex_fifo_offset, c, true);
+                push_edge(&mut edge_fifo, &mut edge_fifo_offset, c, b);
+                push_edge(&mut edge_fifo, &mut edge_fifo_offset, a, c);
+                (a, b, c)
+            }
+        } else if code_tri < 0xfe {
+            let code_aux = code_aux_table[(code_tri & 15) as usize];
+            let feb = (code_aux >> 4) as usize;
+            let fec = (code_aux & 15) as usize;
+
+            // `next` is incremented for all three vertices before decoding, matching the encoder.
+            let a = next;
+            next += 1;
+
+            let b = if feb == 0 {
+                next
+            } else {
+                vertex_fifo[(vertex_fifo_offset + 16 - feb) & 15]
+            };
+            next += (feb == 0) as u32;
+
+            let c = if fec == 0 {
+                next
+            } else {
+                vertex_fifo[(vertex_fifo_offset + 16 - fec) & 15]
+            };
+            next += (fec == 0) as u32;
+
+            push_vertex(&mut vertex_fifo, &mut vertex_fifo_offset, a, true);
+            push_vertex(&mut vertex_fifo, &mut vertex_fifo_offset, b, feb == 0);
+            push_vertex(&mut vertex_fifo, &mut vertex_fifo_offset, c, fec == 0);
+            push_edge(&mut edge_fifo, &mut edge_fifo_offset, b, a);
+            push_edge(&mut edge_fifo, &mut edge_fifo_offset, c, b);
+            push_edge(&mut edge_fifo, &mut edge_fifo_offset, a, c);
+            (a, b, c)
+        } else {
+            // The aux code is stored in full instead of in the table.
+            let code_aux = data[position];
+            position += 1;
+
+            let fea = if code_tri == 0xfe { 0 } else { 15 };
+            let feb = (code_aux >> 4) as usize;
+            let fec = (code_aux & 15) as usize;
+
+            // A reset is encoded as a zero aux code that isn't in the table.
+            if code_aux == 0 {
+                next = 0;
+            }
+
+            let mut a = 0;
+            if fea == 0 {
+                a = next;
+                next += 1;
+            }
+            let mut b = if feb == 0 {
+                next += 1;
+                next - 1
+            } else {
+                vertex_fifo[(vertex_fifo_offset + 16 - feb) & 15]
+            };
+            let mut c = if fec == 0 {
+                next += 1;
+                next - 1
+            } else {
+                vertex_fifo[(vertex_fifo_offset + 16 - fec) & 15]
+            };
+
+            if fea == 15 {
+                a = decode_index(data, &mut position, last);
+                last = a;
+            }
+            if feb == 15 {
+                b = decode_index(data, &mut position, last);
+                last = b;
+            }
+            if fec == 15 {
+                c = decode_index(data, &mut position, last);
+                last = c;
+            }
+
+            push_vertex(&mut vertex_fifo, &mut vertex_fifo_offset, a, true);
+            push_vertex(
+                &mut vertex_fifo,
+                &mut vertex_fifo_offset,
+                b,
+                feb == 0 || feb == 15,
+            );
+            push_vertex(
+                &mut vertex_fifo,
+                &mut vertex_fifo_offset,
+                c,
+                fec == 0 || fec == 15,
+            );
+            push_edge(&mut edge_fifo, &mut edge_fifo_offset, b, a);
+            push_edge(&mut edge_fifo, &mut edge_fifo_offset, c, b);
+            push_edge(&mut edge_fifo, &mut edge_fifo_offset, a, c);
+            (a, b, c)
+        };
+
+        write_index(&mut output, i, index_size, a);
+        write_index(&mut output, i + 1, index_size, b);
+        write_index(&mut output, i + 2, index_size, c);
+    }
+
+    // All of the data should be read, stopping at the table.
+    if position != data_safe_end {
+        Err(MeshoptError::MalformedData)?
+    }
+    Ok(output)
+}
+
+/// Decodes a buffer encoded with the `INDICES` mode.
+pub fn decode_index_sequence(
+    count: usize,
+    index_size: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, MeshoptError> {
+    if index_size != 2 && index_size != 4 {
+        Err(MeshoptError::InvalidStride(index_size))?
+    }
+
+    // The smallest valid encoding is the header, 1 byte per index, and a 4 byte tail.
+    if data.len() < 1 + count + 4 {
+        Err(MeshoptError::MalformedData)?
+    }
+    if data[0] & 0xf0 != SEQUENCE_HEADER {
+        Err(MeshoptError::IncorrectHeader)?
+    }
+    let version = data[0] & 0x0f;
+    if version > 1 {
+        Err(MeshoptError::UnsupportedVersion(version))?
+    }
+
+    let data_safe_end = data.len() - 4;
+    let mut position = 1;
+    let mut last = [0u32; 2];
+    let mut output = vec![0; count * index_size];
+
+    for i in 0..count {
+        // Each index reads at most 5 bytes, which the tail leaves room for.
+        if position >= data_safe_end {
+            Err(MeshoptError::MalformedData)?
+        }
+        let mut v = decode_vbyte(data, &mut position);
+
+        // Each index is a delta from one of two previous indices.
+        let current = (v & 1) as usize;
+        v >>= 1;
+        let delta = (v >> 1) ^ 0u32.wrapping_sub(v & 1);
+        let index = last[current].wrapping_add(delta);
+        last[current] = index;
+
+        write_index(&mut output, i, index_size, index);
+    }
+
+    if position != data_safe_end {
+        Err(MeshoptError::MalformedData)?
+    }
+    Ok(output)
+}
+
+/// Decodes normals or tangents stored as octahedral encoded 8 or 16 bit signed integers in place.
+pub fn decode_filter_octahedral(data: &mut [u8], count: usize, stride: usize) {
+    match stride {
+        4 => {
+            for v in data[..count * 4].chunks_exact_mut(4) {
+                let [x, y, z] = decode_octahedral(
+                    [v[0] as i8 as f32, v[1] as i8 as f32, v[2] as i8 as f32],
+                    127.0,
+                );
+                v[0] = x as i8 as u8;
+                v[1] = y as i8 as u8;
+                v[2] = z as i8 as u8;
+            }
+        }
+        _ => {
+            for v in data[..count * 8].chunks_exact_mut(8) {
+                let read = |i: usize| i16::from_le_bytes([v[i * 2], v[i * 2 + 1]]) as f32;
+                let [x, y, z] = decode_octahedral([read(0), read(1), read(2)], 32767.0);
+                v[0..2].copy_from_slice(&(x as i16).to_le_bytes());
+                v[2..4].copy_from_slice(&(y as i16).to_le_bytes());
+                v[4..6].copy_from_slice(&(z as i16).to_le_bytes());
+            }
+        }
+    }
+}
+
+fn decode_octahedral([mut x, mut y, z]: [f32; 3], max: f32) -> [i32; 3] {
+    // The z component stores the value that encodes 1.0 at the same bit count.
+    let z = z - x.abs() - y.abs();
+
+    // Fix up the coordinates for z < 0
+    let t = z.min(0.0);
+    x += if x >= 0.0 { t } else { -t };
+    y += if y >= 0.0 { t } else { -t };
+
+    let scale = max / (x * x + y * y + z * z).sqrt();
+    [round(x * scale), round(y * scale), round(z * scale)]
+}
+
+/// Rounds a signed float to the nearest integer, away from zero.
+fn round(v: f32) -> i32 {
+    (v + if v >= 0.0 { 0.5 } else { -0.5 }) as i32
+}
+
+/// Decodes rotations stored as 16 bit quaternions with the largest component omitted in place.
+pub fn decode_filter_quaternion(data: &mut [u8], count: usize) {
+    let scale = 1.0 / 2.0f32.sqrt();
+    for v in data[..count * 8].chunks_exact_mut(8) {
+        let read = |i: usize| i16::from_le_bytes([v[i * 2], v[i * 2 + 1]]);
+
+        // The scale is stored in the high bits of the last component.
+        let last = read(3);
+        let s = scale / (last | 3) as f32;
+        let x = read(0) as f32 * s;
+        let y = read(1) as f32 * s;
+        let z = read(2) as f32 * s;
+
+        // Clamp to avoid NaNs due to precision errors.
+        let w = (1.0 - x * x - y * y - z * z).max(0.0).sqrt();
+
+        // The index of the omitted component is stored in the low bits of the last component.
+        let qc = (last & 3) as usize;
+        let values = [
+            (w * 32767.0 + 0.5) as i32,
+            round(x * 32767.0),
+            round(y * 32767.0),
+            round(z * 32767.0),
+        ];
+        for (i, value) in values.iter().enumerate() {
+            let j = (qc + i) & 3;
+            v[j * 2..j * 2 + 2].copy_from_slice(&(*value as i16).to_le_bytes());
+        }
+    }
+}
+
+/// Decodes floats stored as a 24 bit mantissa and 8 bit exponent in place.
+pub fn decode_filter_exponential(data: &mut [u8], count: usize, stride: usize) {
+    for v in data[..count * stride].chunks_exact_mut(4) {
+        let bits = u32::from_le_bytes([v[0], v[1], v[2], v[3]]);
+        let mantissa = ((bits << 8) as i32) >> 8;
+        let exponent = (bits as i32) >> 24;
+        let value = f32::from_bits(((exponent + 127) as u32) << 23) * mantissa as f32;
+        v.copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Decodes a single compressed buffer view.
+pub fn decode_buffer_view(
+    extension: &ExtMeshoptCompressionBufferView,
+    data: &[u8],
+) -> Result<Vec<u8>, MeshoptError> {
+    let count = extension.count;
+    let stride = extension.byte_stride;
+    let mut output = match extension.mode {
+        ExtMeshoptCompressionBufferViewMode::Attributes => {
+            decode_vertex_buffer(count, stride, data)?
+        }
+        ExtMeshoptCompressionBufferViewMode::Triangles => decode_index_buffer(count, stride, data)?,
+        ExtMeshoptCompressionBufferViewMode::Indices => decode_index_sequence(count, stride, data)?,
+    };
+
+    match extension.filter {
+        ExtMeshoptCompressionBufferViewFilter::None => {}
+        ExtMeshoptCompressionBufferViewFilter::Octahedral => {
+            if stride != 4 && stride != 8 {
+                Err(MeshoptError::InvalidStride(stride))?
+            }
+            decode_filter_octahedral(&mut output, count, stride)
+        }
+        ExtMeshoptCompressionBufferViewFilter::Quaternion => {
+            if stride != 8 {
+                Err(MeshoptError::InvalidStride(stride))?
+            }
+            decode_filter_quaternion(&mut output, count)
+        }
+        ExtMeshoptCompressionBufferViewFilter::Exponential => {
+            if stride % 4 != 0 {
+                Err(MeshoptError::InvalidStride(stride))?
+            }
+            decode_filter_exponential(&mut output, count, stride)
+        }
+    }
+    Ok(output)
+}
+
+impl GlTf {
+    /// Decodes every buffer view compressed with `EXT_meshopt_compression`.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers.
+    /// The decoded data is written into each buffer view's own (fallback) buffer,
+    /// which is allocated if it wasn't loaded, so accessors can be read as if the
+    /// glTF was never compressed. The extension is removed afterwards.
+    pub fn decode_meshopt(&mut self, buffers: &mut [Vec<u8>]) -> Result<(), MeshoptError> {
+        for i in 0..self.buffer_views.len() {
+            let extension =
+                match self.buffer_views[i].extension::<ExtMeshoptCompressionBufferView>() {
+                    Some(extension) => extension,
+                    None => continue,
+                };
+
+            let source = extension
+                .byte_offset
+                .checked_add(extension.byte_length)
+                .and_then(|end| {
+                    buffers
+                        .get(extension.buffer.value())?
+                        .get(extension.byte_offset..end)
+                })
+                .ok_or(MeshoptError::MissingBufferData(extension.buffer))?;
+            let decoded = decode_buffer_view(&extension, source)?;
+
+            let view = &mut self.buffer_views[i];
+            let buffer_length = self
+                .buffers
+                .get(view.buffer.value())
+                .ok_or(MeshoptError::MissingBufferData(view.buffer))?
+                .byte_length;
+            let destination = buffers
+                .get_mut(view.buffer.value())
+                .ok_or(MeshoptError::MissingBufferData(view.buffer))?;
+            if destination.len() < buffer_length {
+                destination.resize(buffer_length, 0);
+            }
+
+            let length = view.byte_length.min(decoded.len());
+            view.byte_offset
+                .checked_add(length)
+                .and_then(|end| destination.get_mut(view.byte_offset..end))
+                .ok_or(MeshoptError::MissingBufferData(view.buffer))?
+                .copy_from_slice(&decoded[..length]);
+            view.remove_extension::<ExtMeshoptCompressionBufferView>();
+        }
+
+        for buffer in self.buffers.iter_mut() {
+            buffer.remove_extension::<ExtMeshoptCompressionBuffer>();
+        }
+        self.remove_extension_used(ExtMeshoptCompressionBufferView::NAME);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Encoded with meshoptimizer 0.12's `meshopt_encodeVertexBuffer` and
+    // `meshopt_encodeIndexBuffer`.
+    const ENCODED_VERTICES_4: [u8; 93] = [
+        160, 5, 42, 170, 170, 170, 170, 170, 0, 0, 10, 4, 68, 68, 68, 68, 68, 68, 68, 68, 68, 68,
+        68, 0, 0, 0, 0, 10, 6, 102, 102, 102, 102, 102, 102, 102, 102, 102, 102, 102, 0, 0, 0, 0,
+        10, 10, 228, 140, 38, 174, 72, 194, 106, 228, 140, 38, 174, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 6, 9,
+    ];
+    const ENCODED_VERTICES_12: [u8; 213] = [
+        160, 5, 42, 170, 170, 170, 170, 0, 0, 0, 6, 4, 68, 68, 68, 68, 68, 68, 68, 255, 0, 0, 0, 4,
+        4, 4, 4, 6, 6, 102, 102, 102, 102, 102, 102, 102, 255, 0, 0, 0, 6, 6, 6, 6, 6, 10, 228,
+        140, 38, 174, 72, 194, 106, 255, 0, 0, 0, 14, 4, 8, 12, 5, 42, 170, 170, 170, 170, 0, 0, 0,
+        6, 4, 68, 68, 68, 68, 68, 68, 68, 255, 0, 0, 0, 4, 4, 4, 4, 6, 6, 102, 102, 102, 102, 102,
+        102, 102, 255, 0, 0, 0, 6, 6, 6, 6, 6, 10, 228, 140, 38, 174, 72, 194, 106, 255, 0, 0, 0,
+        14, 4, 8, 12, 5, 42, 170, 170, 170, 170, 0, 0, 0, 6, 4, 68, 68, 68, 68, 68, 68, 68, 255, 0,
+        0, 0, 4, 4, 4, 4, 6, 6, 102, 102, 102, 102, 102, 102, 102, 255, 0, 0, 0, 6, 6, 6, 6, 6, 10,
+        228, 140, 38, 174, 72, 194, 106, 255, 0, 0, 0, 14, 4, 8, 12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 6, 9, 12, 15, 18, 21, 24, 27, 30, 33,
+    ];
+    const ENCODED_VERTICES_16: [u8; 273] = [
+        160, 5, 42, 170, 170, 170, 170, 0, 0, 0, 6, 4, 68, 68, 68, 68, 68, 68, 68, 255, 0, 0, 0, 4,
+        4, 4, 4, 6, 6, 102, 102, 102, 102, 102, 102, 102, 255, 0, 0, 0, 6, 6, 6, 6, 6, 10, 228,
+        140, 38, 174, 72, 194, 106, 255, 0, 0, 0, 14, 4, 8, 12, 5, 42, 170, 170, 170, 170, 0, 0, 0,
+        6, 4, 68, 68, 68, 68, 68, 68, 68, 255, 0, 0, 0, 4, 4, 4, 4, 6, 6, 102, 102, 102, 102, 102,
+        102, 102, 255, 0, 0, 0, 6, 6, 6, 6, 6, 10, 228, 140, 38, 174, 72, 194, 106, 255, 0, 0, 0,
+        14, 4, 8, 12, 5, 42, 170, 170, 170, 170, 0, 0, 0, 6, 4, 68, 68, 68, 68, 68, 68, 68, 255, 0,
+        0, 0, 4, 4, 4, 4, 6, 6, 102, 102, 102, 102, 102, 102, 102, 255, 0, 0, 0, 6, 6, 6, 6, 6, 10,
+        228, 140, 38, 174, 72, 194, 106, 255, 0, 0, 0, 14, 4, 8, 12, 5, 42, 170, 170, 170, 170, 0,
+        0, 0, 6, 4, 68, 68, 68, 68, 68, 68, 68, 255, 0, 0, 0, 4, 4, 4, 4, 6, 6, 102, 102, 102, 102,
+        102, 102, 102, 255, 0, 0, 0, 6, 6, 6, 6, 6, 10, 228, 140, 38, 174, 72, 194, 106, 255, 0, 0,
+        0, 14, 4, 8, 12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 6, 9, 12, 15, 18,
+        21, 24, 27, 30, 33, 36, 39, 42, 45,
+    ];
+
+    // A grid, whose triangles share edges with recent ones.
+    const GRID_INDICES: [u32; 54] = [
+        0, 1, 4, 1, 5, 4, 1, 2, 5, 2, 6, 5, 2, 3, 6, 3, 7, 6, 4, 5, 8, 5, 9, 8, 5, 6, 9, 6, 10, 9,
+        6, 7, 10, 7, 11, 10, 8, 9, 12, 9, 13, 12, 9, 10, 13, 10, 14, 13, 10, 11, 14, 11, 15, 14,
+    ];
+    const ENCODED_GRID_INDICES: [u8; 48] = [
+        224, 254, 31, 16, 15, 16, 15, 143, 31, 20, 15, 19, 15, 143, 31, 19, 15, 19, 15, 15, 8, 2,
+        2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 0, 118, 135, 86, 103, 120, 169, 134, 101, 137, 104, 152, 1,
+        105, 0, 0,
+    ];
+
+    // Triangles that share no vertices, so each index is stored on its own.
+    const SCATTERED_INDICES: [u32; 24] = [
+        14746, 7985, 28168, 20440, 41425, 44940, 64072, 63376, 30801, 49264, 4858, 59924, 21393,
+        51739, 10602, 32841, 48264, 56975, 17201, 39895, 42515, 17142, 33438, 35258,
+    ];
+    const ENCODED_SCATTERED_INDICES: [u8; 99] = [
+        224, 255, 255, 255, 255, 255, 255, 255, 255, 255, 180, 230, 1, 209, 105, 174, 187, 2, 255,
+        223, 120, 242, 199, 2, 246, 54, 255, 248, 170, 2, 239, 10, 253, 252, 3, 255, 190, 160, 2,
+        235, 181, 5, 180, 220, 6, 255, 133, 218, 4, 148, 218, 3, 225, 130, 5, 255, 190, 219, 2,
+        254, 240, 1, 142, 136, 1, 255, 187, 237, 4, 204, 226, 2, 248, 40, 255, 185, 140, 3, 208,
+        254, 1, 184, 28, 0, 118, 135, 86, 103, 120, 169, 134, 101, 137, 104, 152, 1, 105, 0, 0,
+    ];
+
+    /// The vertices that were encoded, which vary slowly in every byte but the last of each four.
+    fn vertices(count: usize, stride: usize) -> Vec<u8> {
+        let mut vertices = Vec::with_capacity(count * stride);
+        for i in 0..count {
+            for k in 0..stride {
+                let noise = if k % 4 == 3 { (i * i) % 7 } else { 0 };
+                vertices.push((i * (k % 4 + 1) + k * 3 + noise) as u8);
+            }
+        }
+        vertices
+    }
+
+    fn to_u32(bytes: &[u8]) -> Vec<u32> {
+        bytes
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()
+    }
+
+    fn to_u16(bytes: &[u8]) -> Vec<u16> {
+        bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect()
+    }
+
+    /// The triangles, each starting with its smallest index, as the encoder can rotate them.
+    fn triangles(indices: &[u32]) -> Vec<[u32; 3]> {
+        indices
+            .chunks_exact(3)
+            .map(|t| {
+                let first = (0..3).min_by_key(|&i| t[i]).unwrap();
+                [0, 1, 2].map(|i| t[(first + i) % 3])
+            })
+            .collect()
+    }
+
+    fn from_u16(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn vertex_buffers() {
+        for (stride, count, encoded) in [
+            (4, 24, &ENCODED_VERTICES_4[..]),
+            (12, 20, &ENCODED_VERTICES_12[..]),
+            (16, 20, &ENCODED_VERTICES_16[..]),
+        ] {
+            let decoded = decode_vertex_buffer(count, stride, encoded).unwrap();
+            assert_eq!(decoded, vertices(count, stride), "stride {}", stride);
+        }
+    }
+
+    #[test]
+    fn vertex_buffer_errors() {
+        assert!(decode_vertex_buffer(24, 6, &ENCODED_VERTICES_4).is_err());
+        let truncated = &ENCODED_VERTICES_4[..ENCODED_VERTICES_4.len() - 1];
+        assert!(decode_vertex_buffer(24, 4, truncated).is_err());
+        // Counts the data can't hold are rejected before anything is allocated.
+        for count in [usize::MAX / 4, usize::MAX] {
+            assert!(matches!(
+                decode_vertex_buffer(count, 4, &ENCODED_VERTICES_4),
+                Err(MeshoptError::MalformedData)
+            ));
+        }
+    }
+
+    #[test]
+    fn index_buffers() {
+        let grid = decode_index_buffer(GRID_INDICES.len(), 4, &ENCODED_GRID_INDICES).unwrap();
+        assert_eq!(triangles(&to_u32(&grid)), triangles(&GRID_INDICES));
+        let grid = decode_index_buffer(GRID_INDICES.len(), 2, &ENCODED_GRID_INDICES).unwrap();
+        let grid: Vec<u32> = to_u16(&grid).into_iter().map(u32::from).collect();
+        assert_eq!(triangles(&grid), triangles(&GRID_INDICES));
+
+        let count = SCATTERED_INDICES.len();
+        let scattered = decode_index_buffer(count, 4, &ENCODED_SCATTERED_INDICES).unwrap();
+        assert_eq!(
+            triangles(&to_u32(&scattered)),
+            triangles(&SCATTERED_INDICES)
+        );
+    }
+
+    // The vectors below are from meshoptimizer's tests.
+
+    #[test]
+    fn index_buffer_version_1() {
+        let encoded = [
+            0xe1, 0xf0, 0x10, 0xfe, 0x1f, 0x3d, 0x00, 0x0a, 0x00, 0x76, 0x87, 0x56, 0x67, 0x78,
+            0xa9, 0x86, 0x65, 0x89, 0x68, 0x98, 0x01, 0x69, 0x00, 0x00,
+        ];
+        let decoded = decode_index_buffer(15, 4, &encoded).unwrap();
+        assert_eq!(
+            to_u32(&decoded),
+            [0, 1, 2, 2, 1, 3, 0, 1, 2, 2, 1, 5, 2, 1, 4]
+        );
+    }
+
+    #[test]
+    fn index_sequence() {
+        let encoded = [
+            0xd1, 0x00, 0x04, 0xcd, 0x01, 0x04, 0x07, 0x98, 0x1f, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let decoded = decode_index_sequence(6, 4, &encoded).unwrap();
+        assert_eq!(to_u32(&decoded), [0, 1, 51, 2, 49, 1000]);
+    }
+
+    #[test]
+    fn octahedral_filter() {
+        let mut data = [
+            0, 1, 127, 0, 0, 187, 127, 1, 255, 1, 127, 0, 14, 130, 127, 1,
+        ];
+        decode_filter_octahedral(&mut data, 4, 4);
+        assert_eq!(
+            data,
+            [0, 1, 127, 0, 0, 159, 82, 1, 255, 1, 127, 0, 1, 130, 241, 1]
+        );
+
+        let mut data = from_u16(&[
+            0, 1, 2047, 0, 0, 1870, 2047, 1, 2017, 1, 2047, 0, 14, 1300, 2047, 1,
+        ]);
+        decode_filter_octahedral(&mut data, 4, 8);
+        assert_eq!(
+            to_u16(&data),
+            [0, 16, 32767, 0, 0, 32621, 3088, 1, 32764, 16, 471, 0, 307, 28541, 16093, 1]
+        );
+    }
+
+    #[test]
+    fn quaternion_filter() {
+        let mut data = from_u16(&[
+            0, 1, 0, 0x7fc, 0, 1870, 0, 0x7fd, 2017, 1, 0, 0x7fe, 14, 1300, 0, 0x7ff,
+        ]);
+        decode_filter_quaternion(&mut data, 4);
+        assert_eq!(
+            to_u16(&data),
+            [32767, 0, 11, 0, 0, 25013, 0, 21166, 11, 0, 23504, 22830, 158, 14715, 0, 29277]
+        );
+    }
+
+    #[test]
+    fn exponential_filter() {
+        let mut data: Vec<u8> = [0u32, 0xff000003, 0x02fffff7, 0xfe7fffff]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        decode_filter_exponential(&mut data, 4, 4);
+        assert_eq!(to_u32(&data), [0, 0x3fc00000, 0xc2100000, 0x49fffffe]);
+    }
+}
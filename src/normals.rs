@@ -0,0 +1,275 @@
+//! Generating normals for primitives without them.
+//!
+//! The specification says clients must compute flat normals when `NORMAL` is missing.
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalKind {
+    /// Each triangle has its own normal, so shared vertices are split.
+    Flat,
+    /// Each vertex has the average of the normals of the triangles that use it,
+    /// weighted by the angle of each triangle at the vertex.
+    Smooth,
+}
+
+/// The normal of a triangle, or `[0, 0, 1]` if it has no area.
+fn triangle_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let normal = normalize(cross(subtract(b, a), subtract(c, a)));
+    if normal == [0.0; 3] {
+        [0.0, 0.0, 1.0]
+    } else {
+        normal
+    }
+}
+
+/// The angle between two edges that start at the same vertex.
+fn angle(a: [f32; 3], b: [f32; 3]) -> f32 {
+    dot(normalize(a), normalize(b)).clamp(-1.0, 1.0).acos()
+}
+
+/// Returns [AccessorError::Compressed] if the primitive's vertices are compressed,
+/// as its accessors would be read as zeros.
+pub(crate) fn check_uncompressed(primitive: &MeshPrimitive) -> Result<(), AccessorError> {
+    if primitive
+        .extensions
+        .contains_key("KHR_draco_mesh_compression")
+    {
+        Err(AccessorError::Compressed)?
+    }
+    Ok(())
+}
+
+impl GlTf {
+    /// Reads an attribute of three component vectors, such as `POSITION` or `NORMAL`.
+    pub(crate) fn read_vec3_attribute(
+        &self,
+        primitive: &MeshPrimitive,
+        semantic: Semantic,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<[f32; 3]>, AccessorError> {
+        let accessor = *primitive
+            .attributes
+            .get(&semantic)
+            .ok_or(AccessorError::MissingAttribute(semantic))?;
+        Ok(self
+            .read_accessor_f32(accessor, buffers)?
+            .chunks_exact(3)
+            .map(|v| [v[0], v[1], v[2]])
+            .collect())
+    }
+
+    /// Computes a normal for each corner of each triangle from [GlTf::read_triangles],
+    /// which is the normal of the triangle.
+    pub fn flat_normals(
+        &self,
+        primitive: &MeshPrimitive,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<[f32; 3]>, AccessorError> {
+        check_uncompressed(primitive)?;
+        let positions = self.read_vec3_attribute(primitive, Semantic::Position, buffers)?;
+        let triangles = self.read_triangles(primitive, buffers)?;
+        let mut normals = Vec::with_capacity(triangles.len() * 3);
+        for [a, b, c] in triangles {
+            let p = |v: u32| positions[v as usize];
+            let normal = triangle_normal(p(a), p(b), p(c));
+            normals.extend_from_slice(&[normal; 3]);
+        }
+        Ok(normals)
+    }
+
+    /// Computes a normal for each vertex from the triangles that use it, weighted by the angle
+    /// of each triangle at the vertex so the result doesn't depend on how faces are split up.
+    ///
+    /// Vertices that aren't part of any triangle have the normal `[0, 0, 1]`. Points, lines and
+    /// primitives without triangles have no normals to average, which returns
+    /// [AccessorError::NoTriangles].
+    pub fn smooth_normals(
+        &self,
+        primitive: &MeshPrimitive,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<[f32; 3]>, AccessorError> {
+        check_uncompressed(primitive)?;
+        let positions = self.read_vec3_attribute(primitive, Semantic::Position, buffers)?;
+        let triangles = self.read_triangles(primitive, buffers)?;
+        if triangles.is_empty() {
+            Err(AccessorError::NoTriangles)?
+        }
+        let mut normals = vec![[0.0; 3]; positions.len()];
+        for triangle in triangles {
+            let p = triangle.map(|v| positions[v as usize]);
+            let normal = triangle_normal(p[0], p[1], p[2]);
+            for corner in 0..3 {
+                let [previous, current, next] =
+                    [p[(corner + 2) % 3], p[corner], p[(corner + 1) % 3]];
+                let weight = angle(subtract(next, current), subtract(previous, current));
+                let sum = &mut normals[triangle[corner] as usize];
+                for i in 0..3 {
+                    sum[i] += normal[i] * weight;
+                }
+            }
+        }
+        for normal in &mut normals {
+            *normal = normalize(*normal);
+            if *normal == [0.0; 3] {
+                *normal = [0.0, 0.0, 1.0];
+            }
+        }
+        Ok(normals)
+    }
+
+    /// Adds a `NORMAL` attribute to a primitive, replacing any it had,
+    /// and returns the index of its accessor.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers, the new accessors are appended
+    /// to the first one. Flat normals need a vertex for each corner of each triangle,
+    /// so the primitive's attributes are copied for each corner and it becomes a list of
+    /// triangles without indices. Normals can't be added to points, lines or primitives
+    /// without triangles, which returns [AccessorError::NoTriangles] without changing them,
+    /// or to primitives with `KHR_draco_mesh_compression`, which returns
+    /// [AccessorError::Compressed].
+    pub fn add_normals(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mesh: MeshIndex,
+        primitive: usize,
+        kind: NormalKind,
+    ) -> Result<AccessorIndex, AccessorError> {
+        let normals = match kind {
+            NormalKind::Flat => {
+                let original = &self.meshes[mesh.value()].primitives[primitive];
+                let normals = self.flat_normals(original, buffers)?;
+                let corners: Vec<u32> = self.read_triangles(original, buffers)?.concat();
+                if corners.is_empty() {
+                    Err(AccessorError::NoTriangles)?
+                }
+                self.copy_vertices(buffers, mesh, primitive, &corners, None)?;
+                normals
+            }
+            NormalKind::Smooth => {
+                self.smooth_normals(&self.meshes[mesh.value()].primitives[primitive], buffers)?
+            }
+        };
+        let accessor = self.push_accessor(
+            buffers,
+            &normals.concat(),
+            AccessorType::Vec3,
+            AccessorComponentType::Float,
+            false,
+            Some(BufferViewTarget::ArrayBuffer),
+        );
+        self.meshes[mesh.value()].primitives[primitive]
+            .attributes
+            .insert(Semantic::Normal, accessor);
+        Ok(accessor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+
+    #[test]
+    fn smooth_normals_are_weighted_by_angle() {
+        // Two triangles share the first vertex, where the first has a right angle
+        // and the second, which is twice as large, a narrow one.
+        let mut builder = GlTfBuilder::new();
+        let positions = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 4.0, 2.0],
+        ];
+        let positions = builder.add_vertex_attribute(&positions.concat(), AccessorType::Vec3);
+        let indices = builder.add_indices(&[0, 1, 2, 0, 2, 3]);
+        let primitive =
+            GlTfBuilder::primitive(&[(Semantic::Position, positions)], Some(indices), None);
+        let mesh = builder.add_mesh(None, vec![primitive]);
+        let (mut gltf, buffer) = builder.finish();
+        let mut buffers = vec![buffer];
+        let accessor = gltf
+            .add_normals(&mut buffers, mesh, 0, NormalKind::Smooth)
+            .unwrap();
+        let normals = gltf.read_accessor_f32(accessor, &buffers).unwrap();
+
+        // The normals are +Z and +X, and the second triangle's angle is atan(1 / 2).
+        // Weighting by area gives [0.894, 0, 0.447] and not weighting [0.707, 0, 0.707].
+        let shared = normalize([0.5f32.atan(), 0.0, std::f32::consts::FRAC_PI_2]);
+        let expected = [shared, [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]];
+        assert_close(
+            &[0, 1, 3].map(|v| [0, 1, 2].map(|i| normals[v * 3 + i])),
+            &expected,
+            1e-5,
+        );
+    }
+
+    #[test]
+    fn smooth_normals_need_triangles() {
+        let (mut gltf, buffers) = cube();
+        gltf.meshes[0].primitives[0].mode = MeshPrimitiveMode::Points;
+        let result = gltf.smooth_normals(&gltf.meshes[0].primitives[0], &buffers);
+        assert!(matches!(result, Err(AccessorError::NoTriangles)));
+    }
+
+    #[test]
+    fn normals_refuse_draco() {
+        let (mut gltf, mut buffers) = cube();
+        gltf.meshes[0].primitives[0].extensions.insert(
+            "KHR_draco_mesh_compression".to_string(),
+            kserde::ThingOwned::Null,
+        );
+        let json = gltf.to_json_formatted(None);
+        for kind in [NormalKind::Flat, NormalKind::Smooth] {
+            let result = gltf.add_normals(&mut buffers, MeshIndex::new(0), 0, kind);
+            assert!(matches!(result, Err(AccessorError::Compressed)));
+        }
+        assert_eq!(gltf.to_json_formatted(None), json);
+    }
+
+    #[test]
+    fn flat_normals_need_triangles() {
+        let (mut gltf, mut buffers) = cube();
+        let mesh = MeshIndex::new(0);
+        let smooth = gltf
+            .smooth_normals(&gltf.meshes[0].primitives[0], &buffers)
+            .unwrap();
+        let positions = world_positions(&gltf, &buffers);
+
+        gltf.meshes[0].primitives[0].mode = MeshPrimitiveMode::Lines;
+        let accessors = gltf.accessors.len();
+        let result = gltf.add_normals(&mut buffers, mesh, 0, NormalKind::Flat);
+        assert!(matches!(result, Err(AccessorError::NoTriangles)));
+        assert_eq!(gltf.accessors.len(), accessors);
+        assert!(matches!(
+            gltf.meshes[0].primitives[0].mode,
+            MeshPrimitiveMode::Lines
+        ));
+
+        // Each corner of each triangle gets its own vertex, facing the way the cube's
+        // smooth normals face at the middle of each side.
+        gltf.meshes[0].primitives[0].mode = MeshPrimitiveMode::Triangles;
+        let triangles = gltf
+            .read_triangles(&gltf.meshes[0].primitives[0], &buffers)
+            .unwrap();
+        gltf.add_normals(&mut buffers, mesh, 0, NormalKind::Flat)
+            .unwrap();
+        let primitive = &gltf.meshes[0].primitives[0];
+        let normals = gltf
+            .read_vec3_attribute(primitive, Semantic::Normal, &buffers)
+            .unwrap();
+        assert_eq!(normals.len(), triangles.len() * 3);
+        let corners: Vec<[f32; 3]> = triangles
+            .iter()
+            .flatten()
+            .map(|&v| smooth[v as usize])
+            .collect();
+        assert_close(&normals, &corners, 1e-5);
+        let corner_positions: Vec<[f32; 3]> = triangles
+            .iter()
+            .flatten()
+            .map(|&v| positions[v as usize])
+            .collect();
+        assert_close(&world_positions(&gltf, &buffers), &corner_positions, 0.0);
+    }
+}
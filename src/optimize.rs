@@ -0,0 +1,427 @@
+//! Reordering triangles and vertices so GPUs draw them faster.
+//!
+//! Triangles are ordered with Tipsify, from "Fast Triangle Reordering for Vertex Locality and
+//! Reduced Overdraw" by Sander, Nehab and Barczak, which only depends on the input so builds
+//! are reproducible.
+
+use std::collections::HashMap;
+
+use crate::*;
+
+/// The number of vertices Tipsify assumes the post-transform cache holds.
+const CACHE_SIZE: usize = 16;
+
+/// The largest `byteStride` the specification allows.
+const MAX_BYTE_STRIDE: usize = 252;
+
+#[derive(Debug, Clone, Default)]
+pub struct OptimizeOptions {
+    /// Also orders the clusters of triangles Tipsify finds so those facing away from the center
+    /// of the primitive are drawn first, which hides more of the triangles drawn after them.
+    pub reduce_overdraw: bool,
+    /// Also stores the attributes of each vertex next to each other in a single buffer view.
+    pub interleave: bool,
+}
+
+/// Orders triangles so their vertices are likely to still be in the cache when they're reused.
+/// Returns the triangles, and where each cluster of triangles that start after
+/// the cache was mostly emptied begins.
+fn tipsify(triangles: &[[u32; 3]], vertex_count: usize) -> (Vec<[u32; 3]>, Vec<usize>) {
+    let mut adjacent = vec![Vec::new(); vertex_count];
+    for (t, triangle) in triangles.iter().enumerate() {
+        for &v in triangle {
+            adjacent[v as usize].push(t);
+        }
+    }
+    let mut live: Vec<usize> = adjacent.iter().map(Vec::len).collect();
+    let mut cache_time = vec![0; vertex_count];
+    let mut emitted = vec![false; triangles.len()];
+    let mut dead_ends = Vec::new();
+    let mut time = CACHE_SIZE + 1;
+    let mut cursor = 0;
+
+    let mut output = Vec::with_capacity(triangles.len());
+    let mut clusters = Vec::new();
+    let mut fanning = (0..vertex_count).find(|&v| live[v] > 0);
+    if fanning.is_some() {
+        clusters.push(0);
+    }
+    while let Some(f) = fanning {
+        let mut candidates = Vec::new();
+        for &t in &adjacent[f] {
+            if emitted[t] {
+                continue;
+            }
+            for &v in &triangles[t] {
+                let v = v as usize;
+                dead_ends.push(v);
+                candidates.push(v);
+                live[v] -= 1;
+                if time - cache_time[v] > CACHE_SIZE {
+                    cache_time[v] = time;
+                    time += 1;
+                }
+            }
+            emitted[t] = true;
+            output.push(triangles[t]);
+        }
+
+        // The next vertex is the one whose triangles will use the most vertices still in the cache.
+        let mut best = None;
+        let mut best_priority = 0;
+        for &v in &candidates {
+            if live[v] == 0 {
+                continue;
+            }
+            let mut priority = 1;
+            if time - cache_time[v] + 2 * live[v] <= CACHE_SIZE {
+                priority += time - cache_time[v];
+            }
+            if priority > best_priority {
+                best_priority = priority;
+                best = Some(v);
+            }
+        }
+        fanning = best.or_else(|| {
+            // Without a candidate, the most recent vertex with triangles left is used,
+            // or the next one in order.
+            let next = std::iter::from_fn(|| dead_ends.pop())
+                .find(|&v| live[v] > 0)
+                .or_else(|| {
+                    while cursor < vertex_count && live[cursor] == 0 {
+                        cursor += 1;
+                    }
+                    (cursor < vertex_count).then_some(cursor)
+                });
+            if next.is_some() {
+                clusters.push(output.len());
+            }
+            next
+        });
+    }
+    (output, clusters)
+}
+
+/// Sorts clusters of triangles by how much they face away from the center of all of them.
+fn sort_clusters(
+    triangles: &[[u32; 3]],
+    clusters: &[usize],
+    positions: &[[f32; 3]],
+) -> Vec<[u32; 3]> {
+    let position = |v: u32| positions.get(v as usize).copied().unwrap_or_default();
+    let mut center = [0.0; 3];
+    let mut total_area = 0.0;
+    // The area weighted centers and normals of the clusters.
+    let mut sums = Vec::with_capacity(clusters.len());
+    for (c, &start) in clusters.iter().enumerate() {
+        let end = clusters.get(c + 1).copied().unwrap_or(triangles.len());
+        let mut cluster_center = [0.0; 3];
+        let mut normal = [0.0; 3];
+        let mut cluster_area = 0.0;
+        for triangle in &triangles[start..end] {
+            let [p0, p1, p2] = triangle.map(position);
+            let cross = cross(subtract(p1, p0), subtract(p2, p0));
+            let area = dot(cross, cross).sqrt();
+            for i in 0..3 {
+                cluster_center[i] += (p0[i] + p1[i] + p2[i]) / 3.0 * area;
+                normal[i] += cross[i];
+            }
+            cluster_area += area;
+        }
+        for i in 0..3 {
+            center[i] += cluster_center[i];
+        }
+        total_area += cluster_area;
+        sums.push((start..end, cluster_center, cluster_area, normal));
+    }
+    if total_area > 0.0 {
+        center = center.map(|x| x / total_area);
+    }
+
+    let mut sorted: Vec<(f32, std::ops::Range<usize>)> = sums
+        .into_iter()
+        .map(|(range, cluster_center, area, normal)| {
+            let cluster_center = if area > 0.0 {
+                cluster_center.map(|x| x / area)
+            } else {
+                cluster_center
+            };
+            (
+                dot(subtract(cluster_center, center), normalize(normal)),
+                range,
+            )
+        })
+        .collect();
+    // The sort is stable, so clusters that face the same way keep their order.
+    sorted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    sorted
+        .into_iter()
+        .flat_map(|(_, range)| triangles[range].iter().copied())
+        .collect()
+}
+
+impl GlTf {
+    /// Reorders the triangles of a primitive for the vertex cache, and then its vertices in the
+    /// order the triangles use them, so vertex data is fetched from memory in order.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers, the new accessors are appended
+    /// to the first one. The original accessors are left in place, [GlTf::prune] with
+    /// [PruneOptions::compact_buffers] removes them. Vertices that no triangle uses are removed.
+    /// Only lists of triangles are reordered, [GlTf::convert_to_lists] converts other modes.
+    /// Primitives with `KHR_draco_mesh_compression` are left as they are.
+    ///
+    /// Accessors can't be empty, so if the primitive has no triangles this returns
+    /// [AccessorError::NoTriangles] without changing anything.
+    pub fn optimize_primitive(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mesh: MeshIndex,
+        primitive: usize,
+        options: &OptimizeOptions,
+    ) -> Result<(), AccessorError> {
+        let original = &self.meshes[mesh.value()].primitives[primitive];
+        if !matches!(original.mode, MeshPrimitiveMode::Triangles)
+            || original
+                .extensions
+                .contains_key("KHR_draco_mesh_compression")
+        {
+            return Ok(());
+        }
+        let vertex_count = self.vertex_count(original);
+        let triangles = self.read_triangles(original, buffers)?;
+        if triangles.is_empty() {
+            Err(AccessorError::NoTriangles)?
+        }
+        let (mut triangles, clusters) = tipsify(&triangles, vertex_count);
+        if options.reduce_overdraw && original.attributes.contains_key(&Semantic::Position) {
+            let positions = self.read_vec3_attribute(original, Semantic::Position, buffers)?;
+            triangles = sort_clusters(&triangles, &clusters, &positions);
+        }
+
+        let mut new_vertices = HashMap::new();
+        let mut vertices = Vec::new();
+        let indices: Vec<u32> = triangles
+            .iter()
+            .flatten()
+            .map(|&v| {
+                *new_vertices.entry(v).or_insert_with(|| {
+                    vertices.push(v);
+                    vertices.len() as u32 - 1
+                })
+            })
+            .collect();
+        self.copy_vertices(buffers, mesh, primitive, &vertices, Some(&indices))?;
+        if options.interleave {
+            self.interleave(buffers, mesh, primitive)?;
+        }
+        Ok(())
+    }
+
+    /// Copies the attributes of a primitive into a single buffer view with a stride,
+    /// unless the stride would be larger than the specification allows.
+    /// Morph targets keep their own buffer views.
+    fn interleave(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mesh: MeshIndex,
+        primitive: usize,
+    ) -> Result<(), AccessorError> {
+        let primitive = &self.meshes[mesh.value()].primitives[primitive];
+        // An accessor can be used by several attributes, but is only stored once.
+        let mut accessors: Vec<AccessorIndex> = primitive.attributes.values().copied().collect();
+        accessors.sort_unstable_by_key(|a| a.value());
+        accessors.dedup();
+
+        // Each element starts at a multiple of 4 bytes, as vertex attributes require.
+        let mut offsets = Vec::with_capacity(accessors.len());
+        let mut stride = 0;
+        for &accessor in &accessors {
+            let accessor = &self.accessors[accessor.value()];
+            offsets.push(stride);
+            stride += (accessor.type_.component_count() * accessor.component_type.size() + 3) & !3;
+        }
+        let vertex_count = self.vertex_count(primitive);
+        if stride == 0 || stride > MAX_BYTE_STRIDE {
+            return Ok(());
+        }
+
+        let mut data = vec![0; vertex_count * stride];
+        for (&accessor, &offset) in accessors.iter().zip(&offsets) {
+            let accessor = &self.accessors[accessor.value()];
+            let element_size = accessor.type_.component_count() * accessor.component_type.size();
+            // Copying the vertices gave every accessor a buffer view.
+            let view = accessor.buffer_view.unwrap();
+            let element_stride = self.buffer_views[view.value()]
+                .byte_stride
+                .unwrap_or(element_size);
+            let (source, buffer) = self.buffer_view_data(view, buffers)?;
+            for (v, vertex) in data.chunks_exact_mut(stride).enumerate() {
+                let start = accessor.byte_offset + v * element_stride;
+                let element = source
+                    .get(start..start + element_size)
+                    .ok_or(AccessorError::MissingBufferData(buffer))?;
+                vertex[offset..offset + element_size].copy_from_slice(element);
+            }
+        }
+
+        let view = self.push_buffer_view(
+            buffers,
+            &data,
+            Some(stride),
+            Some(BufferViewTarget::ArrayBuffer),
+        );
+        for (&accessor, &offset) in accessors.iter().zip(&offsets) {
+            let accessor = &mut self.accessors[accessor.value()];
+            accessor.buffer_view = Some(view);
+            accessor.byte_offset = offset;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+
+    /// The data of the corners of each triangle, in order so they can be compared
+    /// however the triangles were reordered.
+    fn sorted_triangles(
+        gltf: &GlTf,
+        primitive: &MeshPrimitive,
+        buffers: &[Vec<u8>],
+    ) -> Vec<Vec<Vec<u32>>> {
+        let corners = triangle_corners(gltf, primitive, buffers);
+        let mut triangles: Vec<Vec<Vec<u32>>> =
+            corners.chunks_exact(3).map(<[_]>::to_vec).collect();
+        triangles.sort();
+        triangles
+    }
+
+    /// The average number of vertices each triangle adds to a first in, first out cache
+    /// of the size Tipsify assumes.
+    fn average_cache_misses(triangles: &[[u32; 3]]) -> f32 {
+        let mut cache = std::collections::VecDeque::new();
+        let mut misses = 0;
+        for &v in triangles.iter().flatten() {
+            if !cache.contains(&v) {
+                misses += 1;
+                cache.push_back(v);
+                if cache.len() > super::CACHE_SIZE {
+                    cache.pop_front();
+                }
+            }
+        }
+        misses as f32 / triangles.len() as f32
+    }
+
+    /// A grid of squares whose triangles are in a scrambled order.
+    fn scrambled_grid() -> (GlTf, Vec<Vec<u8>>) {
+        const SIZE: u32 = 20;
+        let mut builder = GlTfBuilder::new();
+        let positions: Vec<f32> = (0..(SIZE + 1) * (SIZE + 1))
+            .flat_map(|v| [(v % (SIZE + 1)) as f32, (v / (SIZE + 1)) as f32, 0.0])
+            .collect();
+        let positions = builder.add_vertex_attribute(&positions, AccessorType::Vec3);
+        let mut triangles = Vec::new();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let v = y * (SIZE + 1) + x;
+                triangles.push([v, v + 1, v + SIZE + 2]);
+                triangles.push([v, v + SIZE + 2, v + SIZE + 1]);
+            }
+        }
+        // 7919 is prime, so multiplying by it modulo the count visits every triangle once.
+        let count = triangles.len();
+        let indices: Vec<u32> = (0..count)
+            .flat_map(|t| triangles[t * 7919 % count])
+            .collect();
+        let indices = builder.add_indices(&indices);
+        let primitive =
+            GlTfBuilder::primitive(&[(Semantic::Position, positions)], Some(indices), None);
+        builder.add_mesh(None, vec![primitive]);
+        let (gltf, buffer) = builder.finish();
+        (gltf, vec![buffer])
+    }
+
+    #[test]
+    fn optimizing_reduces_cache_misses() {
+        let (mut gltf, mut buffers) = scrambled_grid();
+        let mesh = MeshIndex::new(0);
+        let before = gltf.read_triangles(&gltf.meshes[0].primitives[0], &buffers);
+        gltf.optimize_primitive(&mut buffers, mesh, 0, &OptimizeOptions::default())
+            .unwrap();
+        let after = gltf.read_triangles(&gltf.meshes[0].primitives[0], &buffers);
+        let (before, after) = (before.unwrap(), after.unwrap());
+        assert_eq!(before.len(), after.len());
+        assert!(average_cache_misses(&after) <= average_cache_misses(&before));
+        assert!(average_cache_misses(&after) < 1.0);
+    }
+
+    #[test]
+    fn optimizing_is_deterministic() {
+        let options = OptimizeOptions {
+            reduce_overdraw: true,
+            interleave: true,
+        };
+        let (gltf, buffers) = fox();
+        let mesh = gltf.nodes.iter().find_map(|n| n.mesh).unwrap();
+        let optimize = || {
+            let (mut gltf, mut buffers) = (gltf.clone(), buffers.clone());
+            gltf.optimize_primitive(&mut buffers, mesh, 0, &options)
+                .unwrap();
+            (gltf.to_json_formatted(None), buffers)
+        };
+        assert_eq!(optimize(), optimize());
+    }
+
+    #[test]
+    fn optimizing_refuses_primitives_without_triangles() {
+        let mut builder = GlTfBuilder::new();
+        let positions = builder.add_vertex_attribute(&[0.0; 9], AccessorType::Vec3);
+        let indices = builder.add_indices(&[3, 4, 5]);
+        let primitive =
+            GlTfBuilder::primitive(&[(Semantic::Position, positions)], Some(indices), None);
+        let mesh = builder.add_mesh(None, vec![primitive]);
+        let (mut gltf, buffer) = builder.finish();
+        let mut buffers = vec![buffer];
+        let json = gltf.to_json_formatted(None);
+        let result = gltf.optimize_primitive(&mut buffers, mesh, 0, &OptimizeOptions::default());
+        assert!(matches!(result, Err(AccessorError::NoTriangles)));
+        assert_eq!(gltf.to_json_formatted(None), json);
+    }
+
+    #[test]
+    fn optimizing_keeps_every_triangle() {
+        let options = OptimizeOptions {
+            reduce_overdraw: true,
+            interleave: true,
+        };
+        for (mut gltf, mut buffers) in [cube(), fox()] {
+            let mesh = gltf.nodes.iter().find_map(|n| n.mesh).unwrap();
+            let original = &gltf.meshes[mesh.value()].primitives[0];
+            let triangles = sorted_triangles(&gltf, original, &buffers);
+            let positions = world_positions(&gltf, &buffers);
+
+            gltf.optimize_primitive(&mut buffers, mesh, 0, &options)
+                .unwrap();
+            gltf.prune(
+                &mut buffers,
+                &PruneOptions {
+                    compact_buffers: true,
+                },
+            )
+            .unwrap();
+            let primitive = &gltf.meshes[mesh.value()].primitives[0];
+            assert_eq!(sorted_triangles(&gltf, primitive, &buffers), triangles);
+            // Every vertex is used, so none were added or removed.
+            assert_eq!(world_positions(&gltf, &buffers).len(), positions.len());
+            let views: Vec<_> = primitive
+                .attributes
+                .values()
+                .map(|&a| gltf.accessors[a.value()].buffer_view)
+                .collect();
+            assert!(views.iter().all(|&view| view == views[0]));
+        }
+    }
+}
@@ -0,0 +1,469 @@
+//! Removing objects that no scene uses, and the references to them.
+
+use kserde::ThingOwned;
+
+use crate::*;
+
+#[derive(Debug, Clone, Default)]
+pub struct PruneOptions {
+    /// Also removes the bytes of each buffer that no buffer view uses.
+    pub compact_buffers: bool,
+}
+
+/// Which objects of each list are used.
+///
+/// The indices of the core specification and of the extensions in `extensions` are known,
+/// whether or not their features are enabled.
+struct Used {
+    accessors: Vec<bool>,
+    buffers: Vec<bool>,
+    buffer_views: Vec<bool>,
+    cameras: Vec<bool>,
+    images: Vec<bool>,
+    materials: Vec<bool>,
+    meshes: Vec<bool>,
+    nodes: Vec<bool>,
+    samplers: Vec<bool>,
+    skins: Vec<bool>,
+    textures: Vec<bool>,
+    /// Objects whose indices haven't been marked as used yet.
+    unvisited: Vec<(IndexKind, usize)>,
+}
+
+impl Used {
+    fn none(gltf: &GlTf) -> Self {
+        Self {
+            accessors: vec![false; gltf.accessors.len()],
+            buffers: vec![false; gltf.buffers.len()],
+            buffer_views: vec![false; gltf.buffer_views.len()],
+            cameras: vec![false; gltf.cameras.len()],
+            images: vec![false; gltf.images.len()],
+            materials: vec![false; gltf.materials.len()],
+            meshes: vec![false; gltf.meshes.len()],
+            nodes: vec![false; gltf.nodes.len()],
+            samplers: vec![false; gltf.samplers.len()],
+            skins: vec![false; gltf.skins.len()],
+            textures: vec![false; gltf.textures.len()],
+            unvisited: Vec::new(),
+        }
+    }
+
+    /// The list for a kind of object, or `None` if those objects are never removed.
+    fn list(&self, kind: IndexKind) -> Option<&Vec<bool>> {
+        Some(match kind {
+            IndexKind::Accessor => &self.accessors,
+            IndexKind::Buffer => &self.buffers,
+            IndexKind::BufferView => &self.buffer_views,
+            IndexKind::Camera => &self.cameras,
+            IndexKind::Image => &self.images,
+            IndexKind::Material => &self.materials,
+            IndexKind::Mesh => &self.meshes,
+            IndexKind::Node => &self.nodes,
+            IndexKind::Sampler => &self.samplers,
+            IndexKind::Skin => &self.skins,
+            IndexKind::Texture => &self.textures,
+            _ => None?,
+        })
+    }
+
+    fn list_mut(&mut self, kind: IndexKind) -> Option<&mut Vec<bool>> {
+        Some(match kind {
+            IndexKind::Accessor => &mut self.accessors,
+            IndexKind::Buffer => &mut self.buffers,
+            IndexKind::BufferView => &mut self.buffer_views,
+            IndexKind::Camera => &mut self.cameras,
+            IndexKind::Image => &mut self.images,
+            IndexKind::Material => &mut self.materials,
+            IndexKind::Mesh => &mut self.meshes,
+            IndexKind::Node => &mut self.nodes,
+            IndexKind::Sampler => &mut self.samplers,
+            IndexKind::Skin => &mut self.skins,
+            IndexKind::Texture => &mut self.textures,
+            _ => None?,
+        })
+    }
+
+    /// Marks the object as used. If it wasn't already, its indices are marked later.
+    fn mark(&mut self, kind: IndexKind, index: usize) {
+        let used = self.list_mut(kind).and_then(|list| list.get_mut(index));
+        if let Some(used) = used.filter(|used| !**used) {
+            *used = true;
+            self.unvisited.push((kind, index));
+        }
+    }
+}
+
+fn is_channel_used(channel: &AnimationChannel, used_nodes: &[bool]) -> bool {
+    // Channels without a node are left to the extensions that define their target.
+    channel
+        .target
+        .node
+        .map_or(true, |node| used_nodes.get(node.value()) == Some(&true))
+}
+
+/// The new position of each object once the unused objects before it are removed.
+pub(crate) fn new_indices(used: &[bool]) -> Vec<usize> {
+    used.iter()
+        .scan(0, |next, &used| {
+            let index = *next;
+            *next += used as usize;
+            Some(index)
+        })
+        .collect()
+}
+
+pub(crate) fn retain_used<T>(list: &mut Vec<T>, used: &[bool]) {
+    let mut used = used.iter();
+    list.retain(|_| *used.next().unwrap());
+}
+
+/// The compressed data of a buffer view's `EXT_meshopt_compression` extension.
+///
+/// This is read from and written to the extension's Json, so the data is moved along with
+/// the rest whether or not the feature is enabled.
+pub(crate) struct MeshoptData {
+    pub buffer: usize,
+    pub byte_offset: usize,
+    pub byte_length: usize,
+}
+
+impl MeshoptData {
+    pub fn get(view: &BufferView) -> Option<Self> {
+        let extension = match view.extensions.get("EXT_meshopt_compression") {
+            Some(ThingOwned::Object(extension)) => extension,
+            _ => return None,
+        };
+        let number = |name: &str, default: Option<usize>| match extension.get(name) {
+            Some(ThingOwned::Number(n)) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as usize),
+            None => default,
+            _ => None,
+        };
+        Some(Self {
+            buffer: number("buffer", None)?,
+            byte_offset: number("byteOffset", Some(0))?,
+            byte_length: number("byteLength", None)?,
+        })
+    }
+
+    /// Stores the buffer and offset in the extension. The length never changes.
+    pub fn set(&self, view: &mut BufferView) {
+        if let Some(ThingOwned::Object(extension)) =
+            view.extensions.get_mut("EXT_meshopt_compression")
+        {
+            extension.insert("buffer".to_string(), ThingOwned::Number(self.buffer as f64));
+            extension.insert(
+                "byteOffset".to_string(),
+                ThingOwned::Number(self.byte_offset as f64),
+            );
+        }
+    }
+}
+
+impl GlTf {
+    /// Removes the objects that aren't used by any scene and updates the indices that refer
+    /// to the remaining objects.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers. If the glTF has no scenes
+    /// every node is kept. Animation channels that target removed nodes are removed too,
+    /// along with animations left without channels.
+    ///
+    /// Returns an error without changing anything if an object uses an extension whose
+    /// indices aren't known, as the objects it refers to can't be found or updated.
+    pub fn prune(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        options: &PruneOptions,
+    ) -> Result<(), ExtensionError> {
+        self.check_extension_indices()?;
+        buffers.resize(self.buffers.len(), Vec::new());
+        let used = self.find_used();
+
+        retain_used(&mut self.accessors, &used.accessors);
+        retain_used(&mut self.buffers, &used.buffers);
+        retain_used(buffers, &used.buffers);
+        retain_used(&mut self.buffer_views, &used.buffer_views);
+        retain_used(&mut self.cameras, &used.cameras);
+        retain_used(&mut self.images, &used.images);
+        retain_used(&mut self.materials, &used.materials);
+        retain_used(&mut self.meshes, &used.meshes);
+        retain_used(&mut self.nodes, &used.nodes);
+        retain_used(&mut self.samplers, &used.samplers);
+        retain_used(&mut self.skins, &used.skins);
+        retain_used(&mut self.textures, &used.textures);
+        self.remove_unused_animations(&used.nodes);
+
+        // Animation samplers were already renumbered along with the channels that use them.
+        let mut new = std::collections::HashMap::new();
+        self.visit_indices_mut(&mut |kind, index| {
+            let new = new
+                .entry(kind)
+                .or_insert_with(|| used.list(kind).map(|list| new_indices(list)));
+            if let Some(&new_index) = new.as_ref().and_then(|new| new.get(*index)) {
+                *index = new_index;
+            }
+        });
+
+        if options.compact_buffers {
+            self.compact_buffers(buffers);
+        }
+        Ok(())
+    }
+
+    /// Finds the objects used by the scenes, and by the animations of the nodes in them.
+    fn find_used(&self) -> Used {
+        let mut used = Used::none(self);
+        let roots: Vec<NodeIndex> = if self.scenes.is_empty() {
+            (0..self.nodes.len()).map(Index::new).collect()
+        } else {
+            self.scenes.iter().flat_map(|s| s.nodes.clone()).collect()
+        };
+        for node in roots {
+            used.mark(IndexKind::Node, node.value());
+        }
+        self.mark_references(&mut used);
+
+        for animation in &self.animations {
+            for channel in &animation.channels {
+                if !is_channel_used(channel, &used.nodes) {
+                    continue;
+                }
+                if let Some(sampler) = animation.samplers.get(channel.sampler.value()) {
+                    used.mark(IndexKind::Accessor, sampler.input.value());
+                    used.mark(IndexKind::Accessor, sampler.output.value());
+                }
+            }
+        }
+        self.mark_references(&mut used);
+        used
+    }
+
+    /// Marks everything the unvisited objects refer to as used, and so on.
+    fn mark_references(&self, used: &mut Used) {
+        fn visit<T: VisitIndices + Clone>(list: &[T], index: usize, used: &mut Used) {
+            if let Some(object) = list.get(index) {
+                object
+                    .clone()
+                    .visit_indices_mut(&mut |kind, &mut index| used.mark(kind, index));
+            }
+        }
+        while let Some((kind, index)) = used.unvisited.pop() {
+            match kind {
+                IndexKind::Accessor => visit(&self.accessors, index, used),
+                IndexKind::Buffer => visit(&self.buffers, index, used),
+                IndexKind::BufferView => visit(&self.buffer_views, index, used),
+                IndexKind::Camera => visit(&self.cameras, index, used),
+                IndexKind::Image => visit(&self.images, index, used),
+                IndexKind::Material => visit(&self.materials, index, used),
+                IndexKind::Mesh => visit(&self.meshes, index, used),
+                IndexKind::Node => visit(&self.nodes, index, used),
+                IndexKind::Sampler => visit(&self.samplers, index, used),
+                IndexKind::Skin => visit(&self.skins, index, used),
+                IndexKind::Texture => visit(&self.textures, index, used),
+                _ => {}
+            }
+        }
+    }
+
+    /// Removes the channels that target removed nodes, the samplers no channel uses
+    /// and the animations without channels.
+    fn remove_unused_animations(&mut self, used_nodes: &[bool]) {
+        for animation in &mut self.animations {
+            animation
+                .channels
+                .retain(|channel| is_channel_used(channel, used_nodes));
+            let mut used_samplers = vec![false; animation.samplers.len()];
+            for channel in &animation.channels {
+                if let Some(used) = used_samplers.get_mut(channel.sampler.value()) {
+                    *used = true;
+                }
+            }
+            let samplers = new_indices(&used_samplers);
+            retain_used(&mut animation.samplers, &used_samplers);
+            for channel in &mut animation.channels {
+                if let Some(&sampler) = samplers.get(channel.sampler.value()) {
+                    channel.sampler = Index::new(sampler);
+                }
+            }
+        }
+        self.animations.retain(|a| !a.channels.is_empty());
+    }
+
+    /// Removes the bytes of each buffer that no buffer view uses, keeping the alignment
+    /// of the rest. Buffers whose data isn't in `buffers` are left as they are.
+    pub fn compact_buffers(&mut self, buffers: &mut [Vec<u8>]) {
+        for (b, data) in buffers.iter_mut().enumerate() {
+            let buffer = Index::new(b);
+            if data.len() < self.buffers[b].byte_length {
+                continue;
+            }
+
+            // The byte ranges that are used, with the view or extension that uses each.
+            let mut ranges = Vec::new();
+            for view in self.buffer_views.iter() {
+                if view.buffer == buffer {
+                    ranges.push((view.byte_offset, view.byte_length));
+                }
+                if let Some(compressed) = MeshoptData::get(view).filter(|d| d.buffer == b) {
+                    ranges.push((compressed.byte_offset, compressed.byte_length));
+                }
+            }
+            ranges.sort_unstable();
+
+            // Overlapping ranges are copied together, each as the start of the original range,
+            // the end and the start in the new data.
+            let mut segments: Vec<(usize, usize, usize)> = Vec::new();
+            let mut compacted = Vec::new();
+            for (offset, length) in ranges {
+                let end = (offset + length).min(data.len());
+                match segments.last_mut() {
+                    Some(segment) if offset <= segment.1 => segment.1 = segment.1.max(end),
+                    _ => segments.push((offset, end, 0)),
+                }
+            }
+            for segment in &mut segments {
+                // Keep the offset modulo 4 so the alignment of the data doesn't change.
+                let start = compacted.len() + (segment.0.wrapping_sub(compacted.len()) & 3);
+                compacted.resize(start, 0);
+                compacted.extend_from_slice(&data[segment.0.min(segment.1)..segment.1]);
+                segment.2 = start;
+            }
+            let new_offset = |offset: usize| {
+                segments
+                    .iter()
+                    .find(|s| (s.0..=s.1).contains(&offset))
+                    .map_or(offset, |s| offset - s.0 + s.2)
+            };
+
+            for view in self.buffer_views.iter_mut() {
+                if view.buffer == buffer {
+                    view.byte_offset = new_offset(view.byte_offset);
+                }
+                if let Some(mut compressed) = MeshoptData::get(view).filter(|d| d.buffer == b) {
+                    compressed.byte_offset = new_offset(compressed.byte_offset);
+                    compressed.set(view);
+                }
+            }
+            self.buffers[b].byte_length = compacted.len();
+            *data = compacted;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+    use kserde::ThingOwned;
+
+    #[test]
+    fn prune_round_trips_used_objects() {
+        let (original, original_buffers) = fox();
+        let (mut gltf, mut buffers) = fox();
+        gltf.prune(&mut buffers, &PruneOptions::default()).unwrap();
+        assert_eq!(
+            gltf.to_json_formatted(None),
+            original.to_json_formatted(None)
+        );
+        assert_eq!(buffers, original_buffers);
+
+        // Unused copies of objects are removed again.
+        gltf.accessors.push(gltf.accessors[0].clone());
+        gltf.materials.push(Material::default());
+        let mesh = gltf.push(gltf.meshes[0].clone());
+        gltf.push(Node {
+            mesh: Some(mesh),
+            ..Default::default()
+        });
+        gltf.prune(&mut buffers, &PruneOptions::default()).unwrap();
+        assert_eq!(
+            gltf.to_json_formatted(None),
+            original.to_json_formatted(None)
+        );
+        assert_close(
+            &world_positions(&gltf, &buffers),
+            &world_positions(&original, &original_buffers),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn prune_keeps_objects_used_by_extensions() {
+        let (mut gltf, mut buffers) = cube();
+        let unused = gltf.push(Image::default());
+        let image = gltf.push(Image::default());
+        let mut extension = std::collections::HashMap::new();
+        extension.insert(
+            "source".to_string(),
+            ThingOwned::Number(image.value() as f64),
+        );
+        gltf.textures[0].extensions.insert(
+            "KHR_texture_basisu".to_string(),
+            ThingOwned::Object(extension),
+        );
+        gltf.prune(&mut buffers, &PruneOptions::default()).unwrap();
+        assert_eq!(gltf.images.len(), unused.value() + 1);
+        let extension = &gltf.textures[0].extensions["KHR_texture_basisu"];
+        let source = match extension {
+            ThingOwned::Object(values) => &values["source"],
+            _ => panic!("expected an object"),
+        };
+        assert!(matches!(source, ThingOwned::Number(n) if *n == unused.value() as f64));
+    }
+
+    #[test]
+    fn prune_refuses_unknown_extensions() {
+        let (mut gltf, mut buffers) = cube();
+        gltf.push(Material::default());
+        gltf.materials[0]
+            .extensions
+            .insert("EXT_unknown".to_string(), ThingOwned::Null);
+        let json = gltf.to_json_formatted(None);
+        let result = gltf.prune(&mut buffers, &PruneOptions::default());
+        assert!(
+            matches!(result, Err(ExtensionError::UnknownIndices(name)) if name == "EXT_unknown")
+        );
+        assert_eq!(gltf.to_json_formatted(None), json);
+    }
+
+    #[test]
+    fn prune_moves_meshopt_data() {
+        // Compressed data is kept whether or not `EXT_meshopt_compression` is enabled.
+        let mut gltf = GlTf::from_json(
+            r#"{
+                "asset": { "version": "2.0" },
+                "buffers": [
+                    { "byteLength": 4 },
+                    { "byteLength": 12 },
+                    { "byteLength": 8, "extensions": { "EXT_meshopt_compression": { "fallback": true } } }
+                ],
+                "bufferViews": [{
+                    "buffer": 2,
+                    "byteLength": 8,
+                    "extensions": { "EXT_meshopt_compression": {
+                        "buffer": 1, "byteOffset": 4, "byteLength": 8,
+                        "byteStride": 4, "count": 2, "mode": "ATTRIBUTES"
+                    } }
+                }],
+                "accessors": [{ "bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR" }],
+                "meshes": [{ "primitives": [{ "attributes": { "_DATA": 0 } }] }],
+                "nodes": [{ "mesh": 0 }],
+                "scenes": [{ "nodes": [0] }]
+            }"#,
+        )
+        .unwrap();
+        let data: Vec<u8> = (0..12).collect();
+        let mut buffers = vec![vec![0; 4], data.clone(), Vec::new()];
+        let options = PruneOptions {
+            compact_buffers: true,
+        };
+        gltf.prune(&mut buffers, &options).unwrap();
+
+        assert_eq!(gltf.buffers.len(), 2);
+        assert_eq!(buffers[0], data[4..]);
+        let compressed = crate::prune::MeshoptData::get(&gltf.buffer_views[0]).unwrap();
+        assert_eq!(compressed.buffer, 0);
+        assert_eq!(compressed.byte_offset, 0);
+        assert_eq!(compressed.byte_length, 8);
+        assert_eq!(gltf.buffer_views[0].buffer, Index::new(1));
+    }
+}
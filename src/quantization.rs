@@ -0,0 +1,341 @@
+//! Storing mesh attributes as integers with `KHR_mesh_quantization`.
+
+use std::collections::HashMap;
+
+use crate::*;
+
+pub const KHR_MESH_QUANTIZATION: &str = "KHR_mesh_quantization";
+
+#[derive(Debug, Clone)]
+pub struct QuantizationOptions {
+    /// The bits kept for each position component, from 1 to 16.
+    /// Positions are stored as unsigned bytes when this is at most 8, otherwise as unsigned shorts.
+    pub position_bits: u32,
+    /// The bits kept for each normal and tangent component, including the sign, from 2 to 16.
+    /// Normals and tangents are stored as normalized bytes when this is at most 8,
+    /// otherwise as normalized shorts.
+    pub normal_bits: u32,
+    /// The bits kept for each texture coordinate component, from 1 to 16.
+    /// Texture coordinates are stored as normalized unsigned bytes when this is at most 8,
+    /// otherwise as normalized unsigned shorts.
+    pub tex_coord_bits: u32,
+}
+
+impl Default for QuantizationOptions {
+    fn default() -> Self {
+        Self {
+            position_bits: 14,
+            normal_bits: 8,
+            tex_coord_bits: 12,
+        }
+    }
+}
+
+/// The translation and uniform scale that dequantize a mesh's positions.
+struct PositionTransform {
+    offset: [f32; 3],
+    scale: f32,
+}
+
+/// Rounds normalized values to the steps of a normalized integer with `bits` bits,
+/// which can be fewer than the bits of the type they're stored in.
+fn round_to_bits(values: &mut [f32], bits: u32, signed: bool) {
+    let max = ((1u32 << (bits - signed as u32)) - 1) as f32;
+    for value in values {
+        *value = (*value * max).round() / max;
+    }
+}
+
+impl GlTf {
+    /// Converts the float attributes of every mesh to the smallest integer type
+    /// `KHR_mesh_quantization` allows, and adds the extension to `extensionsUsed`
+    /// and `extensionsRequired`.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers, the new accessors are appended
+    /// to the first one. The original accessors are left in place.
+    ///
+    /// Positions are offset by the mesh's minimum and scaled uniformly to fit the integer range.
+    /// To compensate, the mesh is moved from each node that uses it to a new child node
+    /// with the inverse transform, along with the node's morph weights.
+    /// Positions of skinned meshes are kept as floats because skinning ignores the node's transform,
+    /// as are those of meshes without nodes or used by nodes with `EXT_mesh_gpu_instancing`,
+    /// which have no node to compensate. Texture coordinates outside of [0, 1] are kept
+    /// as floats because they can't be normalized.
+    pub fn quantize_meshes(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        options: &QuantizationOptions,
+    ) -> Result<(), AccessorError> {
+        let normal_bits = options.normal_bits.clamp(2, 16);
+        let tex_coord_bits = options.tex_coord_bits.clamp(1, 16);
+        let (normal_type, tex_coord_type) = (
+            if normal_bits <= 8 {
+                AccessorComponentType::Byte
+            } else {
+                AccessorComponentType::Short
+            },
+            if tex_coord_bits <= 8 {
+                AccessorComponentType::UnsignedByte
+            } else {
+                AccessorComponentType::UnsignedShort
+            },
+        );
+        let position_bits = options.position_bits.clamp(1, 16);
+        let position_type = if position_bits <= 8 {
+            AccessorComponentType::UnsignedByte
+        } else {
+            AccessorComponentType::UnsignedShort
+        };
+
+        // Accessors can be shared, so each is only converted once.
+        let mut converted = HashMap::new();
+        let mut quantized = false;
+        for mesh in (0..self.meshes.len()).map(MeshIndex::new) {
+            // Meshes without nodes would have nowhere to put the dequantization transform.
+            let users: Vec<&Node> = self.nodes.iter().filter(|n| n.mesh == Some(mesh)).collect();
+            let skinned = users.iter().any(|n| n.skin.is_some());
+            let instanced = users
+                .iter()
+                .any(|n| n.extensions.contains_key("EXT_mesh_gpu_instancing"));
+            let transform = if users.is_empty() || skinned || instanced {
+                None
+            } else {
+                self.position_transform(mesh, buffers, position_bits)?
+            };
+            // Positions depend on the mesh's transform so they're only shared within a mesh.
+            let mut converted_positions = HashMap::new();
+
+            for p in 0..self.meshes[mesh.value()].primitives.len() {
+                let primitive = &self.meshes[mesh.value()].primitives[p];
+                let mut attributes = primitive.attributes.clone();
+                let mut targets = primitive.targets.clone();
+                for (semantic, accessor) in attributes.iter_mut() {
+                    if !self.is_float_accessor(*accessor) {
+                        continue;
+                    }
+                    if *semantic == Semantic::Position {
+                        if let Some(transform) = &transform {
+                            *accessor = match converted_positions.get(accessor) {
+                                Some(&new) => new,
+                                None => {
+                                    let mut values = self.read_accessor_f32(*accessor, buffers)?;
+                                    for position in values.chunks_exact_mut(3) {
+                                        for (value, offset) in
+                                            position.iter_mut().zip(transform.offset)
+                                        {
+                                            *value = (*value - offset) / transform.scale;
+                                        }
+                                    }
+                                    let new = self.push_accessor(
+                                        buffers,
+                                        &values,
+                                        AccessorType::Vec3,
+                                        position_type.clone(),
+                                        false,
+                                        Some(BufferViewTarget::ArrayBuffer),
+                                    );
+                                    converted_positions.insert(*accessor, new);
+                                    new
+                                }
+                            };
+                            quantized = true;
+                        }
+                        continue;
+                    }
+
+                    let (type_, component_type, bits, signed) = match semantic {
+                        Semantic::Normal => {
+                            (AccessorType::Vec3, normal_type.clone(), normal_bits, true)
+                        }
+                        Semantic::Tangent => {
+                            (AccessorType::Vec4, normal_type.clone(), normal_bits, true)
+                        }
+                        Semantic::TexCoord(_) => (
+                            AccessorType::Vec2,
+                            tex_coord_type.clone(),
+                            tex_coord_bits,
+                            false,
+                        ),
+                        _ => continue,
+                    };
+                    *accessor = match converted.get(accessor) {
+                        Some(&new) => new,
+                        None => {
+                            let mut values = self.read_accessor_f32(*accessor, buffers)?;
+                            if matches!(type_, AccessorType::Vec2)
+                                && values.iter().any(|&v| !(0.0..=1.0).contains(&v))
+                            {
+                                continue;
+                            }
+                            round_to_bits(&mut values, bits, signed);
+                            let new = self.push_accessor(
+                                buffers,
+                                &values,
+                                type_,
+                                component_type,
+                                true,
+                                Some(BufferViewTarget::ArrayBuffer),
+                            );
+                            converted.insert(*accessor, new);
+                            new
+                        }
+                    };
+                    quantized = true;
+                }
+
+                // Position displacements are scaled by the new node too, but not offset.
+                if let Some(transform) = &transform {
+                    for target in &mut targets {
+                        if let Some(accessor) = target.get_mut(&Semantic::Position) {
+                            let mut values = self.read_accessor_f32(*accessor, buffers)?;
+                            for value in &mut values {
+                                *value /= transform.scale;
+                            }
+                            *accessor = self.push_accessor(
+                                buffers,
+                                &values,
+                                AccessorType::Vec3,
+                                AccessorComponentType::Float,
+                                false,
+                                Some(BufferViewTarget::ArrayBuffer),
+                            );
+                        }
+                    }
+                }
+
+                let primitive = &mut self.meshes[mesh.value()].primitives[p];
+                primitive.attributes = attributes;
+                primitive.targets = targets;
+            }
+
+            if let Some(transform) = transform {
+                self.add_dequantization_nodes(mesh, &transform);
+            }
+        }
+
+        if quantized {
+            self.add_extension_used(KHR_MESH_QUANTIZATION, true);
+        }
+        Ok(())
+    }
+
+    fn is_float_accessor(&self, accessor: AccessorIndex) -> bool {
+        self.get(accessor)
+            .is_some_and(|a| matches!(a.component_type, AccessorComponentType::Float))
+    }
+
+    /// Finds the offset and scale that fit the mesh's float positions in the integer range,
+    /// or `None` if it has none.
+    fn position_transform(
+        &self,
+        mesh: MeshIndex,
+        buffers: &[Vec<u8>],
+        bits: u32,
+    ) -> Result<Option<PositionTransform>, AccessorError> {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for primitive in &self.meshes[mesh.value()].primitives {
+            let accessor = match primitive.attributes.get(&Semantic::Position) {
+                Some(&accessor) if self.is_float_accessor(accessor) => accessor,
+                _ => continue,
+            };
+            for position in self.read_accessor_f32(accessor, buffers)?.chunks_exact(3) {
+                for i in 0..3 {
+                    min[i] = min[i].min(position[i]);
+                    max[i] = max[i].max(position[i]);
+                }
+            }
+        }
+        if min[0] > max[0] {
+            return Ok(None);
+        }
+
+        // A uniform scale keeps normals pointing the same way.
+        let extent = (0..3).map(|i| max[i] - min[i]).fold(0.0, f32::max);
+        let scale = if extent > 0.0 {
+            extent / ((1u32 << bits) - 1) as f32
+        } else {
+            1.0
+        };
+        Ok(Some(PositionTransform { offset: min, scale }))
+    }
+
+    /// Moves the mesh from each node that uses it to a new child node that dequantizes it.
+    fn add_dequantization_nodes(&mut self, mesh: MeshIndex, transform: &PositionTransform) {
+        for parent in (0..self.nodes.len()).map(NodeIndex::new) {
+            if self.nodes[parent.value()].mesh != Some(mesh) {
+                continue;
+            }
+            let child = Index::new(self.nodes.len());
+            let node = &mut self.nodes[parent.value()];
+            node.mesh = None;
+            node.children.push(child);
+            let weights = std::mem::take(&mut node.weights);
+            self.nodes.push(Node {
+                mesh: Some(mesh),
+                scale: Some([transform.scale; 3]),
+                translation: Some(transform.offset),
+                weights,
+                ..Default::default()
+            });
+
+            // Morph weights are animated on the node with the mesh.
+            for animation in &mut self.animations {
+                for channel in &mut animation.channels {
+                    if channel.target.node == Some(parent)
+                        && matches!(channel.target.path, AnimationChannelTargetPath::Weights)
+                    {
+                        channel.target.node = Some(child);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+    use kserde::ThingOwned;
+
+    #[test]
+    fn quantized_cube_keeps_positions_and_loads() {
+        let (mut gltf, mut buffers) = cube();
+        let original = world_positions(&gltf, &buffers);
+        gltf.quantize_meshes(&mut buffers, &QuantizationOptions::default())
+            .unwrap();
+
+        let position = gltf.meshes[0].primitives[0].attributes[&Semantic::Position];
+        assert!(matches!(
+            gltf.accessors[position.value()].component_type,
+            AccessorComponentType::UnsignedShort
+        ));
+        assert_close(&world_positions(&gltf, &buffers), &original, 1e-3);
+
+        let registry = ExtensionRegistry::new();
+        assert!(GlTf::from_json_with_extensions(&gltf.to_json(), &registry).is_ok());
+    }
+
+    #[test]
+    fn instanced_and_unused_meshes_keep_float_positions() {
+        let (mut gltf, mut buffers) = cube();
+        let mesh = gltf.meshes[0].clone();
+        gltf.meshes.push(mesh);
+        let instancing = ThingOwned::Object(Default::default());
+        gltf.nodes[0]
+            .extensions
+            .insert("EXT_mesh_gpu_instancing".to_string(), instancing);
+        gltf.quantize_meshes(&mut buffers, &QuantizationOptions::default())
+            .unwrap();
+
+        assert_eq!(gltf.nodes.len(), 1);
+        for mesh in &gltf.meshes {
+            let position = mesh.primitives[0].attributes[&Semantic::Position];
+            assert!(matches!(
+                gltf.accessors[position.value()].component_type,
+                AccessorComponentType::Float
+            ));
+        }
+    }
+}
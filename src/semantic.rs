@@ -0,0 +1,342 @@
+use kserde::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+
+use crate::*;
+
+/// The name of a vertex attribute, such as `POSITION` or `TEXCOORD_0`.
+///
+/// Semantics are ordered as they're listed here, then by set index, which is the order
+/// they're serialized in.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Semantic {
+    Position,
+    Normal,
+    Tangent,
+    TexCoord(u32),
+    Color(u32),
+    Joints(u32),
+    Weights(u32),
+    /// An application-specific attribute, whose name should start with an underscore.
+    Custom(String),
+}
+
+impl Semantic {
+    /// Parses an attribute name. Names the specification doesn't define are `Custom`,
+    /// including set indices with a sign or leading zeros, so every name is written back
+    /// as it was read.
+    pub fn parse(name: &str) -> Self {
+        let set = |prefix: &str| {
+            let n = name.strip_prefix(prefix)?;
+            n.parse().ok().filter(|set: &u32| set.to_string() == n)
+        };
+        match name {
+            "POSITION" => Self::Position,
+            "NORMAL" => Self::Normal,
+            "TANGENT" => Self::Tangent,
+            _ => {
+                if let Some(n) = set("TEXCOORD_") {
+                    Self::TexCoord(n)
+                } else if let Some(n) = set("COLOR_") {
+                    Self::Color(n)
+                } else if let Some(n) = set("JOINTS_") {
+                    Self::Joints(n)
+                } else if let Some(n) = set("WEIGHTS_") {
+                    Self::Weights(n)
+                } else {
+                    Self::Custom(name.to_string())
+                }
+            }
+        }
+    }
+
+    /// Whether an accessor can hold the attribute.
+    ///
+    /// Morph targets hold displacements, so their tangents are `VEC3` and their integers
+    /// are signed. `quantized` allows the extra types of `KHR_mesh_quantization`.
+    fn accepts(&self, accessor: &Accessor, morph_target: bool, quantized: bool) -> bool {
+        use AccessorComponentType::*;
+        use AccessorType::*;
+        let float = matches!(accessor.component_type, Float);
+        let normalized = accessor.normalized;
+        let signed = matches!(accessor.component_type, Byte | Short);
+        let unsigned = matches!(accessor.component_type, UnsignedByte | UnsignedShort);
+        if morph_target {
+            return match (self, &accessor.type_) {
+                (Self::Position, Vec3) => float || (quantized && signed),
+                (Self::Normal, Vec3) | (Self::Tangent, Vec3) => {
+                    float || (quantized && normalized && signed)
+                }
+                (Self::TexCoord(_), Vec2) => {
+                    float || (normalized && (signed || unsigned)) || (quantized && signed)
+                }
+                (Self::Color(_), Vec3 | Vec4) => float || (normalized && (signed || unsigned)),
+                (Self::Custom(_), _) => true,
+                _ => false,
+            };
+        }
+        match (self, &accessor.type_) {
+            (Self::Position, Vec3) => float || (quantized && (signed || unsigned)),
+            (Self::Normal, Vec3) | (Self::Tangent, Vec4) => {
+                float || (quantized && normalized && signed)
+            }
+            (Self::TexCoord(_), Vec2) => {
+                float || (normalized && unsigned) || (quantized && (signed || unsigned))
+            }
+            (Self::Color(_), Vec3 | Vec4) | (Self::Weights(_), Vec4) => {
+                float || (normalized && unsigned)
+            }
+            (Self::Joints(_), Vec4) => !normalized && unsigned,
+            (Self::Custom(_), _) => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<&str> for Semantic {
+    fn from(name: &str) -> Self {
+        Self::parse(name)
+    }
+}
+
+impl fmt::Display for Semantic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Position => write!(f, "POSITION"),
+            Self::Normal => write!(f, "NORMAL"),
+            Self::Tangent => write!(f, "TANGENT"),
+            Self::TexCoord(n) => write!(f, "TEXCOORD_{}", n),
+            Self::Color(n) => write!(f, "COLOR_{}", n),
+            Self::Joints(n) => write!(f, "JOINTS_{}", n),
+            Self::Weights(n) => write!(f, "WEIGHTS_{}", n),
+            Self::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// The accessors of a primitive's attributes or of a morph target, in the order of their semantics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes(pub BTreeMap<Semantic, AccessorIndex>);
+
+impl Deref for Attributes {
+    type Target = BTreeMap<Semantic, AccessorIndex>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Attributes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl FromIterator<(Semantic, AccessorIndex)> for Attributes {
+    fn from_iter<I: IntoIterator<Item = (Semantic, AccessorIndex)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = (&'a Semantic, &'a AccessorIndex);
+    type IntoIter = std::collections::btree_map::Iter<'a, Semantic, AccessorIndex>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<S: Serializer> Serialize<S> for Attributes {
+    fn serialize(&self, serializer: &mut S) {
+        serializer.begin_object();
+        for (semantic, accessor) in &self.0 {
+            serializer.property(&semantic.to_string(), accessor);
+        }
+        serializer.end_object();
+    }
+}
+
+impl<'a, D: Deserializer<'a>> Deserialize<'a, D> for Attributes {
+    fn deserialize(deserializer: &mut D) -> Option<Self> {
+        deserializer.begin_object().then_some(())?;
+        let mut attributes = BTreeMap::new();
+        while let Some(name) = deserializer.has_property() {
+            attributes.insert(
+                Semantic::parse(&name),
+                AccessorIndex::deserialize(deserializer)?,
+            );
+        }
+        Some(Self(attributes))
+    }
+}
+
+#[derive(Debug)]
+pub enum AttributeError {
+    /// The semantic isn't defined by the specification and doesn't start with an underscore.
+    UnknownSemantic(String),
+    /// The attribute refers to an accessor that doesn't exist.
+    MissingAccessor(Semantic, AccessorIndex),
+    /// The accessor's type, component type or normalization isn't allowed for the semantic.
+    InvalidAccessor(Semantic, AccessorIndex),
+    /// The attributes don't all have the same number of elements.
+    MismatchedCount(Semantic, AccessorIndex),
+}
+
+impl GlTf {
+    /// Checks that the attributes and morph targets of a primitive use accessors
+    /// the specification allows for their semantics.
+    pub fn validate_attributes(&self, primitive: &MeshPrimitive) -> Result<(), AttributeError> {
+        let quantized = self
+            .extensions_used
+            .iter()
+            .any(|e| e == "KHR_mesh_quantization");
+        let mut count = None;
+        let all_attributes = std::iter::once((&primitive.attributes, false))
+            .chain(primitive.targets.iter().map(|target| (target, true)));
+        for (attributes, morph_target) in all_attributes {
+            for (semantic, &index) in attributes {
+                if let Semantic::Custom(name) = semantic {
+                    if !name.starts_with('_') {
+                        Err(AttributeError::UnknownSemantic(name.clone()))?
+                    }
+                }
+                let accessor = self
+                    .get(index)
+                    .ok_or_else(|| AttributeError::MissingAccessor(semantic.clone(), index))?;
+                if !semantic.accepts(accessor, morph_target, quantized) {
+                    Err(AttributeError::InvalidAccessor(semantic.clone(), index))?
+                }
+                if *count.get_or_insert(accessor.count) != accessor.count {
+                    Err(AttributeError::MismatchedCount(semantic.clone(), index))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for name in [
+            "POSITION",
+            "TEXCOORD_0",
+            "COLOR_12",
+            "TEXCOORD_01",
+            "JOINTS_+1",
+            "_X",
+        ] {
+            assert_eq!(Semantic::parse(name).to_string(), name);
+        }
+        assert_eq!(Semantic::parse("TEXCOORD_1"), Semantic::TexCoord(1));
+        assert_eq!(
+            Semantic::parse("TEXCOORD_01"),
+            Semantic::Custom("TEXCOORD_01".to_string())
+        );
+    }
+
+    /// Validates a primitive with a single attribute, or a morph target with it.
+    fn validate(
+        name: &str,
+        component_type: AccessorComponentType,
+        type_: AccessorType,
+        normalized: bool,
+        morph_target: bool,
+        quantized: bool,
+    ) -> Result<(), AttributeError> {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        if quantized {
+            gltf.add_extension_used("KHR_mesh_quantization", true);
+        }
+        let accessor = gltf.push(Accessor {
+            normalized,
+            ..Accessor::new(component_type, 3, type_)
+        });
+        let attributes: Attributes = std::iter::once((Semantic::parse(name), accessor)).collect();
+        let primitive = if morph_target {
+            MeshPrimitive {
+                targets: vec![attributes],
+                ..MeshPrimitive::new(Attributes::default())
+            }
+        } else {
+            MeshPrimitive::new(attributes)
+        };
+        gltf.validate_attributes(&primitive)
+    }
+
+    #[test]
+    fn accessors_are_checked_against_semantics() {
+        use AccessorComponentType::*;
+        use AccessorType::*;
+        let cases = [
+            // Name, component type, type, normalized, morph target, quantized, valid.
+            ("POSITION", Float, Vec3, false, false, false, true),
+            ("POSITION", Float, Vec2, false, false, false, false),
+            ("POSITION", UnsignedShort, Vec3, false, false, false, false),
+            ("POSITION", UnsignedShort, Vec3, false, false, true, true),
+            ("POSITION", UnsignedShort, Vec3, false, true, true, false),
+            ("POSITION", Short, Vec3, false, true, true, true),
+            ("NORMAL", Byte, Vec3, true, false, false, false),
+            ("NORMAL", Byte, Vec3, true, false, true, true),
+            ("NORMAL", Byte, Vec3, false, false, true, false),
+            ("NORMAL", Byte, Vec3, true, true, true, true),
+            ("NORMAL", Byte, Vec3, false, true, true, false),
+            ("TANGENT", Float, Vec3, false, false, false, false),
+            ("TANGENT", Float, Vec4, false, true, false, false),
+            ("TANGENT", Float, Vec3, false, true, false, true),
+            ("TANGENT", Short, Vec3, true, true, true, true),
+            ("TANGENT", Short, Vec3, false, true, true, false),
+            ("TEXCOORD_0", UnsignedByte, Vec2, true, false, false, true),
+            ("TEXCOORD_0", UnsignedByte, Vec2, false, false, false, false),
+            ("TEXCOORD_0", Byte, Vec2, true, false, false, false),
+            ("TEXCOORD_0", Short, Vec2, false, true, true, true),
+            ("TEXCOORD_0", Short, Vec2, false, true, false, false),
+            ("TEXCOORD_0", UnsignedShort, Vec2, false, true, true, false),
+            ("COLOR_0", UnsignedShort, Vec4, false, false, false, false),
+            ("COLOR_0", UnsignedShort, Vec4, true, false, false, true),
+            ("COLOR_0", Float, Vec2, false, false, false, false),
+            ("JOINTS_0", UnsignedShort, Vec4, false, false, false, true),
+            ("JOINTS_0", UnsignedByte, Vec4, true, false, false, false),
+            ("JOINTS_0", Float, Vec4, false, false, false, false),
+            ("JOINTS_0", UnsignedShort, Vec4, false, true, false, false),
+            ("WEIGHTS_0", UnsignedInt, Vec4, true, false, false, false),
+            ("WEIGHTS_0", UnsignedByte, Vec4, true, false, false, true),
+            ("_TEMPERATURE", UnsignedInt, Mat4, false, false, false, true),
+        ];
+        for (name, component_type, type_, normalized, morph_target, quantized, valid) in cases {
+            let result = validate(
+                name,
+                component_type.clone(),
+                type_.clone(),
+                normalized,
+                morph_target,
+                quantized,
+            );
+            match result {
+                Ok(()) => assert!(valid, "{} {:?} {:?}", name, component_type, type_),
+                Err(AttributeError::InvalidAccessor(semantic, _)) => {
+                    assert!(!valid, "{} {:?} {:?}", name, component_type, type_);
+                    assert_eq!(semantic, Semantic::parse(name));
+                }
+                Err(e) => panic!("{:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_semantics_are_rejected() {
+        use AccessorComponentType::*;
+        use AccessorType::*;
+        // A set index with a leading zero isn't a set, so the name is an unknown semantic.
+        for name in ["TEXCOORD_01", "COLOR_+1", "UV"] {
+            let result = validate(name, Float, Vec2, false, false, false);
+            assert!(matches!(result, Err(AttributeError::UnknownSemantic(n)) if n == name));
+        }
+    }
+}
@@ -0,0 +1,619 @@
+//! Generating tangents with MikkTSpace, which the specification requires when `TANGENT`
+//! is missing from a primitive with a normal texture.
+//!
+//! This follows the reference implementation by Morten S. Mikkelsen so tangents match those
+//! that normal maps are baked with. It only handles triangles, so the parts for quads are left out.
+
+use std::collections::HashMap;
+
+use crate::*;
+
+struct Vertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    tex_coord: [f32; 2],
+}
+
+/// The tangent space of a corner of a triangle.
+#[derive(Clone, Copy)]
+struct TangentSpace {
+    tangent: [f32; 3],
+    /// Whether the texture coordinates aren't mirrored.
+    orientation_preserving: bool,
+}
+
+impl Default for TangentSpace {
+    /// The tangent space of corners that aren't part of any group. The reference
+    /// implementation doesn't make them orientation preserving with its flipped
+    /// texture coordinates, which is the opposite with glTF's.
+    fn default() -> Self {
+        Self {
+            tangent: [1.0, 0.0, 0.0],
+            orientation_preserving: true,
+        }
+    }
+}
+
+struct Triangle {
+    /// The welded corners.
+    corners: [usize; 3],
+    /// The index of the triangle before degenerate triangles were moved to the end.
+    original: usize,
+    /// The triangle across each edge, which starts at the corner with the same index.
+    neighbors: [Option<usize>; 3],
+    /// The group of each corner.
+    groups: [Option<usize>; 3],
+    /// The normalized direction of increasing `u` and `v`, before projection onto the normals.
+    os: [f32; 3],
+    ot: [f32; 3],
+    orientation_preserving: bool,
+    /// Whether the triangle has no area in texture space, so it can join any group.
+    group_with_any: bool,
+}
+
+/// Corners that share a vertex and are connected by edges of triangles with the same orientation.
+struct Group {
+    /// The welded corner shared by every triangle of the group.
+    vertex: usize,
+    orientation_preserving: bool,
+    triangles: Vec<usize>,
+}
+
+fn not_zero(x: f32) -> bool {
+    x.abs() > f32::MIN_POSITIVE
+}
+
+fn vector_not_zero(v: [f32; 3]) -> bool {
+    not_zero(v[0]) || not_zero(v[1]) || not_zero(v[2])
+}
+
+fn scale(s: f32, v: [f32; 3]) -> [f32; 3] {
+    [s * v[0], s * v[1], s * v[2]]
+}
+
+/// Normalizes as the reference implementation does, to get identical results.
+fn mikk_normalize(v: [f32; 3]) -> [f32; 3] {
+    scale(1.0 / dot(v, v).sqrt(), v)
+}
+
+/// Projects a vector onto the plane of a normal and normalizes it.
+fn project(v: [f32; 3], normal: [f32; 3]) -> [f32; 3] {
+    let projected = subtract(v, scale(dot(normal, v), normal));
+    if vector_not_zero(projected) {
+        mikk_normalize(projected)
+    } else {
+        projected
+    }
+}
+
+/// Identifies vertices with identical data. Adding 0 makes -0 the same as 0.
+fn vertex_key(vertex: &Vertex) -> [u32; 8] {
+    let v = vertex;
+    [
+        v.position[0],
+        v.position[1],
+        v.position[2],
+        v.normal[0],
+        v.normal[1],
+        v.normal[2],
+        v.tex_coord[0],
+        v.tex_coord[1],
+    ]
+    .map(|x| (x + 0.0).to_bits())
+}
+
+/// Computes the tangent space of each corner of each triangle.
+fn generate(vertices: &[Vertex], input: &[[u32; 3]]) -> Vec<TangentSpace> {
+    let vertex = |corner: usize| &vertices[input[corner / 3][corner % 3] as usize];
+
+    // Corners with identical data are welded to the first of them.
+    let mut first = HashMap::new();
+    let welded: Vec<usize> = (0..input.len() * 3)
+        .map(|corner| *first.entry(vertex_key(vertex(corner))).or_insert(corner))
+        .collect();
+
+    // Degenerate triangles are moved after the others, which keep their order.
+    let is_degenerate = |t: usize| {
+        let p = |i: usize| vertex(welded[t * 3 + i]).position;
+        p(0) == p(1) || p(0) == p(2) || p(1) == p(2)
+    };
+    let (good, degenerate): (Vec<usize>, Vec<usize>) =
+        (0..input.len()).partition(|&t| !is_degenerate(t));
+
+    let mut triangles: Vec<Triangle> = good
+        .iter()
+        .map(|&original| {
+            let corners = [0, 1, 2].map(|i| welded[original * 3 + i]);
+            let [v1, v2, v3] = corners.map(|c| vertex(c).position);
+            let [t1, t2, t3] = corners.map(|c| vertex(c).tex_coord);
+            let (t21x, t21y) = (t2[0] - t1[0], t2[1] - t1[1]);
+            let (t31x, t31y) = (t3[0] - t1[0], t3[1] - t1[1]);
+            let d1 = subtract(v2, v1);
+            let d2 = subtract(v3, v1);
+            let signed_area = t21x * t31y - t21y * t31x;
+            let os = subtract(scale(t31y, d1), scale(t21y, d2));
+            let ot = [0, 1, 2].map(|i| -t31x * d1[i] + t21x * d2[i]);
+            let orientation_preserving = signed_area > 0.0;
+
+            let mut triangle = Triangle {
+                corners,
+                original,
+                neighbors: [None; 3],
+                groups: [None; 3],
+                os: [0.0; 3],
+                ot: [0.0; 3],
+                orientation_preserving,
+                group_with_any: true,
+            };
+            if not_zero(signed_area) {
+                let area = signed_area.abs();
+                let (length_os, length_ot) = (dot(os, os).sqrt(), dot(ot, ot).sqrt());
+                let sign = if orientation_preserving { 1.0 } else { -1.0 };
+                if not_zero(length_os) {
+                    triangle.os = scale(sign / length_os, os);
+                }
+                if not_zero(length_ot) {
+                    triangle.ot = scale(sign / length_ot, ot);
+                }
+                triangle.group_with_any =
+                    !(not_zero(length_os / area) && not_zero(length_ot / area));
+            }
+            triangle
+        })
+        .collect();
+
+    find_neighbors(&mut triangles);
+    let groups = build_groups(&mut triangles);
+
+    let mut spaces = vec![TangentSpace::default(); input.len() * 3];
+    for (g, group) in groups.iter().enumerate() {
+        // Each distinct set of triangles that agree with one of the group's triangles
+        // is a subgroup with its own tangent space.
+        let mut subgroups: Vec<(Vec<usize>, [f32; 3])> = Vec::new();
+        for &f in &group.triangles {
+            let corner = (0..3).find(|&i| triangles[f].groups[i] == Some(g)).unwrap();
+            let normal = vertex(triangles[f].corners[corner]).normal;
+            let os = project(triangles[f].os, normal);
+            let ot = project(triangles[f].ot, normal);
+
+            let mut members: Vec<usize> = group
+                .triangles
+                .iter()
+                .copied()
+                .filter(|&t| {
+                    let os2 = project(triangles[t].os, normal);
+                    let ot2 = project(triangles[t].ot, normal);
+                    // The reference implementation compares with the cosine of its
+                    // angular threshold, which is -1 for the default of 180 degrees.
+                    triangles[f].group_with_any
+                        || triangles[t].group_with_any
+                        || triangles[f].original == triangles[t].original
+                        || (dot(os, os2) > -1.0 && dot(ot, ot2) > -1.0)
+                })
+                .collect();
+            members.sort_unstable();
+
+            let tangent = match subgroups.iter().find(|(m, _)| *m == members) {
+                Some(&(_, tangent)) => tangent,
+                None => {
+                    let tangent = evaluate(&members, &triangles, group.vertex, &vertex);
+                    subgroups.push((members, tangent));
+                    tangent
+                }
+            };
+            spaces[triangles[f].original * 3 + corner] = TangentSpace {
+                tangent,
+                orientation_preserving: group.orientation_preserving,
+            };
+        }
+    }
+
+    // Corners of degenerate triangles use the tangent space of the first good corner
+    // they're welded to.
+    let mut first_good = HashMap::new();
+    for triangle in &triangles {
+        for (i, &corner) in triangle.corners.iter().enumerate() {
+            first_good
+                .entry(corner)
+                .or_insert(triangle.original * 3 + i);
+        }
+    }
+    for &t in &degenerate {
+        for i in 0..3 {
+            if let Some(&source) = first_good.get(&welded[t * 3 + i]) {
+                spaces[t * 3 + i] = spaces[source];
+            }
+        }
+    }
+    spaces
+}
+
+/// Pairs each edge with an edge of another triangle that goes the opposite way.
+fn find_neighbors(triangles: &mut [Triangle]) {
+    let mut edges: Vec<(usize, usize, usize, usize)> = Vec::with_capacity(triangles.len() * 3);
+    for (f, triangle) in triangles.iter().enumerate() {
+        for edge in 0..3 {
+            let i0 = triangle.corners[edge];
+            let i1 = triangle.corners[(edge + 1) % 3];
+            edges.push((i0.min(i1), i0.max(i1), f, edge));
+        }
+    }
+    edges.sort_unstable();
+
+    for i in 0..edges.len() {
+        let (i0, i1, f, edge) = edges[i];
+        if triangles[f].neighbors[edge].is_some() {
+            continue;
+        }
+        let start = triangles[f].corners[edge];
+        let end = triangles[f].corners[(edge + 1) % 3];
+        let other = edges[i + 1..]
+            .iter()
+            .take_while(|e| e.0 == i0 && e.1 == i1)
+            .find(|&&(_, _, t, other_edge)| {
+                triangles[t].corners[other_edge] == end
+                    && triangles[t].corners[(other_edge + 1) % 3] == start
+                    && triangles[t].neighbors[other_edge].is_none()
+            });
+        if let Some(&(_, _, t, other_edge)) = other {
+            triangles[f].neighbors[edge] = Some(t);
+            triangles[t].neighbors[other_edge] = Some(f);
+        }
+    }
+}
+
+/// Groups the corners of each vertex that are connected through triangles
+/// with the same orientation.
+fn build_groups(triangles: &mut [Triangle]) -> Vec<Group> {
+    let mut groups = Vec::new();
+    for f in 0..triangles.len() {
+        for i in 0..3 {
+            if triangles[f].group_with_any || triangles[f].groups[i].is_some() {
+                continue;
+            }
+            let g = groups.len();
+            groups.push(Group {
+                vertex: triangles[f].corners[i],
+                orientation_preserving: triangles[f].orientation_preserving,
+                triangles: vec![f],
+            });
+            triangles[f].groups[i] = Some(g);
+            // The right neighbor is visited after everything reachable from the left one.
+            let neighbors = [
+                triangles[f].neighbors[(i + 2) % 3],
+                triangles[f].neighbors[i],
+            ];
+            let mut unvisited: Vec<usize> = neighbors.iter().flatten().copied().collect();
+            while let Some(t) = unvisited.pop() {
+                let group = &mut groups[g];
+                let triangle = &mut triangles[t];
+                let corner = match triangle.corners.iter().position(|&c| c == group.vertex) {
+                    Some(corner) => corner,
+                    None => continue,
+                };
+                if triangle.groups[corner].is_some() {
+                    continue;
+                }
+                // The first group a triangle without a texture space joins decides its orientation.
+                if triangle.group_with_any && triangle.groups.iter().all(Option::is_none) {
+                    triangle.orientation_preserving = group.orientation_preserving;
+                }
+                if triangle.orientation_preserving != group.orientation_preserving {
+                    continue;
+                }
+                group.triangles.push(t);
+                triangle.groups[corner] = Some(g);
+                let neighbors = [
+                    triangle.neighbors[(corner + 2) % 3],
+                    triangle.neighbors[corner],
+                ];
+                unvisited.extend(neighbors.iter().flatten());
+            }
+        }
+    }
+    groups
+}
+
+/// Averages the tangents of triangles at a vertex, weighted by the angle of each triangle there.
+fn evaluate<'a>(
+    members: &[usize],
+    triangles: &[Triangle],
+    vertex: usize,
+    vertex_data: &impl Fn(usize) -> &'a Vertex,
+) -> [f32; 3] {
+    let mut sum = [0.0; 3];
+    for &f in members {
+        let triangle = &triangles[f];
+        if triangle.group_with_any {
+            continue;
+        }
+        let i = triangle.corners.iter().position(|&c| c == vertex).unwrap();
+        let normal = vertex_data(triangle.corners[i]).normal;
+        let os = project(triangle.os, normal);
+        let p0 = vertex_data(triangle.corners[(i + 2) % 3]).position;
+        let p1 = vertex_data(triangle.corners[i]).position;
+        let p2 = vertex_data(triangle.corners[(i + 1) % 3]).position;
+        let v1 = project(subtract(p0, p1), normal);
+        let v2 = project(subtract(p2, p1), normal);
+        let angle = dot(v1, v2).clamp(-1.0, 1.0).acos();
+        sum = [0, 1, 2].map(|c| sum[c] + angle * os[c]);
+    }
+    if vector_not_zero(sum) {
+        mikk_normalize(sum)
+    } else {
+        sum
+    }
+}
+
+impl GlTf {
+    /// Computes the MikkTSpace tangent of each corner of each triangle from
+    /// [GlTf::read_triangles], using the texture coordinates of the material's normal texture.
+    ///
+    /// The `w` of each tangent is the handedness of the bitangent as glTF defines it.
+    /// MikkTSpace expects texture coordinates that start at the bottom of the image, so tools
+    /// flip `v` before computing tangents for glTF. That mirrors texture space, which reverses
+    /// which triangles are orientation preserving but leaves the tangents themselves the same.
+    /// Rather than flipping `v` and losing precision, `w` is negated.
+    ///
+    /// Primitives with `KHR_draco_mesh_compression` return [AccessorError::Compressed].
+    pub fn mikktspace_tangents(
+        &self,
+        primitive: &MeshPrimitive,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<[f32; 4]>, AccessorError> {
+        crate::normals::check_uncompressed(primitive)?;
+        let positions = self.read_vec3_attribute(primitive, Semantic::Position, buffers)?;
+        let normals = self.read_vec3_attribute(primitive, Semantic::Normal, buffers)?;
+        let tex_coord = primitive
+            .material
+            .and_then(|material| self.get(material))
+            .and_then(|material| material.normal_texture.as_ref())
+            .map_or(0, |texture| texture.tex_coord as u32);
+        let semantic = Semantic::TexCoord(tex_coord);
+        let tex_coords = *primitive
+            .attributes
+            .get(&semantic)
+            .ok_or(AccessorError::MissingAttribute(semantic))?;
+        let tex_coords = self.read_accessor_f32(tex_coords, buffers)?;
+
+        let vertices: Vec<Vertex> = (0..self.vertex_count(primitive))
+            .map(|v| Vertex {
+                position: positions.get(v).copied().unwrap_or_default(),
+                normal: normals.get(v).copied().unwrap_or_default(),
+                tex_coord: [
+                    tex_coords.get(v * 2).copied().unwrap_or_default(),
+                    tex_coords.get(v * 2 + 1).copied().unwrap_or_default(),
+                ],
+            })
+            .collect();
+        let triangles = self.read_triangles(primitive, buffers)?;
+        Ok(generate(&vertices, &triangles)
+            .into_iter()
+            .map(|space| {
+                let w = if space.orientation_preserving {
+                    -1.0
+                } else {
+                    1.0
+                };
+                let [x, y, z] = space.tangent;
+                [x, y, z, w]
+            })
+            .collect())
+    }
+
+    /// Adds a `TANGENT` attribute with MikkTSpace tangents to a primitive,
+    /// replacing any it had, and returns the index of its accessor.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers, the new accessors are appended
+    /// to the first one. MikkTSpace can give the corners that share a vertex different tangents,
+    /// such as along a mirrored seam. Those vertices are split, which makes the primitive
+    /// a list of triangles. Vertices that aren't part of a triangle have the tangent `[1, 0, 0, 1]`.
+    /// Primitives with `KHR_draco_mesh_compression` are left as they are and return
+    /// [AccessorError::Compressed].
+    pub fn add_tangents(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mesh: MeshIndex,
+        primitive: usize,
+    ) -> Result<AccessorIndex, AccessorError> {
+        let original = &self.meshes[mesh.value()].primitives[primitive];
+        let corner_tangents = self.mikktspace_tangents(original, buffers)?;
+        let triangles = self.read_triangles(original, buffers)?;
+
+        let mut tangents = vec![None; self.vertex_count(original)];
+        let mut split = false;
+        for (&vertex, tangent) in triangles.iter().flatten().zip(&corner_tangents) {
+            let existing = tangents[vertex as usize].get_or_insert(*tangent);
+            split |= existing != tangent;
+        }
+        let mut tangents: Vec<[f32; 4]> = tangents
+            .into_iter()
+            .map(|t| t.unwrap_or([1.0, 0.0, 0.0, 1.0]))
+            .collect();
+
+        if split {
+            // Each distinct tangent of a vertex gets its own copy of the vertex.
+            let mut copies = HashMap::new();
+            let mut vertices = Vec::new();
+            tangents.clear();
+            let indices: Vec<u32> = triangles
+                .iter()
+                .flatten()
+                .zip(&corner_tangents)
+                .map(|(&vertex, tangent)| {
+                    *copies
+                        .entry((vertex, tangent.map(f32::to_bits)))
+                        .or_insert_with(|| {
+                            vertices.push(vertex);
+                            tangents.push(*tangent);
+                            vertices.len() as u32 - 1
+                        })
+                })
+                .collect();
+            self.copy_vertices(buffers, mesh, primitive, &vertices, Some(&indices))?;
+        }
+
+        let accessor = self.push_accessor(
+            buffers,
+            &tangents.concat(),
+            AccessorType::Vec4,
+            AccessorComponentType::Float,
+            false,
+            Some(BufferViewTarget::ArrayBuffer),
+        );
+        self.meshes[mesh.value()].primitives[primitive]
+            .attributes
+            .insert(Semantic::Tangent, accessor);
+        Ok(accessor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+
+    // Two quads with a mirrored seam at x = 1, a triangle with no area in texture space,
+    // a triangle with no area and one that uses a vertex twice.
+    const POSITIONS: [[f32; 3]; 10] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.2],
+        [0.0, 1.0, 0.0],
+        [2.0, 0.0, 0.1],
+        [2.0, 1.0, 0.0],
+        [3.0, 0.0, 0.0],
+        [3.0, 1.0, 0.0],
+        [4.0, 0.0, 0.0],
+        [5.0, 0.0, 0.0],
+    ];
+    const NORMALS: [[f32; 3]; 10] = [
+        [0.0, 0.0, 1.0],
+        [0.0, 0.19611613, 0.9805807],
+        [0.0, -0.09950372, 0.9950372],
+        [0.09950372, 0.0, 0.9950372],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+    ];
+    const TEX_COORDS: [[f32; 2]; 10] = [
+        [0.0, 1.0],
+        [1.0, 1.0],
+        [1.0, 0.0],
+        [0.0, 0.0],
+        [0.0, 1.0],
+        [0.0, 0.0],
+        [0.0, 1.0],
+        [0.0, 1.0],
+        [0.5, 0.5],
+        [0.7, 0.2],
+    ];
+    const TRIANGLES: [[u32; 3]; 7] = [
+        [0, 1, 2],
+        [0, 2, 3],
+        [1, 4, 5],
+        [1, 5, 2],
+        [4, 6, 7],
+        [6, 8, 9],
+        [3, 3, 0],
+    ];
+    /// The tangent of each corner from the reference implementation, given `1 - v`
+    /// as the texture coordinates like tools that export glTF do.
+    const REFERENCE: [[f32; 4]; 21] = [
+        [1.0, 0.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.99995077, 0.00986715, 0.0009867133, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.99995077, 0.00986715, 0.0009867133, 1.0],
+        [0.99503714, 0.0, -0.09950371, 1.0],
+        [-0.99995196, -0.00960661, 0.0019213196, -1.0],
+        [-1.0, 0.0, 0.0, -1.0],
+        [-1.0, 0.0, 0.0, -1.0],
+        [-0.99995196, -0.00960661, 0.0019213196, -1.0],
+        [-1.0, 0.0, 0.0, -1.0],
+        [-0.99980205, 0.019798059, 0.0019798025, -1.0],
+        [1.0, 0.0, 0.0, -1.0],
+        [1.0, 0.0, 0.0, -1.0],
+        [1.0, 0.0, 0.0, -1.0],
+        [0.0, 0.0, 0.0, 1.0],
+        [-1.0, 0.0, 0.0, 1.0],
+        [0.0, 0.0, 0.0, 1.0],
+        [0.99503714, 0.0, -0.09950371, 1.0],
+        [0.99503714, 0.0, -0.09950371, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+    ];
+
+    fn assert_tangents(tangents: &[[f32; 4]], expected: &[[f32; 4]], compare_w: bool) {
+        assert_eq!(tangents.len(), expected.len());
+        for (t, e) in tangents.iter().zip(expected) {
+            let close = (0..3).all(|i| (t[i] - e[i]).abs() <= 1e-6);
+            assert!(close && (!compare_w || t[3] == e[3]), "{:?} != {:?}", t, e);
+        }
+    }
+
+    #[test]
+    fn tangents_match_the_reference_implementation() {
+        let mut builder = GlTfBuilder::new();
+        let attributes = [
+            (
+                Semantic::Position,
+                builder.add_vertex_attribute(&POSITIONS.concat(), AccessorType::Vec3),
+            ),
+            (
+                Semantic::Normal,
+                builder.add_vertex_attribute(&NORMALS.concat(), AccessorType::Vec3),
+            ),
+            (
+                Semantic::TexCoord(0),
+                builder.add_vertex_attribute(&TEX_COORDS.concat(), AccessorType::Vec2),
+            ),
+        ];
+        let indices = builder.add_indices(&TRIANGLES.concat());
+        let primitive = GlTfBuilder::primitive(&attributes, Some(indices), None);
+        builder.add_mesh(None, vec![primitive]);
+        let (gltf, buffer) = builder.finish();
+        let tangents = gltf
+            .mikktspace_tangents(&gltf.meshes[0].primitives[0], &[buffer])
+            .unwrap();
+        assert_tangents(&tangents, &REFERENCE, true);
+    }
+
+    #[test]
+    fn tangents_match_the_cube_model() {
+        let (gltf, buffers) = cube();
+        let primitive = &gltf.meshes[0].primitives[0];
+        let stored = gltf
+            .read_accessor_f32(primitive.attributes[&Semantic::Tangent], &buffers)
+            .unwrap();
+        let expected: Vec<[f32; 4]> = gltf
+            .read_triangles(primitive, &buffers)
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(|&v| {
+                let t = &stored[v as usize * 4..v as usize * 4 + 4];
+                [t[0], t[1], t[2], t[3]]
+            })
+            .collect();
+        // The cube's tangents were computed without flipping `v`, so only the directions match.
+        let tangents = gltf.mikktspace_tangents(primitive, &buffers).unwrap();
+        assert_tangents(&tangents, &expected, false);
+        assert!(tangents.iter().zip(&expected).all(|(t, e)| t[3] == -e[3]));
+    }
+
+    #[test]
+    fn tangents_refuse_draco() {
+        let (mut gltf, mut buffers) = cube();
+        gltf.meshes[0].primitives[0].extensions.insert(
+            "KHR_draco_mesh_compression".to_string(),
+            kserde::ThingOwned::Null,
+        );
+        let json = gltf.to_json_formatted(None);
+        let result = gltf.add_tangents(&mut buffers, MeshIndex::new(0), 0);
+        assert!(matches!(result, Err(AccessorError::Compressed)));
+        assert_eq!(gltf.to_json_formatted(None), json);
+    }
+}
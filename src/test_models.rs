@@ -0,0 +1,82 @@
+//! The models in the `models` folder, with their buffers, for tests.
+
+// Some helpers are only used by the tests of optional features.
+#![allow(dead_code)]
+
+use crate::*;
+
+pub(crate) fn cube() -> (GlTf, Vec<Vec<u8>>) {
+    let folder = concat!(env!("CARGO_MANIFEST_DIR"), "/models/cube/");
+    let json = std::fs::read_to_string(format!("{}Cube.gltf", folder)).unwrap();
+    let gltf = GlTf::from_json(&json).unwrap();
+    let buffers = gltf
+        .buffers
+        .iter()
+        .map(|b| std::fs::read(format!("{}{}", folder, b.uri.as_ref().unwrap())).unwrap())
+        .collect();
+    (gltf, buffers)
+}
+
+pub(crate) fn fox() -> (GlTf, Vec<Vec<u8>>) {
+    let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/models/Fox.glb")).unwrap();
+    let glb = GLB::from_bytes(&data).unwrap();
+    (glb.gltf, vec![glb.binary_data.unwrap().into_owned()])
+}
+
+/// The position of every vertex of every node's mesh, in the order of the nodes.
+pub(crate) fn world_positions(gltf: &GlTf, buffers: &[Vec<u8>]) -> Vec<[f32; 3]> {
+    let world = gltf.world_matrices();
+    let mut positions = Vec::new();
+    for (node, matrix) in gltf.nodes.iter().zip(&world) {
+        let mesh = match node.mesh {
+            Some(mesh) => &gltf.meshes[mesh.value()],
+            None => continue,
+        };
+        for primitive in &mesh.primitives {
+            let local = gltf.read_vec3_attribute(primitive, Semantic::Position, buffers);
+            for p in local.unwrap() {
+                positions.push([0, 1, 2].map(|r| {
+                    matrix[r] * p[0] + matrix[4 + r] * p[1] + matrix[8 + r] * p[2] + matrix[12 + r]
+                }));
+            }
+        }
+    }
+    positions
+}
+
+pub(crate) fn assert_close(a: &[[f32; 3]], b: &[[f32; 3]], epsilon: f32) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert!(
+            (0..3).all(|i| (a[i] - b[i]).abs() <= epsilon),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+}
+
+/// The data of every attribute and morph target at each corner of each triangle of a primitive,
+/// as bits so it can be compared exactly, apart from -0 being the same as 0.
+pub(crate) fn triangle_corners(
+    gltf: &GlTf,
+    primitive: &MeshPrimitive,
+    buffers: &[Vec<u8>],
+) -> Vec<Vec<u32>> {
+    let mut vertices = vec![Vec::new(); gltf.vertex_count(primitive)];
+    for attributes in std::iter::once(&primitive.attributes).chain(&primitive.targets) {
+        for &accessor in attributes.values() {
+            let components = gltf.accessors[accessor.value()].type_.component_count();
+            let values = gltf.read_accessor_f32(accessor, buffers).unwrap();
+            for (vertex, values) in vertices.iter_mut().zip(values.chunks_exact(components)) {
+                vertex.extend(values.iter().map(|x| (x + 0.0).to_bits()));
+            }
+        }
+    }
+    let triangles = gltf.read_triangles(primitive, buffers).unwrap();
+    triangles
+        .iter()
+        .flatten()
+        .map(|&v| vertices[v as usize].clone())
+        .collect()
+}
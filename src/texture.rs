@@ -0,0 +1,188 @@
+use crate::*;
+
+/// The format of an image. Besides the JPEG and PNG of the core specification, these are the
+/// formats of the texture extensions, which are also allowed as the `mimeType` of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    /// KTX2 with Basis Universal supercompression, from `KHR_texture_basisu`.
+    Ktx2,
+    /// WebP, from `EXT_texture_webp`.
+    Webp,
+    /// DDS, from `MSFT_texture_dds`.
+    Dds,
+}
+
+impl ImageFormat {
+    /// The format with this MIME type, or `None` if it isn't known.
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        Some(match mime_type {
+            "image/jpeg" => Self::Jpeg,
+            "image/png" => Self::Png,
+            "image/ktx2" => Self::Ktx2,
+            "image/webp" => Self::Webp,
+            "image/vnd-ms.dds" => Self::Dds,
+            _ => None?,
+        })
+    }
+}
+
+impl Image {
+    /// The format of the image, from `mimeType`, or else from the media type of a data URI
+    /// or the file extension of `uri`. Returns `None` if the format isn't known.
+    pub fn format(&self) -> Option<ImageFormat> {
+        if let Some(mime_type) = &self.mime_type {
+            return match mime_type {
+                ImageMimeType::ImageJpeg => Some(ImageFormat::Jpeg),
+                ImageMimeType::ImagePng => Some(ImageFormat::Png),
+                ImageMimeType::Other(mime_type) => ImageFormat::from_mime_type(mime_type),
+            };
+        }
+        let uri = self.uri.as_ref()?;
+        if let Some(data) = uri.strip_prefix("data:") {
+            return ImageFormat::from_mime_type(data.split([';', ',']).next()?);
+        }
+        // Query strings and fragments aren't part of the file name.
+        let name = uri.split(['?', '#']).next()?;
+        let extension = name.rsplit(['.', '/']).next()?;
+        Some(match &*extension.to_ascii_lowercase() {
+            "jpeg" | "jpg" => ImageFormat::Jpeg,
+            "png" => ImageFormat::Png,
+            "ktx2" => ImageFormat::Ktx2,
+            "webp" => ImageFormat::Webp,
+            "dds" => ImageFormat::Dds,
+            _ => None?,
+        })
+    }
+}
+
+impl Texture {
+    /// The images of this texture: `source`, then the images of the texture extensions.
+    ///
+    /// The images of `KHR_texture_basisu`, `EXT_texture_webp` and `MSFT_texture_dds`
+    /// are only included when their features are enabled.
+    pub fn sources(&self) -> Vec<ImageIndex> {
+        // Without the texture extensions' features this is never extended.
+        #[allow(unused_mut)]
+        let mut sources: Vec<ImageIndex> = self.source.into_iter().collect();
+        #[cfg(feature = "KHR_texture_basisu")]
+        sources.extend(
+            self.extension::<extensions::KhrTextureBasisu>()
+                .map(|e| e.source),
+        );
+        #[cfg(feature = "EXT_texture_webp")]
+        sources.extend(
+            self.extension::<extensions::ExtTextureWebp>()
+                .and_then(|e| e.source),
+        );
+        #[cfg(feature = "MSFT_texture_dds")]
+        sources.extend(
+            self.extension::<extensions::MsftTextureDds>()
+                .and_then(|e| e.source),
+        );
+        sources
+    }
+}
+
+impl GlTf {
+    /// Picks the image to load for a texture, trying each of the formats in
+    /// `supported_formats` in order of preference.
+    ///
+    /// The format of each of the texture's images comes from [Image::format], so an image is
+    /// only picked if its `mimeType` or `uri` shows it's in a supported format.
+    /// Images of texture extensions whose features are disabled are never picked,
+    /// see [Texture::sources]. Returns `None` if no image is available in a supported format.
+    pub fn effective_source(
+        &self,
+        texture: TextureIndex,
+        supported_formats: &[ImageFormat],
+    ) -> Option<ImageIndex> {
+        let sources = self.get(texture)?.sources();
+        supported_formats.iter().find_map(|format| {
+            sources.iter().copied().find(|&source| {
+                self.get(source)
+                    .and_then(Image::format)
+                    .is_some_and(|f| f == *format)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn image(uri: &str) -> Image {
+        Image {
+            uri: Some(uri.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn image_formats() {
+        let format = |uri| image(uri).format();
+        assert_eq!(format("a/b.PNG"), Some(ImageFormat::Png));
+        assert_eq!(format("b.jpg?v=2"), Some(ImageFormat::Jpeg));
+        assert_eq!(
+            format("data:image/ktx2;base64,AAAA"),
+            Some(ImageFormat::Ktx2)
+        );
+        assert_eq!(format("texture"), None);
+        assert_eq!(format("folder.png/texture"), None);
+    }
+
+    #[test]
+    fn extension_mime_types_are_read() {
+        let gltf = GlTf::from_json(
+            r#"{
+                "asset": {"version": "2.0"},
+                "images": [
+                    {"bufferView": 0, "mimeType": "image/ktx2"},
+                    {"uri": "a.png", "mimeType": "image/webp"},
+                    {"bufferView": 0, "mimeType": "image/unknown"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let formats: Vec<_> = gltf.images.iter().map(Image::format).collect();
+        assert_eq!(
+            formats,
+            [Some(ImageFormat::Ktx2), Some(ImageFormat::Webp), None]
+        );
+        assert!(gltf
+            .to_json_formatted(None)
+            .contains(r#""mimeType":"image/ktx2""#));
+    }
+
+    #[test]
+    fn effective_source_checks_formats() {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        let png = gltf.push(image("a.png"));
+        let texture = gltf.push(Texture {
+            source: Some(png),
+            ..Default::default()
+        });
+        let source = |formats: &[ImageFormat]| gltf.effective_source(texture, formats);
+        assert_eq!(source(&[ImageFormat::Jpeg]), None);
+        assert_eq!(source(&[ImageFormat::Jpeg, ImageFormat::Png]), Some(png));
+    }
+
+    #[cfg(feature = "KHR_texture_basisu")]
+    #[test]
+    fn effective_source_prefers_extensions() {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        let png = gltf.push(image("a.png"));
+        let ktx2 = gltf.push(image("a.ktx2"));
+        let mut texture = Texture {
+            source: Some(png),
+            ..Default::default()
+        };
+        texture.set_extension(&extensions::KhrTextureBasisu::new(ktx2));
+        let texture = gltf.push(texture);
+        let formats = [ImageFormat::Ktx2, ImageFormat::Png];
+        assert_eq!(gltf.effective_source(texture, &formats), Some(ktx2));
+        assert_eq!(gltf.effective_source(texture, &formats[1..]), Some(png));
+    }
+}
@@ -0,0 +1,148 @@
+//! Node transforms as column-major 4x4 matrices, the layout glTF uses for `Node::matrix`.
+
+use crate::*;
+
+pub const IDENTITY_MATRIX: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
+/// Computes `a * b`, which applies `b` first.
+pub fn multiply_matrices(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+    let mut out = [0.0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            out[column * 4 + row] = (0..4).map(|i| a[i * 4 + row] * b[column * 4 + i]).sum();
+        }
+    }
+    out
+}
+
+/// Builds the matrix that scales, then rotates by the quaternion `[x, y, z, w]`, then translates.
+pub fn compose_matrix(translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) -> [f32; 16] {
+    let [x, y, z, w] = rotation;
+    let [sx, sy, sz] = scale;
+    [
+        (1.0 - 2.0 * (y * y + z * z)) * sx,
+        (2.0 * (x * y + z * w)) * sx,
+        (2.0 * (x * z - y * w)) * sx,
+        0.0,
+        (2.0 * (x * y - z * w)) * sy,
+        (1.0 - 2.0 * (x * x + z * z)) * sy,
+        (2.0 * (y * z + x * w)) * sy,
+        0.0,
+        (2.0 * (x * z + y * w)) * sz,
+        (2.0 * (y * z - x * w)) * sz,
+        (1.0 - 2.0 * (x * x + y * y)) * sz,
+        0.0,
+        translation[0],
+        translation[1],
+        translation[2],
+        1.0,
+    ]
+}
+
+pub(crate) fn subtract(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Scales a vector to a length of 1, unless it has no length.
+pub(crate) fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = dot(v, v).sqrt();
+    if length > 0.0 {
+        [v[0] / length, v[1] / length, v[2] / length]
+    } else {
+        v
+    }
+}
+
+/// Computes the inverse of a matrix, or `None` if it has no inverse, such as when it scales by 0.
+pub fn invert_matrix(m: &[f32; 16]) -> Option<[f32; 16]> {
+    // The inverse is the adjugate divided by the determinant. This works for either
+    // layout, as the inverse of the transpose is the transpose of the inverse.
+    let mut adjugate = [0.0; 16];
+    for i in 0..4 {
+        for j in 0..4 {
+            // The minor that excludes row `i` and column `j`.
+            let rows: Vec<usize> = (0..4).filter(|&r| r != i).collect();
+            let columns: Vec<usize> = (0..4).filter(|&c| c != j).collect();
+            let at = |r: usize, c: usize| m[columns[c] * 4 + rows[r]];
+            let minor = at(0, 0) * (at(1, 1) * at(2, 2) - at(1, 2) * at(2, 1))
+                - at(0, 1) * (at(1, 0) * at(2, 2) - at(1, 2) * at(2, 0))
+                + at(0, 2) * (at(1, 0) * at(2, 1) - at(1, 1) * at(2, 0));
+            let sign = if (i + j) % 2 == 0 { 1.0 } else { -1.0 };
+            // The adjugate is the transpose of the cofactor matrix.
+            adjugate[i * 4 + j] = sign * minor;
+        }
+    }
+    let determinant: f32 = (0..4).map(|c| m[c * 4] * adjugate[c]).sum();
+    if determinant == 0.0 || !determinant.is_finite() {
+        return None;
+    }
+    Some(adjugate.map(|value| value / determinant))
+}
+
+impl Node {
+    /// The node's transform relative to its parent, from `matrix` or else from
+    /// `translation`, `rotation` and `scale`.
+    pub fn local_matrix(&self) -> [f32; 16] {
+        match self.matrix {
+            Some(matrix) => matrix,
+            None => compose_matrix(
+                self.translation.unwrap_or([0.0; 3]),
+                self.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]),
+                self.scale.unwrap_or([1.0; 3]),
+            ),
+        }
+    }
+}
+
+impl GlTf {
+    /// The index of each node's parent, or `None` for root nodes.
+    pub fn node_parents(&self) -> Vec<Option<NodeIndex>> {
+        let mut parents = vec![None; self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate() {
+            for &child in &node.children {
+                if let Some(parent) = parents.get_mut(child.value()) {
+                    *parent = Some(Index::new(i));
+                }
+            }
+        }
+        parents
+    }
+
+    /// The transform of every node relative to the scene root.
+    pub fn world_matrices(&self) -> Vec<[f32; 16]> {
+        let parents = self.node_parents();
+        let mut world: Vec<Option<[f32; 16]>> = vec![None; self.nodes.len()];
+        for node in 0..self.nodes.len() {
+            // Walk up to the closest ancestor that is already known, then back down.
+            let mut chain = Vec::new();
+            let mut current = Some(node);
+            while let Some(i) = current {
+                if world[i].is_some() || chain.contains(&i) {
+                    break;
+                }
+                chain.push(i);
+                current = parents[i].map(|p| p.value());
+            }
+            let mut matrix = current.and_then(|i| world[i]).unwrap_or(IDENTITY_MATRIX);
+            for &i in chain.iter().rev() {
+                matrix = multiply_matrices(&matrix, &self.nodes[i].local_matrix());
+                world[i] = Some(matrix);
+            }
+        }
+        world.into_iter().map(|m| m.unwrap()).collect()
+    }
+}
@@ -0,0 +1,281 @@
+//! The triangles and lines of mesh primitives, whichever mode they're stored in.
+
+use std::collections::HashMap;
+
+use crate::*;
+
+impl GlTf {
+    /// The number of vertices of a primitive, which each of its attributes has.
+    pub fn vertex_count(&self, primitive: &MeshPrimitive) -> usize {
+        primitive
+            .attributes
+            .values()
+            .next()
+            .and_then(|&accessor| self.get(accessor))
+            .map_or(0, |accessor| accessor.count)
+    }
+
+    /// Reads the triangles of a primitive as the indices of their vertices, in the order
+    /// the specification defines for lists, strips and fans.
+    ///
+    /// Points and lines have no triangles. Triangles with vertices that don't exist are left out.
+    pub fn read_triangles(
+        &self,
+        primitive: &MeshPrimitive,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<[u32; 3]>, AccessorError> {
+        let vertex_count = self.vertex_count(primitive);
+        let vertices = match primitive.indices {
+            Some(indices) => self.read_accessor_u32(indices, buffers)?,
+            None => (0..vertex_count as u32).collect(),
+        };
+        let triangles: Vec<[u32; 3]> = match primitive.mode {
+            MeshPrimitiveMode::Triangles => vertices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect(),
+            // Every other triangle of a strip is flipped so they all face the same way.
+            MeshPrimitiveMode::TriangleStrip => (0..vertices.len().saturating_sub(2))
+                .map(|i| {
+                    let odd = i % 2;
+                    [vertices[i], vertices[i + 1 + odd], vertices[i + 2 - odd]]
+                })
+                .collect(),
+            MeshPrimitiveMode::TriangleFan => (1..vertices.len().saturating_sub(1))
+                .map(|i| [vertices[i], vertices[i + 1], vertices[0]])
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(triangles
+            .into_iter()
+            .filter(|t| t.iter().all(|&v| (v as usize) < vertex_count))
+            .collect())
+    }
+
+    /// Reads the lines of a primitive as the indices of their vertices, in the order
+    /// the specification defines for lists, strips and loops.
+    ///
+    /// Points and triangles have no lines. Lines with vertices that don't exist are left out.
+    pub fn read_lines(
+        &self,
+        primitive: &MeshPrimitive,
+        buffers: &[Vec<u8>],
+    ) -> Result<Vec<[u32; 2]>, AccessorError> {
+        let vertex_count = self.vertex_count(primitive);
+        let vertices = match primitive.indices {
+            Some(indices) => self.read_accessor_u32(indices, buffers)?,
+            None => (0..vertex_count as u32).collect(),
+        };
+        let mut lines: Vec<[u32; 2]> = match primitive.mode {
+            MeshPrimitiveMode::Lines => vertices.chunks_exact(2).map(|l| [l[0], l[1]]).collect(),
+            MeshPrimitiveMode::LineStrip | MeshPrimitiveMode::LineLoop => {
+                vertices.windows(2).map(|l| [l[0], l[1]]).collect()
+            }
+            _ => Vec::new(),
+        };
+        if let (MeshPrimitiveMode::LineLoop, [first, .., last]) = (&primitive.mode, &vertices[..]) {
+            lines.push([*last, *first]);
+        }
+        Ok(lines
+            .into_iter()
+            .filter(|l| l.iter().all(|&v| (v as usize) < vertex_count))
+            .collect())
+    }
+
+    /// Converts every primitive that's a strip, fan or loop into a list of triangles or lines
+    /// with the same winding, so renderers only need to handle lists.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers, the new indices are appended
+    /// to the first one. The vertices are left as they are. Triangles that use a vertex more than
+    /// once, which strips use to join separate strips, are left out. Primitives with
+    /// `KHR_draco_mesh_compression` are left as they are, since their indices are compressed.
+    ///
+    /// Accessors can't be empty, so if a primitive would be left without triangles or lines
+    /// this returns [AccessorError::NoTriangles] or [AccessorError::NoLines] without
+    /// changing anything.
+    pub fn convert_to_lists(&mut self, buffers: &mut Vec<Vec<u8>>) -> Result<(), AccessorError> {
+        let mut lists = Vec::new();
+        for m in 0..self.meshes.len() {
+            for p in 0..self.meshes[m].primitives.len() {
+                let primitive = &self.meshes[m].primitives[p];
+                if primitive
+                    .extensions
+                    .contains_key("KHR_draco_mesh_compression")
+                {
+                    continue;
+                }
+                let (indices, mode) = match primitive.mode {
+                    MeshPrimitiveMode::TriangleStrip | MeshPrimitiveMode::TriangleFan => {
+                        let triangles = self.read_triangles(primitive, buffers)?;
+                        let indices: Vec<u32> = triangles
+                            .into_iter()
+                            .filter(|&[a, b, c]| a != b && b != c && c != a)
+                            .flatten()
+                            .collect();
+                        if indices.is_empty() {
+                            Err(AccessorError::NoTriangles)?
+                        }
+                        (indices, MeshPrimitiveMode::Triangles)
+                    }
+                    MeshPrimitiveMode::LineStrip | MeshPrimitiveMode::LineLoop => {
+                        let indices = self.read_lines(primitive, buffers)?.concat();
+                        if indices.is_empty() {
+                            Err(AccessorError::NoLines)?
+                        }
+                        (indices, MeshPrimitiveMode::Lines)
+                    }
+                    _ => continue,
+                };
+                lists.push((m, p, indices, mode));
+            }
+        }
+        for (m, p, indices, mode) in lists {
+            let indices = self.push_indices(buffers, &indices);
+            let primitive = &mut self.meshes[m].primitives[p];
+            primitive.indices = Some(indices);
+            primitive.mode = mode;
+        }
+        Ok(())
+    }
+
+    /// Replaces the vertices of a primitive with copies of the listed vertices, and makes it
+    /// a list of triangles with the given indices of the new vertices, or without indices.
+    /// The copies are appended to the first buffer, with the same bytes as the originals.
+    pub(crate) fn copy_vertices(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mesh: MeshIndex,
+        primitive: usize,
+        vertices: &[u32],
+        indices: Option<&[u32]>,
+    ) -> Result<(), AccessorError> {
+        let mut attributes = self.meshes[mesh.value()].primitives[primitive]
+            .attributes
+            .clone();
+        let mut targets = self.meshes[mesh.value()].primitives[primitive]
+            .targets
+            .clone();
+        // An accessor can be used by several attributes, so each is only copied once.
+        let mut copied_accessors = HashMap::new();
+        for attributes in std::iter::once(&mut attributes).chain(targets.iter_mut()) {
+            for accessor in attributes.values_mut() {
+                *accessor = match copied_accessors.get(accessor) {
+                    Some(&new) => new,
+                    None => {
+                        let new = self.copy_accessor_elements(
+                            buffers,
+                            *accessor,
+                            vertices,
+                            Some(BufferViewTarget::ArrayBuffer),
+                        )?;
+                        copied_accessors.insert(*accessor, new);
+                        new
+                    }
+                };
+            }
+        }
+
+        let indices = indices.map(|indices| self.push_indices(buffers, indices));
+        let primitive = &mut self.meshes[mesh.value()].primitives[primitive];
+        primitive.attributes = attributes;
+        primitive.targets = targets;
+        primitive.indices = indices;
+        primitive.mode = MeshPrimitiveMode::Triangles;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+
+    /// A primitive of a row of squares, with the given mode and indices.
+    fn squares(mode: MeshPrimitiveMode, indices: &[u32]) -> (GlTf, Vec<Vec<u8>>) {
+        let mut builder = GlTfBuilder::new();
+        let positions: Vec<f32> = (0..8)
+            .flat_map(|v| [(v / 2) as f32, (v % 2) as f32, 0.0])
+            .collect();
+        let positions = builder.add_vertex_attribute(&positions, AccessorType::Vec3);
+        let indices = builder.add_indices(indices);
+        let primitive = MeshPrimitive {
+            mode,
+            ..GlTfBuilder::primitive(&[(Semantic::Position, positions)], Some(indices), None)
+        };
+        let mesh = builder.add_mesh(None, vec![primitive]);
+        let node = builder.add_node(None, Some(mesh), &[]);
+        builder.add_scene(None, &[node]);
+        let (gltf, buffer) = builder.finish();
+        (gltf, vec![buffer])
+    }
+
+    #[test]
+    fn lists_round_trip() {
+        let (original, _) = cube();
+        let (mut gltf, mut buffers) = cube();
+        gltf.convert_to_lists(&mut buffers).unwrap();
+        assert_eq!(
+            gltf.to_json_formatted(None),
+            original.to_json_formatted(None)
+        );
+
+        // Two strips joined by repeating the end of the first and the start of the second.
+        let (mut gltf, mut buffers) = squares(
+            MeshPrimitiveMode::TriangleStrip,
+            &[0, 1, 2, 3, 3, 4, 4, 5, 6, 7],
+        );
+        let primitive = &gltf.meshes[0].primitives[0];
+        let triangles: Vec<[u32; 3]> = gltf
+            .read_triangles(primitive, &buffers)
+            .unwrap()
+            .into_iter()
+            .filter(|&[a, b, c]| a != b && b != c && c != a)
+            .collect();
+        assert_eq!(triangles.len(), 4);
+        let positions = world_positions(&gltf, &buffers);
+        gltf.convert_to_lists(&mut buffers).unwrap();
+        let primitive = &gltf.meshes[0].primitives[0];
+        assert!(matches!(primitive.mode, MeshPrimitiveMode::Triangles));
+        assert_eq!(gltf.read_triangles(primitive, &buffers).unwrap(), triangles);
+        assert_close(&world_positions(&gltf, &buffers), &positions, 0.0);
+    }
+
+    #[test]
+    fn lists_need_triangles() {
+        let (mut gltf, mut buffers) = squares(MeshPrimitiveMode::TriangleFan, &[0, 1, 1, 1]);
+        let accessors = gltf.accessors.len();
+        let result = gltf.convert_to_lists(&mut buffers);
+        assert!(matches!(result, Err(AccessorError::NoTriangles)));
+        assert_eq!(gltf.accessors.len(), accessors);
+        assert!(matches!(
+            gltf.meshes[0].primitives[0].mode,
+            MeshPrimitiveMode::TriangleFan
+        ));
+    }
+
+    #[test]
+    fn copied_vertices_keep_their_bytes() {
+        let (mut gltf, mut buffers) = squares(MeshPrimitiveMode::Triangles, &[0, 1, 2, 2, 1, 3]);
+        // Integers above 2^24 can't be stored exactly as floats.
+        let ids: Vec<u8> = (0..8u32)
+            .flat_map(|i| (i + (1 << 24) + 1).to_le_bytes())
+            .collect();
+        let view = gltf.push_buffer_view(&mut buffers, &ids, None, None);
+        let ids = gltf.push(Accessor {
+            buffer_view: Some(view),
+            ..Accessor::new(AccessorComponentType::UnsignedInt, 8, AccessorType::Scalar)
+        });
+        let id = Semantic::Custom("_ID".to_string());
+        gltf.meshes[0].primitives[0]
+            .attributes
+            .insert(id.clone(), ids);
+
+        gltf.copy_vertices(&mut buffers, MeshIndex::new(0), 0, &[3, 2, 1], None)
+            .unwrap();
+        let copied = gltf.meshes[0].primitives[0].attributes[&id];
+        assert_eq!(
+            gltf.read_accessor_u32(copied, &buffers).unwrap(),
+            [3, 2, 1].map(|i| i + (1 << 24) + 1)
+        );
+    }
+}
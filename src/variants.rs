@@ -0,0 +1,120 @@
+//! Material variants of mesh primitives with `KHR_materials_variants`.
+
+use crate::extensions::khr_materials_variants::*;
+use crate::*;
+
+impl GlTf {
+    /// The material variants defined in the root extension, which mappings refer to by index.
+    pub fn variants(&self) -> Vec<KhrMaterialsVariantsVariant> {
+        self.extension::<KhrMaterialsVariantsGlTf>()
+            .map_or(Vec::new(), |e| e.variants)
+    }
+
+    /// Adds a variant to the root extension and returns its index.
+    pub fn add_variant(&mut self, name: &str) -> Index<KhrMaterialsVariantsVariant> {
+        let mut extension = self
+            .extension::<KhrMaterialsVariantsGlTf>()
+            .unwrap_or_else(|| KhrMaterialsVariantsGlTf::new(Vec::new()));
+        extension
+            .variants
+            .push(KhrMaterialsVariantsVariant::new(name.to_string()));
+        self.set_extension(&extension);
+        self.add_extension_used(KhrMaterialsVariantsGlTf::NAME, false);
+        Index::new(extension.variants.len() - 1)
+    }
+}
+
+impl MeshPrimitive {
+    /// The material to use when a variant is selected, or the primitive's own material
+    /// if the variant isn't mapped or no variant is selected.
+    pub fn effective_material(
+        &self,
+        variant: Option<Index<KhrMaterialsVariantsVariant>>,
+    ) -> Option<MaterialIndex> {
+        variant
+            .and_then(|variant| {
+                self.extension::<KhrMaterialsVariantsMeshPrimitive>()?
+                    .mappings
+                    .into_iter()
+                    .find(|m| m.variants.contains(&variant))
+            })
+            .map_or(self.material, |m| Some(m.material))
+    }
+
+    /// Maps a variant to a material, replacing any previous mapping of the variant.
+    ///
+    /// Variants that use the same material share a mapping.
+    pub fn set_variant_material(
+        &mut self,
+        variant: Index<KhrMaterialsVariantsVariant>,
+        material: MaterialIndex,
+    ) {
+        let mut extension = self
+            .extension::<KhrMaterialsVariantsMeshPrimitive>()
+            .unwrap_or_else(|| KhrMaterialsVariantsMeshPrimitive::new(Vec::new()));
+        // A variant can only be in one mapping.
+        for mapping in &mut extension.mappings {
+            mapping.variants.retain(|&v| v != variant);
+        }
+        extension.mappings.retain(|m| !m.variants.is_empty());
+
+        match extension
+            .mappings
+            .iter_mut()
+            .find(|m| m.material == material)
+        {
+            Some(mapping) => mapping.variants.push(variant),
+            None => extension
+                .mappings
+                .push(KhrMaterialsVariantsMapping::new(vec![variant], material)),
+        }
+        self.set_extension(&extension);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::extensions::khr_materials_variants::*;
+    use crate::*;
+
+    #[test]
+    fn variants_fall_back_to_the_default_material() {
+        let mut gltf = GlTf::new(Asset::new("2.0".to_string()));
+        let day = gltf.add_variant("day");
+        let dusk = gltf.add_variant("dusk");
+        let night = gltf.add_variant("night");
+        let names: Vec<_> = gltf.variants().into_iter().map(|v| v.name).collect();
+        assert_eq!(names, ["day", "dusk", "night"]);
+        assert_eq!(
+            gltf.extensions_used,
+            [KhrMaterialsVariantsGlTf::NAME.to_string()]
+        );
+
+        let (default, light, dark) = (Index::new(0), Index::new(1), Index::new(2));
+        let mut primitive = MeshPrimitive {
+            material: Some(default),
+            ..MeshPrimitive::new(Default::default())
+        };
+        assert_eq!(primitive.effective_material(Some(day)), Some(default));
+        primitive.set_variant_material(day, light);
+        primitive.set_variant_material(dusk, light);
+        primitive.set_variant_material(night, dark);
+        assert_eq!(primitive.effective_material(Some(dusk)), Some(light));
+        assert_eq!(primitive.effective_material(None), Some(default));
+
+        // Moving a variant to another material takes it out of its old mapping.
+        primitive.set_variant_material(day, dark);
+        assert_eq!(primitive.effective_material(Some(day)), Some(dark));
+        let mappings = primitive
+            .extension::<KhrMaterialsVariantsMeshPrimitive>()
+            .unwrap()
+            .mappings;
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0].variants, [dusk]);
+        assert_eq!(mappings[1].variants, [night, day]);
+
+        // Without a mapping for the variant, the primitive's own material is used.
+        let unmapped = gltf.add_variant("unmapped");
+        assert_eq!(primitive.effective_material(Some(unmapped)), Some(default));
+    }
+}
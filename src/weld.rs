@@ -0,0 +1,224 @@
+//! Merging vertices with identical data, which also gives unindexed primitives indices.
+
+use std::collections::HashMap;
+
+use crate::*;
+
+#[derive(Debug, Clone, Default)]
+pub struct WeldOptions {
+    /// How far apart positions can be in each axis for their vertices to be merged.
+    /// Every other attribute has to be identical.
+    pub position_epsilon: f32,
+}
+
+/// The cell of the grid with the size of the epsilon that a position is in.
+fn cell(position: &[f32], epsilon: f32) -> [i64; 3] {
+    [0, 1, 2].map(|i| (position[i] / epsilon).floor() as i64)
+}
+
+impl GlTf {
+    /// Merges the vertices of a primitive whose attributes and morph target displacements
+    /// are the same, and returns the number of vertices left.
+    ///
+    /// `buffers` holds the data for each of the glTF's buffers, the new accessors are appended
+    /// to the first one. The primitive gets indices, which are 16 bit unless there are too many
+    /// vertices, and attributes with only the first of each set of merged vertices.
+    /// Its mode is kept, so indices of points, lines and triangles all work.
+    /// Primitives with `KHR_draco_mesh_compression` are left as they are, since their vertices
+    /// are compressed.
+    pub fn weld_vertices(
+        &mut self,
+        buffers: &mut Vec<Vec<u8>>,
+        mesh: MeshIndex,
+        primitive: usize,
+        options: &WeldOptions,
+    ) -> Result<usize, AccessorError> {
+        let original = &self.meshes[mesh.value()].primitives[primitive];
+        let vertex_count = self.vertex_count(original);
+        if original
+            .extensions
+            .contains_key("KHR_draco_mesh_compression")
+        {
+            return Ok(vertex_count);
+        }
+        let epsilon = options.position_epsilon;
+
+        // The data of each vertex, apart from its position when it's compared with the epsilon.
+        let mut keys = vec![Vec::new(); vertex_count];
+        let mut positions = Vec::new();
+        let all_attributes = std::iter::once(&original.attributes).chain(&original.targets);
+        for (a, attributes) in all_attributes.enumerate() {
+            for (semantic, &accessor) in attributes {
+                let values = self.read_accessor_f32(accessor, buffers)?;
+                if a == 0 && *semantic == Semantic::Position && epsilon > 0.0 {
+                    positions = values;
+                    continue;
+                }
+                let components = self.accessors[accessor.value()].type_.component_count();
+                for (key, vertex) in keys.iter_mut().zip(values.chunks_exact(components)) {
+                    // Adding 0 makes -0 the same as 0.
+                    key.extend(vertex.iter().map(|x| (x + 0.0).to_bits()));
+                }
+            }
+        }
+
+        let mut remap = Vec::with_capacity(vertex_count);
+        let mut vertices = Vec::new();
+        let mut exact = HashMap::new();
+        let mut cells: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+        for (v, key) in keys.iter().enumerate() {
+            let welded = match positions.get(v * 3..v * 3 + 3) {
+                // Positions close to each other can be in neighboring cells.
+                Some(position) => {
+                    let [x, y, z] = cell(position, epsilon);
+                    let neighbors = (x - 1..=x + 1).flat_map(|x| {
+                        (y - 1..=y + 1).flat_map(move |y| (z - 1..=z + 1).map(move |z| [x, y, z]))
+                    });
+                    let existing = neighbors
+                        .filter_map(|c| cells.get(&c))
+                        .flatten()
+                        .copied()
+                        .find(|&w| {
+                            let other = vertices[w as usize] as usize;
+                            (0..3)
+                                .all(|i| (position[i] - positions[other * 3 + i]).abs() <= epsilon)
+                                && keys[other] == *key
+                        });
+                    existing.unwrap_or_else(|| {
+                        cells
+                            .entry([x, y, z])
+                            .or_default()
+                            .push(vertices.len() as u32);
+                        vertices.push(v as u32);
+                        vertices.len() as u32 - 1
+                    })
+                }
+                None => *exact.entry(key).or_insert_with(|| {
+                    vertices.push(v as u32);
+                    vertices.len() as u32 - 1
+                }),
+            };
+            remap.push(welded);
+        }
+
+        let indices = match original.indices {
+            Some(indices) => self.read_accessor_u32(indices, buffers)?,
+            None => (0..vertex_count as u32).collect(),
+        };
+        // Indices of vertices that don't exist still refer to ones that don't.
+        let indices: Vec<u32> = indices
+            .into_iter()
+            .map(|i| match remap.get(i as usize) {
+                Some(&welded) => welded,
+                None => i - vertex_count as u32 + vertices.len() as u32,
+            })
+            .collect();
+        // Copying the vertices makes the primitive a list of triangles.
+        let mode = original.mode.clone();
+        self.copy_vertices(buffers, mesh, primitive, &vertices, Some(&indices))?;
+        self.meshes[mesh.value()].primitives[primitive].mode = mode;
+        Ok(vertices.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_models::*;
+    use crate::*;
+
+    /// Welds a list of triangles with these positions, returning the positions left
+    /// and the indices.
+    fn weld(positions: &[[f32; 3]], epsilon: f32) -> (Vec<[f32; 3]>, Vec<u32>) {
+        let mut builder = GlTfBuilder::new();
+        let accessor = builder.add_vertex_attribute(&positions.concat(), AccessorType::Vec3);
+        let primitive = GlTfBuilder::primitive(&[(Semantic::Position, accessor)], None, None);
+        let mesh = builder.add_mesh(None, vec![primitive]);
+        let (mut gltf, buffer) = builder.finish();
+        let mut buffers = vec![buffer];
+        let options = WeldOptions {
+            position_epsilon: epsilon,
+        };
+        gltf.weld_vertices(&mut buffers, mesh, 0, &options).unwrap();
+        let primitive = &gltf.meshes[0].primitives[0];
+        let positions = gltf.read_vec3_attribute(primitive, Semantic::Position, &buffers);
+        let indices = gltf.read_accessor_u32(primitive.indices.unwrap(), &buffers);
+        (positions.unwrap(), indices.unwrap())
+    }
+
+    #[test]
+    fn welding_within_epsilon_across_cells() {
+        // Each axis of the first two positions is in a different cell of the grid.
+        let positions = [[0.29, -0.01, 1.0], [0.31, 0.01, 1.0], [5.0, 5.0, 5.0]];
+        let (welded, indices) = weld(&positions, 0.1);
+        assert_eq!(welded, [positions[0], positions[2]]);
+        assert_eq!(indices, [0, 0, 1]);
+    }
+
+    #[test]
+    fn welding_outside_epsilon() {
+        let positions = [[0.0, 0.0, 0.0], [0.11, 0.0, 0.0], [0.0, -0.11, 0.0]];
+        let (welded, indices) = weld(&positions, 0.1);
+        assert_eq!(welded, positions);
+        assert_eq!(indices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn welding_skips_draco() {
+        let (mut gltf, mut buffers) = cube();
+        gltf.meshes[0].primitives[0].extensions.insert(
+            "KHR_draco_mesh_compression".to_string(),
+            kserde::ThingOwned::Null,
+        );
+        let json = gltf.to_json_formatted(None);
+        let vertex_count = gltf.vertex_count(&gltf.meshes[0].primitives[0]);
+        let welded = gltf
+            .weld_vertices(&mut buffers, MeshIndex::new(0), 0, &WeldOptions::default())
+            .unwrap();
+        assert_eq!(welded, vertex_count);
+        assert_eq!(gltf.to_json_formatted(None), json);
+    }
+
+    #[test]
+    fn welding_keeps_every_corner() {
+        // Copying each corner of the cube's triangles and welding them gives the vertices
+        // welding the cube itself does.
+        let (mut gltf, mut buffers) = cube();
+        let mesh = MeshIndex::new(0);
+        let options = WeldOptions::default();
+        let vertex_count = gltf
+            .clone()
+            .weld_vertices(&mut buffers.clone(), mesh, 0, &options)
+            .unwrap();
+        let original = &gltf.meshes[0].primitives[0];
+        assert!(vertex_count < gltf.vertex_count(original));
+        let corners = triangle_corners(&gltf, original, &buffers);
+        let triangles = gltf.read_triangles(original, &buffers).unwrap().concat();
+        gltf.copy_vertices(&mut buffers, mesh, 0, &triangles, None)
+            .unwrap();
+        assert_eq!(
+            gltf.vertex_count(&gltf.meshes[0].primitives[0]),
+            corners.len()
+        );
+
+        let welded = gltf.weld_vertices(&mut buffers, mesh, 0, &options).unwrap();
+        assert_eq!(welded, vertex_count);
+        let primitive = &gltf.meshes[0].primitives[0];
+        assert_eq!(triangle_corners(&gltf, primitive, &buffers), corners);
+        let indices = &gltf.accessors[primitive.indices.unwrap().value()];
+        assert!(matches!(
+            indices.component_type,
+            AccessorComponentType::UnsignedShort
+        ));
+
+        let (mut gltf, mut buffers) = fox();
+        let mesh = gltf.nodes.iter().find_map(|n| n.mesh).unwrap();
+        let original = &gltf.meshes[mesh.value()].primitives[0];
+        let vertex_count = gltf.vertex_count(original);
+        let corners = triangle_corners(&gltf, original, &buffers);
+        let welded = gltf.weld_vertices(&mut buffers, mesh, 0, &options).unwrap();
+        assert!(welded <= vertex_count);
+        let primitive = &gltf.meshes[mesh.value()].primitives[0];
+        assert_eq!(gltf.vertex_count(primitive), welded);
+        assert_eq!(triangle_corners(&gltf, primitive, &buffers), corners);
+    }
+}